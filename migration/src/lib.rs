@@ -8,6 +8,11 @@ mod m20220810_132250_create_asset_transfer;
 mod m20220810_132253_create_coloring;
 mod m20220810_132256_create_transfer;
 mod m20221128_182236_rename_rgb21_to_rgb121;
+mod m20230110_100000_add_transfer_consignment_key;
+mod m20230116_090000_add_transfer_proxy_url;
+mod m20230120_110000_add_transfer_transport_endpoints;
+mod m20230125_100000_add_batch_transfer_min_confirmations;
+mod m20230201_100000_add_transfer_recipient_id;
 
 pub struct Migrator;
 
@@ -23,6 +28,11 @@ impl MigratorTrait for Migrator {
             Box::new(m20220810_132253_create_coloring::Migration),
             Box::new(m20220810_132256_create_transfer::Migration),
             Box::new(m20221128_182236_rename_rgb21_to_rgb121::Migration),
+            Box::new(m20230110_100000_add_transfer_consignment_key::Migration),
+            Box::new(m20230116_090000_add_transfer_proxy_url::Migration),
+            Box::new(m20230120_110000_add_transfer_transport_endpoints::Migration),
+            Box::new(m20230125_100000_add_batch_transfer_min_confirmations::Migration),
+            Box::new(m20230201_100000_add_transfer_recipient_id::Migration),
         ]
     }
 }
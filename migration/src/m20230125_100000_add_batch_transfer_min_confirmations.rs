@@ -0,0 +1,40 @@
+use super::m20220810_132240_create_batch_transfer::BatchTransfer;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230125_100000_add_batch_transfer_min_confirmations"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BatchTransfer::Table)
+                    .add_column(
+                        ColumnDef::new(Alias::new("min_confirmations"))
+                            .small_unsigned()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BatchTransfer::Table)
+                    .drop_column(Alias::new("min_confirmations"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
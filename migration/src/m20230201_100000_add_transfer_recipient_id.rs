@@ -0,0 +1,35 @@
+use super::m20220810_132256_create_transfer::Transfer;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230201_100000_add_transfer_recipient_id"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfer::Table)
+                    .add_column(ColumnDef::new(Alias::new("recipient_id")).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfer::Table)
+                    .drop_column(Alias::new("recipient_id"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -1,7 +1,9 @@
 #![allow(clippy::too_many_arguments)]
 
 use std::collections::HashMap;
-use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+
+use rgb_lib::wallet::TransferListener;
 
 uniffi_macros::include_scaffolding!("rgb-lib");
 
@@ -9,24 +11,39 @@ type AssetRgb20 = rgb_lib::wallet::AssetRgb20;
 type AssetRgb121 = rgb_lib::wallet::AssetRgb121;
 type AssetType = rgb_lib::wallet::AssetType;
 type Assets = rgb_lib::wallet::Assets;
+type AutoRefresh = rgb_lib::wallet::AutoRefresh;
 type Balance = rgb_lib::wallet::Balance;
+type InputSelection = rgb_lib::wallet::InputSelection;
 type InvoiceData = rgb_lib::wallet::InvoiceData;
 type BitcoinNetwork = rgb_lib::BitcoinNetwork;
 type BlindData = rgb_lib::wallet::BlindData;
+type BtcRecipient = rgb_lib::wallet::BtcRecipient;
+type CpfpResult = rgb_lib::wallet::CpfpResult;
+type CreateUtxosResult = rgb_lib::wallet::CreateUtxosResult;
 type DatabaseType = rgb_lib::wallet::DatabaseType;
+type DrainToResult = rgb_lib::wallet::DrainToResult;
 type RgbLibInvoice = rgb_lib::wallet::Invoice;
 type Keys = rgb_lib::keys::Keys;
 type Media = rgb_lib::wallet::Media;
 type Metadata = rgb_lib::wallet::Metadata;
+type NetworkPolicy = rgb_lib::wallet::NetworkPolicy;
 type Online = rgb_lib::wallet::Online;
 type Outpoint = rgb_lib::wallet::Outpoint;
+type ProxyAuth = rgb_lib::wallet::ProxyAuth;
+type ProxyOptions = rgb_lib::wallet::ProxyOptions;
 type Recipient = rgb_lib::wallet::Recipient;
+type RefreshedTransfer = rgb_lib::wallet::RefreshedTransfer;
+type RetriedTransfer = rgb_lib::wallet::RetriedTransfer;
 type RgbAllocation = rgb_lib::wallet::RgbAllocation;
 type RgbLibBlindedUTXO = rgb_lib::wallet::BlindedUTXO;
 type RgbLibError = rgb_lib::Error;
 type RgbLibWallet = rgb_lib::wallet::Wallet;
+type SendOptions = rgb_lib::wallet::SendOptions;
+type SendPreview = rgb_lib::wallet::SendPreview;
+type SendResult = rgb_lib::wallet::SendResult;
 type Transfer = rgb_lib::wallet::Transfer;
 type TransferStatus = rgb_lib::wallet::TransferStatus;
+type TransferStatusChange = rgb_lib::wallet::TransferStatusChange;
 type Unspent = rgb_lib::wallet::Unspent;
 type Utxo = rgb_lib::wallet::Utxo;
 type WalletData = rgb_lib::wallet::WalletData;
@@ -82,13 +99,15 @@ impl Invoice {
 }
 
 struct Wallet {
-    wallet_mutex: Mutex<RgbLibWallet>,
+    wallet_mutex: Arc<Mutex<RgbLibWallet>>,
+    auto_refresh: Mutex<Option<AutoRefresh>>,
 }
 
 impl Wallet {
     fn new(wallet_data: WalletData) -> Result<Self, RgbLibError> {
         Ok(Wallet {
-            wallet_mutex: Mutex::new(RgbLibWallet::new(wallet_data)?),
+            wallet_mutex: Arc::new(Mutex::new(RgbLibWallet::new(wallet_data)?)),
+            auto_refresh: Mutex::new(None),
         })
     }
 
@@ -96,13 +115,69 @@ impl Wallet {
         self.wallet_mutex.lock().expect("wallet")
     }
 
+    fn abort_send(&self, txid: String) -> Result<(), RgbLibError> {
+        self._get_wallet().abort_send(txid)
+    }
+
     fn blind(
         &self,
         asset_id: Option<String>,
         amount: Option<u64>,
         duration_seconds: Option<u32>,
+        transport_endpoints: Vec<String>,
+        min_confirmations: Option<u8>,
     ) -> Result<BlindData, RgbLibError> {
-        self._get_wallet().blind(asset_id, amount, duration_seconds)
+        self._get_wallet().blind(
+            asset_id,
+            amount,
+            duration_seconds,
+            transport_endpoints,
+            min_confirmations,
+        )
+    }
+
+    fn bump_fee(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<SendResult, RgbLibError> {
+        self._get_wallet().bump_fee(online, txid, fee_rate)
+    }
+
+    fn bump_fee_begin(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet().bump_fee_begin(online, txid, fee_rate)
+    }
+
+    fn bump_fee_end(&self, online: Online, signed_psbt: String) -> Result<SendResult, RgbLibError> {
+        self._get_wallet().bump_fee_end(online, signed_psbt)
+    }
+
+    fn cpfp(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<CpfpResult, RgbLibError> {
+        self._get_wallet().cpfp(online, txid, fee_rate)
+    }
+
+    fn cpfp_begin(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet().cpfp_begin(online, txid, fee_rate)
+    }
+
+    fn cpfp_end(&self, online: Online, signed_psbt: String) -> Result<CpfpResult, RgbLibError> {
+        self._get_wallet().cpfp_end(online, signed_psbt)
     }
 
     fn create_utxos(
@@ -111,8 +186,10 @@ impl Wallet {
         up_to: bool,
         num: Option<u8>,
         size: Option<u32>,
-    ) -> Result<u8, RgbLibError> {
-        self._get_wallet().create_utxos(online, up_to, num, size)
+        fee_rate: Option<f32>,
+    ) -> Result<CreateUtxosResult, RgbLibError> {
+        self._get_wallet()
+            .create_utxos(online, up_to, num, size, fee_rate)
     }
 
     fn create_utxos_begin(
@@ -121,12 +198,17 @@ impl Wallet {
         up_to: bool,
         num: Option<u8>,
         size: Option<u32>,
+        fee_rate: Option<f32>,
     ) -> Result<String, RgbLibError> {
         self._get_wallet()
-            .create_utxos_begin(online, up_to, num, size)
+            .create_utxos_begin(online, up_to, num, size, fee_rate)
     }
 
-    fn create_utxos_end(&self, online: Online, signed_psbt: String) -> Result<u8, RgbLibError> {
+    fn create_utxos_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<CreateUtxosResult, RgbLibError> {
         self._get_wallet().create_utxos_end(online, signed_psbt)
     }
 
@@ -143,8 +225,10 @@ impl Wallet {
         online: Online,
         address: String,
         destroy_assets: bool,
-    ) -> Result<String, RgbLibError> {
-        self._get_wallet().drain_to(online, address, destroy_assets)
+        fee_rate: Option<f32>,
+    ) -> Result<DrainToResult, RgbLibError> {
+        self._get_wallet()
+            .drain_to(online, address, destroy_assets, fee_rate)
     }
 
     fn drain_to_begin(
@@ -152,15 +236,42 @@ impl Wallet {
         online: Online,
         address: String,
         destroy_assets: bool,
+        fee_rate: Option<f32>,
     ) -> Result<String, RgbLibError> {
         self._get_wallet()
-            .drain_to_begin(online, address, destroy_assets)
+            .drain_to_begin(online, address, destroy_assets, fee_rate)
     }
 
-    fn drain_to_end(&self, online: Online, signed_psbt: String) -> Result<String, RgbLibError> {
+    fn drain_to_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<DrainToResult, RgbLibError> {
         self._get_wallet().drain_to_end(online, signed_psbt)
     }
 
+    fn estimate_fee(&self, online: Online, target_blocks: u16) -> Result<f32, RgbLibError> {
+        self._get_wallet().estimate_fee(online, target_blocks)
+    }
+
+    fn extend_blind_expiration(
+        &self,
+        invoice: String,
+        duration_seconds: Option<u32>,
+    ) -> Result<BlindData, RgbLibError> {
+        self._get_wallet()
+            .extend_blind_expiration(invoice, duration_seconds)
+    }
+
+    fn extend_send_expiration(
+        &self,
+        txid: String,
+        duration_seconds: Option<u32>,
+    ) -> Result<Option<i64>, RgbLibError> {
+        self._get_wallet()
+            .extend_send_expiration(txid, duration_seconds)
+    }
+
     fn fail_transfers(
         &self,
         online: Online,
@@ -191,10 +302,17 @@ impl Wallet {
         &self,
         skip_consistency_check: bool,
         electrum_url: String,
-        proxy_url: String,
+        proxy_urls: Vec<String>,
+        proxy_options: Option<ProxyOptions>,
+        network_policy: Option<NetworkPolicy>,
     ) -> Result<Online, RgbLibError> {
-        self._get_wallet()
-            .go_online(skip_consistency_check, electrum_url, proxy_url)
+        self._get_wallet().go_online(
+            skip_consistency_check,
+            electrum_url,
+            proxy_urls,
+            proxy_options,
+            network_policy,
+        )
     }
 
     fn issue_asset_rgb20(
@@ -242,17 +360,37 @@ impl Wallet {
         self._get_wallet().list_unspents(settled_only)
     }
 
-    fn refresh(&self, online: Online, asset_id: Option<String>) -> Result<(), RgbLibError> {
+    fn preview_send(
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        options: SendOptions,
+    ) -> Result<SendPreview, RgbLibError> {
+        self._get_wallet()
+            .preview_send(online, recipient_map, options)
+    }
+
+    fn refresh(
+        &self,
+        online: Online,
+        asset_id: Option<String>,
+    ) -> Result<Vec<RefreshedTransfer>, RgbLibError> {
         self._get_wallet().refresh(online, asset_id)
     }
 
+    fn remove_transfer_listener(&self) {
+        self._get_wallet().remove_transfer_listener()
+    }
+
     fn send(
         &self,
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
-    ) -> Result<String, RgbLibError> {
-        self._get_wallet().send(online, recipient_map, donation)
+        options: SendOptions,
+    ) -> Result<SendResult, RgbLibError> {
+        self._get_wallet()
+            .send(online, recipient_map, donation, options)
     }
 
     fn send_begin(
@@ -260,14 +398,32 @@ impl Wallet {
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
+        options: SendOptions,
     ) -> Result<String, RgbLibError> {
         self._get_wallet()
-            .send_begin(online, recipient_map, donation)
+            .send_begin(online, recipient_map, donation, options)
     }
 
-    fn send_end(&self, online: Online, signed_psbt: String) -> Result<String, RgbLibError> {
+    fn send_end(&self, online: Online, signed_psbt: String) -> Result<SendResult, RgbLibError> {
         self._get_wallet().send_end(online, signed_psbt)
     }
+
+    fn set_transfer_listener(&self, listener: Box<dyn TransferListener>) {
+        self._get_wallet().set_transfer_listener(listener)
+    }
+
+    fn start_auto_refresh(&self, online: Online, interval_seconds: u32) -> Result<(), RgbLibError> {
+        let auto_refresh = AutoRefresh::start(self.wallet_mutex.clone(), online, interval_seconds)?;
+        *self.auto_refresh.lock().expect("auto-refresh") = Some(auto_refresh);
+        Ok(())
+    }
+
+    fn stop_auto_refresh(&self) {
+        let auto_refresh = self.auto_refresh.lock().expect("auto-refresh").take();
+        if let Some(mut auto_refresh) = auto_refresh {
+            auto_refresh.stop();
+        }
+    }
 }
 
 uniffi::deps::static_assertions::assert_impl_all!(Wallet: Sync, Send);
@@ -0,0 +1,75 @@
+//! Consignment encryption
+//!
+//! Consignments and the media they reference are posted to the proxy encrypted with
+//! ChaCha20-Poly1305, using a key generated by the recipient when blinding and shared with the
+//! sender via the invoice. Encrypted payloads start with a magic prefix followed by the nonce, so
+//! they can be told apart from the cleartext data posted by clients not supporting encryption.
+
+use bitcoin::hashes::hex::{FromHex, ToHex};
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::error::{Error, InternalError};
+
+const ENCRYPTED_MAGIC: &[u8] = b"RGBLIBENC1";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+fn _parse_key(key: &str) -> Result<Key, Error> {
+    let bytes =
+        Vec::<u8>::from_hex(key).map_err(|e| Error::InvalidConsignmentKey(e.to_string()))?;
+    if bytes.len() != KEY_LEN {
+        return Err(Error::InvalidConsignmentKey(format!(
+            "expected {} bytes, got {}",
+            KEY_LEN,
+            bytes.len()
+        )));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// Generate a new random consignment key, hex-encoded
+pub(crate) fn generate_key() -> String {
+    let mut key = [0u8; KEY_LEN];
+    thread_rng().fill_bytes(&mut key);
+    key.to_hex()
+}
+
+/// Check the provided consignment key is valid
+pub(crate) fn check_key(key: &str) -> Result<(), Error> {
+    _parse_key(key).map(|_| ())
+}
+
+/// Whether the provided data has been produced by [`encrypt`]
+pub(crate) fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// Encrypt `plaintext` with the provided consignment key
+pub(crate) fn encrypt(key: &str, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let cipher = ChaCha20Poly1305::new(&_parse_key(key)?);
+    let mut nonce = [0u8; NONCE_LEN];
+    thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| InternalError::Unexpected)?;
+    let mut data = ENCRYPTED_MAGIC.to_vec();
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+/// Decrypt data produced by [`encrypt`] with the provided consignment key
+///
+/// Returns `None` if the data is not encrypted, is malformed or has been encrypted with a
+/// different key.
+pub(crate) fn decrypt(key: &str, data: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let cipher = ChaCha20Poly1305::new(&_parse_key(key)?);
+    let payload = match data.strip_prefix(ENCRYPTED_MAGIC) {
+        Some(payload) if payload.len() >= NONCE_LEN => payload,
+        _ => return Ok(None),
+    };
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    Ok(cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok())
+}
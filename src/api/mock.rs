@@ -0,0 +1,382 @@
+//! In-process mock of the RGB proxy server, for tests
+//!
+//! [`MockProxy`] serves all the endpoints used by the [`Proxy`](super::Proxy) trait, keeping
+//! consignments, media and ACKs in memory, and allows injecting faults into its responses.
+//! As the real proxy server, it refuses to replace the consignment stored for a recipient.
+
+use amplify::s;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MOCK_PROXY_VERSION: &str = "0.1.0";
+
+/// A fault the [`MockProxy`] can inject into its responses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Fault {
+    /// Wait for the given time before handling the request
+    Timeout(Duration),
+    /// Reply with an HTTP 500 error
+    ServerError,
+    /// Reply with a body that is not valid JSON
+    MalformedJson,
+    /// Reply with success to an ACK or NACK without storing it
+    LostAck,
+}
+
+/// A request received by the [`MockProxy`]
+#[derive(Clone, Debug)]
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Request {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|v| v.as_str())
+    }
+
+    fn endpoint(&self) -> &str {
+        self.path
+            .trim_start_matches('/')
+            .split('/')
+            .next()
+            .unwrap_or_default()
+    }
+
+    fn path_param(&self) -> Option<&str> {
+        self.path.trim_start_matches('/').split('/').nth(1)
+    }
+
+    /// Return the text fields and files of a multipart body
+    fn multipart_fields(&self) -> HashMap<String, Vec<u8>> {
+        let mut fields = HashMap::new();
+        let boundary = match self
+            .header("content-type")
+            .and_then(|ct| ct.split("boundary=").nth(1))
+        {
+            Some(b) => format!("--{}", b.trim_matches('"')),
+            None => return fields,
+        };
+        for part in _split(&self.body, boundary.as_bytes()).into_iter().skip(1) {
+            let (head, content) = match _split_once(part, b"\r\n\r\n") {
+                Some(split) => split,
+                None => continue,
+            };
+            let head = String::from_utf8_lossy(head);
+            let name = head
+                .split(';')
+                .map(|p| p.trim())
+                .find_map(|p| p.strip_prefix("name="))
+                .map(|n| n.trim_matches('"').to_string());
+            if let Some(name) = name {
+                let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+                fields.insert(name, content.to_vec());
+            }
+        }
+        fields
+    }
+
+    /// Return the recipient ID sent with a POST request
+    fn recipient_id(&self, fields: &HashMap<String, Vec<u8>>) -> Option<String> {
+        fields
+            .get("blindedutxo")
+            .map(|v| String::from_utf8_lossy(v).to_string())
+    }
+
+    fn json_fields(&self) -> HashMap<String, Vec<u8>> {
+        serde_json::from_slice::<HashMap<String, String>>(&self.body)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(k, v)| (k, v.into_bytes()))
+            .collect()
+    }
+}
+
+fn _split<'a>(data: &'a [u8], separator: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = vec![];
+    let mut rest = data;
+    while let Some((part, tail)) = _split_once(rest, separator) {
+        parts.push(part);
+        rest = tail;
+    }
+    parts.push(rest);
+    parts
+}
+
+fn _split_once<'a>(data: &'a [u8], separator: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    data.windows(separator.len())
+        .position(|w| w == separator)
+        .map(|i| (&data[..i], &data[i + separator.len()..]))
+}
+
+#[derive(Default)]
+struct MockState {
+    version: String,
+    consignments: HashMap<String, String>,
+    media: HashMap<String, String>,
+    acks: HashMap<String, bool>,
+    faults: Vec<(String, Fault, usize)>,
+    requests: Vec<Request>,
+}
+
+impl MockState {
+    /// Consume and return the next fault to be injected for the given endpoint, if any
+    fn take_fault(&mut self, endpoint: &str) -> Option<Fault> {
+        let pos = self
+            .faults
+            .iter()
+            .position(|(e, _, _)| e == endpoint || e == "*")?;
+        let fault = self.faults[pos].1.clone();
+        self.faults[pos].2 -= 1;
+        if self.faults[pos].2 == 0 {
+            self.faults.remove(pos);
+        }
+        Some(fault)
+    }
+}
+
+/// An RGB proxy server running in-process
+///
+/// Every instance has its own state, so tests injecting faults should start a dedicated one.
+#[derive(Clone)]
+pub(crate) struct MockProxy {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    started: Instant,
+}
+
+impl MockProxy {
+    /// Start a mock proxy listening on a random local port
+    pub(crate) fn start() -> Self {
+        Self::start_on("127.0.0.1:0")
+    }
+
+    /// Start a mock proxy listening on the given address
+    pub(crate) fn start_on(addr: impl ToSocketAddrs) -> Self {
+        let listener = TcpListener::bind(addr).expect("mock proxy address should be available");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockState {
+            version: MOCK_PROXY_VERSION.to_string(),
+            ..Default::default()
+        }));
+        let mock = MockProxy {
+            url,
+            state,
+            started: Instant::now(),
+        };
+        let server = mock.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let server = server.clone();
+                thread::spawn(move || server._serve(stream));
+            }
+        });
+        mock
+    }
+
+    pub(crate) fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Set the protocol version reported by the getinfo endpoint
+    pub(crate) fn set_version(&self, version: &str) {
+        self._state().version = version.to_string();
+    }
+
+    /// Inject `fault` into the next `times` requests to `endpoint` ("*" matches any endpoint)
+    pub(crate) fn inject_fault(&self, endpoint: &str, fault: Fault, times: usize) {
+        if times > 0 {
+            self._state()
+                .faults
+                .push((endpoint.to_string(), fault, times));
+        }
+    }
+
+    /// Return all the requests received so far
+    pub(crate) fn requests(&self) -> Vec<Request> {
+        self._state().requests.clone()
+    }
+
+    /// Return the stored consignment for the given recipient, if any
+    pub(crate) fn consignment(&self, recipient_id: &str) -> Option<Vec<u8>> {
+        self._state()
+            .consignments
+            .get(recipient_id)
+            .map(|c| base64::decode(c).expect("stored consignments should be valid base64"))
+    }
+
+    /// Return the stored media with the given ID, if any
+    pub(crate) fn media(&self, media_id: &str) -> Option<Vec<u8>> {
+        self._state()
+            .media
+            .get(media_id)
+            .map(|m| base64::decode(m).expect("stored media should be valid base64"))
+    }
+
+    /// Return the stored ACK (true) or NACK (false) for the given recipient, if any
+    pub(crate) fn ack(&self, recipient_id: &str) -> Option<bool> {
+        self._state().acks.get(recipient_id).copied()
+    }
+
+    fn _state(&self) -> std::sync::MutexGuard<MockState> {
+        self.state
+            .lock()
+            .expect("mock proxy state lock should not be poisoned")
+    }
+
+    fn _serve(&self, stream: TcpStream) {
+        let mut writer = match stream.try_clone() {
+            Ok(w) => w,
+            Err(_) => return,
+        };
+        let mut reader = BufReader::new(stream);
+        while let Some(request) = _read_request(&mut reader) {
+            let (status, body) = self._handle(request);
+            let reply = format!(
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            if writer.write_all(reply.as_bytes()).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn _handle(&self, request: Request) -> (&'static str, String) {
+        let endpoint = request.endpoint().to_string();
+        let fault = {
+            let mut state = self._state();
+            state.requests.push(request.clone());
+            state.take_fault(&endpoint)
+        };
+        match fault {
+            Some(Fault::Timeout(duration)) => thread::sleep(duration),
+            Some(Fault::ServerError) => return ("500 Internal Server Error", s!("")),
+            Some(Fault::MalformedJson) => return ("200 OK", s!("{\"success\":")),
+            _ => {}
+        }
+        let lost_ack = fault == Some(Fault::LostAck);
+
+        let mut state = self._state();
+        let body = match (request.method.as_str(), endpoint.as_str()) {
+            ("GET", "getinfo") => serde_json::json!({
+                "version": state.version,
+                "uptime": self.started.elapsed().as_secs(),
+            }),
+            ("GET", "ack") => {
+                let ack = request
+                    .path_param()
+                    .and_then(|id| state.acks.get(id))
+                    .copied();
+                serde_json::json!({
+                    "success": true,
+                    "ack": ack.filter(|a| *a),
+                    "nack": ack.filter(|a| !*a).map(|_| true),
+                })
+            }
+            ("GET", "consignment") => {
+                match request
+                    .path_param()
+                    .and_then(|id| state.consignments.get(id))
+                {
+                    Some(c) => serde_json::json!({"success": true, "consignment": c}),
+                    None => serde_json::json!({"success": false}),
+                }
+            }
+            ("GET", "media") => match request.path_param().and_then(|id| state.media.get(id)) {
+                Some(m) => serde_json::json!({"success": true, "media": m}),
+                None => serde_json::json!({"success": false}),
+            },
+            ("POST", "ack") | ("POST", "nack") => {
+                let recipient_id = request.recipient_id(&request.json_fields());
+                match recipient_id {
+                    Some(id) if state.consignments.contains_key(&id) => {
+                        if !lost_ack {
+                            state.acks.insert(id, endpoint == "ack");
+                        }
+                        serde_json::json!({"success": true})
+                    }
+                    _ => serde_json::json!({"success": false}),
+                }
+            }
+            ("POST", "consignment") => {
+                let fields = request.multipart_fields();
+                match (request.recipient_id(&fields), fields.get("consignment")) {
+                    (Some(id), Some(consignment)) => {
+                        let consignment = base64::encode(consignment);
+                        // like the real proxy, refuse to replace a stored consignment but accept
+                        // the same one again, so a repeated POST is harmless
+                        let stored = state.consignments.entry(id).or_insert(consignment.clone());
+                        serde_json::json!({"success": *stored == consignment})
+                    }
+                    _ => serde_json::json!({"success": false}),
+                }
+            }
+            ("POST", "media") => {
+                let fields = request.multipart_fields();
+                match (fields.get("attachment_id"), fields.get("media")) {
+                    (Some(id), Some(media)) => {
+                        state.media.insert(
+                            String::from_utf8_lossy(id).to_string(),
+                            base64::encode(media),
+                        );
+                        serde_json::json!({"success": true})
+                    }
+                    _ => serde_json::json!({"success": false}),
+                }
+            }
+            _ => return ("404 Not Found", s!("")),
+        };
+        ("200 OK", body.to_string())
+    }
+}
+
+fn _read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+    }
+    let mut body = vec![];
+    if let Some(len) = headers.get("content-length") {
+        body.resize(len.parse().ok()?, 0);
+        reader.read_exact(&mut body).ok()?;
+    } else if headers.get("transfer-encoding").map(|v| v.as_str()) == Some("chunked") {
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line).ok()?;
+            let size = usize::from_str_radix(size_line.trim(), 16).ok()?;
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).ok()?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    }
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
@@ -1,2 +1,8 @@
+pub(crate) mod encryption;
 pub(crate) mod proxy;
 pub use proxy::Proxy;
+
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(test)]
+mod test;
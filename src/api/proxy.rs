@@ -1,9 +1,71 @@
-use reqwest::blocking::{multipart, Client};
+use reqwest::blocking::{multipart, Client, RequestBuilder, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Identity;
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
 use crate::error::{Error, InternalError};
+use crate::wallet::{ProxyAuth, ProxyOptions};
+
+const PROXY_VERSION_MIN: ProxyVersion = ProxyVersion {
+    major: 0,
+    minor: 1,
+    patch: 0,
+};
+/// Only 0.1 servers are supported, newer ones need to be added here together with the request
+/// shapes they expect
+const PROXY_VERSION_MAX: ProxyVersion = ProxyVersion {
+    major: 0,
+    minor: 1,
+    patch: u16::MAX,
+};
+
+/// Protocol version reported by a proxy server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProxyVersion {
+    major: u16,
+    minor: u16,
+    patch: u16,
+}
+
+impl ProxyVersion {
+    pub(crate) fn is_supported(&self) -> bool {
+        *self >= PROXY_VERSION_MIN && *self <= PROXY_VERSION_MAX
+    }
+}
+
+impl fmt::Display for ProxyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ProxyVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unsupported = || Error::UnsupportedProxyVersion(s.to_string());
+        let parts: Vec<&str> = s.trim().trim_start_matches('v').split('.').collect();
+        if parts.len() != 3 {
+            return Err(unsupported());
+        }
+        let numbers = parts
+            .iter()
+            .map(|p| p.parse::<u16>().map_err(|_| unsupported()))
+            .collect::<Result<Vec<u16>, Error>>()?;
+        Ok(ProxyVersion {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+        })
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct InfoResponse {
@@ -11,6 +73,17 @@ pub struct InfoResponse {
     pub(crate) uptime: u64,
 }
 
+impl InfoResponse {
+    /// Parse the reported protocol version, failing if it's not supported by this library
+    pub(crate) fn supported_version(&self) -> Result<ProxyVersion, Error> {
+        let version = ProxyVersion::from_str(&self.version)?;
+        if !version.is_supported() {
+            return Err(Error::UnsupportedProxyVersion(self.version.clone()));
+        }
+        Ok(version)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AckResponse {
     pub(crate) success: bool,
@@ -32,12 +105,64 @@ pub struct MediaResponse {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SuccessResponse {
-    success: bool,
+    pub(crate) success: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct AckNackRequest {
-    blindedutxo: String,
+fn _auth_header(auth: &ProxyAuth) -> String {
+    match auth {
+        ProxyAuth::Bearer { token } => format!("Bearer {}", token),
+        ProxyAuth::Basic { username, password } => {
+            let credentials = format!("{}:{}", username, password.clone().unwrap_or_default());
+            format!("Basic {}", base64::encode(credentials))
+        }
+    }
+}
+
+/// Build the client used to contact the proxy server, applying the given options to all requests
+pub(crate) fn build_proxy_client(
+    timeout: Duration,
+    options: Option<&ProxyOptions>,
+) -> Result<Client, Error> {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(options) = options {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &options.headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| Error::InvalidProxyOptions(e.to_string()))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|e| Error::InvalidProxyOptions(e.to_string()))?;
+            headers.insert(header_name, header_value);
+        }
+        if let Some(auth) = &options.auth {
+            let mut auth_value = HeaderValue::from_str(&_auth_header(auth))
+                .map_err(|e| Error::InvalidProxyOptions(e.to_string()))?;
+            auth_value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, auth_value);
+        }
+        builder = builder.default_headers(headers);
+        if let Some(cert_path) = &options.tls_client_cert_path {
+            let cert_bytes = fs::read(cert_path)
+                .map_err(|e| Error::InvalidProxyOptions(format!("{}: {}", cert_path, e)))?;
+            let password = options.tls_client_cert_password.clone().unwrap_or_default();
+            let identity = Identity::from_pkcs12_der(&cert_bytes, &password)
+                .map_err(|e| Error::InvalidProxyOptions(e.to_string()))?;
+            builder = builder.identity(identity);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Send the request, treating server-side failures as errors so they can be retried
+fn _send(request: RequestBuilder) -> Result<Response, Error> {
+    let response = request.send().map_err(Error::Proxy)?;
+    if response.status().is_server_error() {
+        return Err(Error::Proxy(
+            response
+                .error_for_status()
+                .expect_err("server error statuses are always errors"),
+        ));
+    }
+    Ok(response)
 }
 
 pub trait Proxy {
@@ -70,59 +195,41 @@ pub trait Proxy {
 
 impl Proxy for Client {
     fn get_info(self, url: &str) -> Result<InfoResponse, Error> {
-        Ok(self
-            .get(format!("{}/getinfo", url))
-            .send()
-            .map_err(Error::Proxy)?
+        Ok(_send(self.get(format!("{}/getinfo", url)))?
             .json::<InfoResponse>()
             .map_err(InternalError::from)?)
     }
 
     fn get_ack(self, url: &str, blindedutxo: String) -> Result<AckResponse, Error> {
-        Ok(self
-            .get(format!("{}/ack/{}", url, blindedutxo))
-            .send()
-            .map_err(Error::Proxy)?
+        Ok(_send(self.get(format!("{}/ack/{}", url, blindedutxo)))?
             .json::<AckResponse>()
             .map_err(InternalError::from)?)
     }
 
     fn get_consignment(self, url: &str, blindedutxo: String) -> Result<ConsignmentResponse, Error> {
-        Ok(self
-            .get(format!("{}/consignment/{}", url, blindedutxo))
-            .send()
-            .map_err(Error::Proxy)?
-            .json::<ConsignmentResponse>()
-            .map_err(InternalError::from)?)
+        Ok(
+            _send(self.get(format!("{}/consignment/{}", url, blindedutxo)))?
+                .json::<ConsignmentResponse>()
+                .map_err(InternalError::from)?,
+        )
     }
 
     fn get_media(self, url: &str, attachment_id: String) -> Result<MediaResponse, Error> {
-        Ok(self
-            .get(format!("{}/media/{}", url, attachment_id))
-            .send()
-            .map_err(Error::Proxy)?
+        Ok(_send(self.get(format!("{}/media/{}", url, attachment_id)))?
             .json::<MediaResponse>()
             .map_err(InternalError::from)?)
     }
 
     fn post_nack(self, url: &str, blindedutxo: String) -> Result<SuccessResponse, Error> {
-        let body = AckNackRequest { blindedutxo };
-        Ok(self
-            .post(format!("{}/nack", url))
-            .json(&body)
-            .send()
-            .map_err(Error::Proxy)?
+        let body = HashMap::from([("blindedutxo", blindedutxo)]);
+        Ok(_send(self.post(format!("{}/nack", url)).json(&body))?
             .json::<SuccessResponse>()
             .map_err(InternalError::from)?)
     }
 
     fn post_ack(self, url: &str, blindedutxo: String) -> Result<SuccessResponse, Error> {
-        let body = AckNackRequest { blindedutxo };
-        Ok(self
-            .post(format!("{}/ack", url))
-            .json(&body)
-            .send()
-            .map_err(Error::Proxy)?
+        let body = HashMap::from([("blindedutxo", blindedutxo)]);
+        Ok(_send(self.post(format!("{}/ack", url)).json(&body))?
             .json::<SuccessResponse>()
             .map_err(InternalError::from)?)
     }
@@ -136,13 +243,11 @@ impl Proxy for Client {
         let form = multipart::Form::new()
             .text("blindedutxo", blindedutxo)
             .file("consignment", consignment_path)?;
-        Ok(self
-            .post(format!("{}/consignment", url))
-            .multipart(form)
-            .send()
-            .map_err(Error::Proxy)?
-            .json::<SuccessResponse>()
-            .map_err(InternalError::from)?)
+        Ok(
+            _send(self.post(format!("{}/consignment", url)).multipart(form))?
+                .json::<SuccessResponse>()
+                .map_err(InternalError::from)?,
+        )
     }
 
     fn post_media(
@@ -154,11 +259,7 @@ impl Proxy for Client {
         let form = multipart::Form::new()
             .text("attachment_id", attachment_id)
             .file("media", media_path)?;
-        Ok(self
-            .post(format!("{}/media", url))
-            .multipart(form)
-            .send()
-            .map_err(Error::Proxy)?
+        Ok(_send(self.post(format!("{}/media", url)).multipart(form))?
             .json::<SuccessResponse>()
             .map_err(InternalError::from)?)
     }
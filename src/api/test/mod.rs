@@ -0,0 +1,544 @@
+use amplify::s;
+use slog::{o, Discard, Logger};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use super::encryption;
+use super::mock::{Fault, MockProxy};
+use super::proxy::*;
+use crate::error::InternalError;
+use crate::wallet::{NetworkPolicy, ProxyAuth, ProxyOptions};
+use crate::Error;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn _failing_server(failures: usize) -> MockProxy {
+    let server = MockProxy::start();
+    server.inject_fault("*", Fault::ServerError, failures);
+    server
+}
+
+fn _version() -> ProxyVersion {
+    "0.1.0".parse().unwrap()
+}
+
+#[test]
+fn no_options() {
+    let server = MockProxy::start();
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+
+    let info = client.get_info(&server.url()).unwrap();
+    assert_eq!(info.supported_version().unwrap(), _version());
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/getinfo");
+    assert!(requests[0].header("authorization").is_none());
+}
+
+#[test]
+fn bearer_auth() {
+    let server = MockProxy::start();
+    let options = ProxyOptions {
+        auth: Some(ProxyAuth::Bearer {
+            token: s!("secret-token"),
+        }),
+        ..Default::default()
+    };
+    let client = build_proxy_client(TIMEOUT, Some(&options)).unwrap();
+
+    client.clone().get_info(&server.url()).unwrap();
+    client.get_ack(&server.url(), s!("utxob1")).unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert_eq!(request.header("authorization"), Some("Bearer secret-token"));
+    }
+}
+
+#[test]
+fn basic_auth() {
+    let server = MockProxy::start();
+    let options = ProxyOptions {
+        auth: Some(ProxyAuth::Basic {
+            username: s!("user"),
+            password: Some(s!("pass")),
+        }),
+        ..Default::default()
+    };
+    let client = build_proxy_client(TIMEOUT, Some(&options)).unwrap();
+
+    let consignment_path = std::env::temp_dir().join("rgb_lib_api_test_consignment");
+    std::fs::write(&consignment_path, b"consignment-data").unwrap();
+    client
+        .post_consignment(&server.url(), s!("utxob1"), consignment_path.clone())
+        .unwrap();
+    std::fs::remove_file(consignment_path).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/consignment");
+    // base64 of "user:pass"
+    assert_eq!(request.header("authorization"), Some("Basic dXNlcjpwYXNz"));
+    let body = String::from_utf8_lossy(&request.body);
+    assert!(body.contains("name=\"blindedutxo\""));
+    assert!(body.contains("consignment-data"));
+
+    // a missing password is sent as empty
+    let options = ProxyOptions {
+        auth: Some(ProxyAuth::Basic {
+            username: s!("user"),
+            password: None,
+        }),
+        ..Default::default()
+    };
+    let client = build_proxy_client(TIMEOUT, Some(&options)).unwrap();
+    client.get_info(&server.url()).unwrap();
+    let requests = server.requests();
+    // base64 of "user:"
+    assert_eq!(requests[1].header("authorization"), Some("Basic dXNlcjo="));
+}
+
+#[test]
+fn custom_headers() {
+    let server = MockProxy::start();
+    let options = ProxyOptions {
+        headers: HashMap::from([(s!("X-Api-Key"), s!("api-key"))]),
+        ..Default::default()
+    };
+    let client = build_proxy_client(TIMEOUT, Some(&options)).unwrap();
+
+    client.post_ack(&server.url(), s!("utxob1")).unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/ack");
+    assert_eq!(requests[0].header("x-api-key"), Some("api-key"));
+    assert!(requests[0].header("authorization").is_none());
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["blindedutxo"], "utxob1");
+}
+
+#[test]
+fn proxy_version_parse() {
+    let version: ProxyVersion = "0.1.0".parse().unwrap();
+    assert_eq!(version.to_string(), "0.1.0");
+    // leading "v" and surrounding whitespace are accepted
+    assert_eq!(
+        "v0.2.3".parse::<ProxyVersion>().unwrap().to_string(),
+        "0.2.3"
+    );
+    assert_eq!(
+        " 0.1.2\n".parse::<ProxyVersion>().unwrap().to_string(),
+        "0.1.2"
+    );
+
+    for invalid in [
+        "",
+        "0.1",
+        "0.1.0.0",
+        "0.1.x",
+        "0.-1.0",
+        "0.1.65536",
+        "a.b.c",
+    ] {
+        let result = invalid.parse::<ProxyVersion>();
+        assert!(matches!(result, Err(Error::UnsupportedProxyVersion(ref v)) if v == invalid));
+    }
+}
+
+#[test]
+fn proxy_version_is_supported() {
+    let supported = |v: &str| v.parse::<ProxyVersion>().unwrap().is_supported();
+    // bounds are inclusive
+    assert!(supported("0.1.0"));
+    assert!(supported("0.1.9"));
+    assert!(supported("0.1.65535"));
+    // versions outside the range are refused, the major one included
+    assert!(!supported("0.0.9"));
+    assert!(!supported("0.2.0"));
+    assert!(!supported("0.3.0"));
+    assert!(!supported("1.0.0"));
+    assert!(!supported("1.1.0"));
+    assert!(!supported("1.2.0"));
+}
+
+#[test]
+fn unsupported_proxy_version() {
+    let info = |version: &str| InfoResponse {
+        version: version.to_string(),
+        uptime: 0,
+    };
+    assert_eq!(
+        info("0.1.1").supported_version().unwrap().to_string(),
+        "0.1.1"
+    );
+    for version in ["0.2.0", "0.3.0", "1.0.0", "invalid"] {
+        let result = info(version).supported_version();
+        assert!(matches!(result, Err(Error::UnsupportedProxyVersion(ref v)) if v == version));
+    }
+
+    // the error is surfaced with the reported version
+    let server = MockProxy::start();
+    server.set_version("0.3.0");
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+    let result = client.get_info(&server.url()).unwrap().supported_version();
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unsupported proxy version: 0.3.0"
+    );
+}
+
+#[test]
+fn invalid_options() {
+    // invalid header name
+    let options = ProxyOptions {
+        headers: HashMap::from([(s!("bad header"), s!("value"))]),
+        ..Default::default()
+    };
+    let result = build_proxy_client(TIMEOUT, Some(&options));
+    assert!(matches!(result, Err(Error::InvalidProxyOptions(_))));
+
+    // invalid header value
+    let options = ProxyOptions {
+        headers: HashMap::from([(s!("X-Header"), s!("bad\nvalue"))]),
+        ..Default::default()
+    };
+    let result = build_proxy_client(TIMEOUT, Some(&options));
+    assert!(matches!(result, Err(Error::InvalidProxyOptions(_))));
+
+    // missing client certificate
+    let options = ProxyOptions {
+        tls_client_cert_path: Some(s!("/nonexistent/client.p12")),
+        ..Default::default()
+    };
+    let result = build_proxy_client(TIMEOUT, Some(&options));
+    assert!(matches!(result, Err(Error::InvalidProxyOptions(_))));
+
+    // invalid client certificate
+    let cert_path = std::env::temp_dir().join("rgb_lib_api_test_invalid.p12");
+    std::fs::write(&cert_path, b"not a certificate").unwrap();
+    let options = ProxyOptions {
+        tls_client_cert_path: Some(cert_path.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    let result = build_proxy_client(TIMEOUT, Some(&options));
+    std::fs::remove_file(cert_path).unwrap();
+    assert!(matches!(result, Err(Error::InvalidProxyOptions(_))));
+}
+
+fn _fast_policy(max_retries: u8) -> NetworkPolicy {
+    NetworkPolicy {
+        max_retries,
+        backoff_initial_ms: 1,
+        backoff_max_ms: 4,
+        ..Default::default()
+    }
+}
+
+fn _logger() -> Logger {
+    Logger::root(Discard, o!())
+}
+
+#[test]
+fn backoff() {
+    let policy = NetworkPolicy {
+        backoff_initial_ms: 500,
+        backoff_max_ms: 3000,
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(0), Duration::from_millis(500));
+    assert_eq!(policy.backoff(1), Duration::from_millis(1000));
+    assert_eq!(policy.backoff(2), Duration::from_millis(2000));
+    assert_eq!(policy.backoff(3), Duration::from_millis(3000));
+    assert_eq!(policy.backoff(100), Duration::from_millis(3000));
+}
+
+#[test]
+fn retry_server_error() {
+    // transient failures are retried
+    let server = _failing_server(2);
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+    let policy = _fast_policy(3);
+    let result = policy.retry(&_logger(), || client.clone().get_info(&server.url()));
+    assert!(result.is_ok());
+    assert_eq!(server.requests().len(), 3);
+
+    // retries are limited
+    let server = _failing_server(5);
+    let result = policy.retry(&_logger(), || client.clone().get_info(&server.url()));
+    assert!(matches!(result, Err(Error::Proxy(_))));
+    assert_eq!(server.requests().len(), 4);
+
+    // server errors are not retried if disabled
+    let server = _failing_server(1);
+    let policy = NetworkPolicy {
+        retry_on_server_error: false,
+        .._fast_policy(3)
+    };
+    let result = policy.retry(&_logger(), || client.clone().get_info(&server.url()));
+    assert!(matches!(result, Err(Error::Proxy(_))));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn retry_connect_error() {
+    // find a free port with nothing listening on it
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+
+    let mut attempts = 0;
+    let result = _fast_policy(2).retry(&_logger(), || {
+        attempts += 1;
+        client.clone().get_info(&url)
+    });
+    assert!(matches!(result, Err(Error::Proxy(_))));
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let policy = NetworkPolicy {
+        retry_on_connect: false,
+        .._fast_policy(2)
+    };
+    let result = policy.retry(&_logger(), || {
+        attempts += 1;
+        client.clone().get_info(&url)
+    });
+    assert!(matches!(result, Err(Error::Proxy(_))));
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn retry_timeout() {
+    let server = MockProxy::start();
+    let url = server.url();
+    let client = build_proxy_client(Duration::from_millis(200), None).unwrap();
+    let policy = _fast_policy(2);
+
+    // GET requests are retried on timeout
+    server.inject_fault("ack", Fault::Timeout(Duration::from_secs(1)), 1);
+    let result = policy.retry(&_logger(), || client.clone().get_ack(&url, s!("utxob1")));
+    assert!(result.is_ok());
+    assert_eq!(server.requests().len(), 2);
+
+    // POST requests may have been processed, so they're not repeated on timeout
+    server.inject_fault("ack", Fault::Timeout(Duration::from_secs(1)), 1);
+    let result = policy.retry_post(&_logger(), || client.clone().post_ack(&url, s!("utxob1")));
+    assert!(matches!(result, Err(Error::Proxy(ref e)) if e.is_timeout()));
+    assert_eq!(server.requests().len(), 3);
+
+    // other transient failures are still retried for POST requests
+    server.inject_fault("ack", Fault::ServerError, 1);
+    let result = policy.retry_post(&_logger(), || client.clone().post_ack(&url, s!("utxob1")));
+    assert!(result.is_ok());
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[test]
+fn no_retry_other_errors() {
+    let mut attempts = 0;
+    let result: Result<(), Error> = _fast_policy(3).retry(&_logger(), || {
+        attempts += 1;
+        Err(Error::InvalidProxyOptions(s!("not a network error")))
+    });
+    assert!(matches!(result, Err(Error::InvalidProxyOptions(_))));
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn retryable_errors() {
+    let policy = NetworkPolicy::default();
+    let electrum_io = |kind: ErrorKind| {
+        Error::Electrum(electrum_client::Error::IOError(std::io::Error::from(kind)))
+    };
+
+    // electrum timeouts are retried only for idempotent calls, connection failures always
+    let timeout = electrum_io(ErrorKind::TimedOut);
+    assert!(policy.is_retryable(&timeout, true));
+    assert!(!policy.is_retryable(&timeout, false));
+    let refused = electrum_io(ErrorKind::ConnectionRefused);
+    assert!(policy.is_retryable(&refused, true));
+    assert!(policy.is_retryable(&refused, false));
+    // failures the electrum client doesn't detail are only retried for idempotent calls
+    let failed = Error::Electrum(electrum_client::Error::AllAttemptsErrored(vec![]));
+    assert!(policy.is_retryable(&failed, true));
+    assert!(!policy.is_retryable(&failed, false));
+    // errors returned by the electrum server are not retried
+    let protocol = Error::Electrum(electrum_client::Error::Protocol(serde_json::Value::Null));
+    assert!(!policy.is_retryable(&protocol, true));
+
+    // RGB node message bus failures are retried, errors from the node itself are not
+    let bus = Error::from(InternalError::RgbRpc(rgb_rpc::Error::Esb(
+        internet2::transport::Error::ServiceOffline.into(),
+    )));
+    assert!(policy.is_retryable(&bus, true));
+    let node = Error::from(InternalError::RgbRpc(
+        rgb_rpc::Error::UnexpectedServerResponse,
+    ));
+    assert!(!policy.is_retryable(&node, true));
+
+    // connection failures are not retried if disabled
+    let policy = NetworkPolicy {
+        retry_on_connect: false,
+        ..Default::default()
+    };
+    assert!(!policy.is_retryable(&refused, true));
+    assert!(!policy.is_retryable(&bus, true));
+}
+
+#[test]
+fn mock_endpoints() {
+    let server = MockProxy::start();
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+    let url = server.url();
+    let tmp_dir = std::env::temp_dir();
+
+    // consignments
+    let consignment_path = tmp_dir.join("rgb_lib_api_test_mock_consignment");
+    std::fs::write(&consignment_path, b"consignment").unwrap();
+    let res = client.clone().get_consignment(&url, s!("utxob1")).unwrap();
+    assert!(res.consignment.is_none());
+    client
+        .clone()
+        .post_consignment(&url, s!("utxob1"), consignment_path.clone())
+        .unwrap();
+    let res = client.clone().get_consignment(&url, s!("utxob1")).unwrap();
+    assert_eq!(
+        base64::decode(res.consignment.unwrap()).unwrap(),
+        b"consignment"
+    );
+    assert_eq!(server.consignment("utxob1").unwrap(), b"consignment");
+    // posting the same consignment again succeeds, replacing it is refused
+    let res = client
+        .clone()
+        .post_consignment(&url, s!("utxob1"), consignment_path.clone())
+        .unwrap();
+    assert!(res.success);
+    let other_consignment_path = tmp_dir.join("rgb_lib_api_test_mock_other_consignment");
+    std::fs::write(&other_consignment_path, b"other consignment").unwrap();
+    let res = client
+        .clone()
+        .post_consignment(&url, s!("utxob1"), other_consignment_path.clone())
+        .unwrap();
+    assert!(!res.success);
+    assert_eq!(server.consignment("utxob1").unwrap(), b"consignment");
+
+    // ACKs and NACKs
+    let res = client.clone().get_ack(&url, s!("utxob1")).unwrap();
+    assert_eq!((res.ack, res.nack), (None, None));
+    client.clone().post_ack(&url, s!("utxob1")).unwrap();
+    let res = client.clone().get_ack(&url, s!("utxob1")).unwrap();
+    assert_eq!((res.ack, res.nack), (Some(true), None));
+    client
+        .clone()
+        .post_consignment(&url, s!("utxob2"), consignment_path.clone())
+        .unwrap();
+    client.clone().post_nack(&url, s!("utxob2")).unwrap();
+    let res = client.clone().get_ack(&url, s!("utxob2")).unwrap();
+    assert_eq!((res.ack, res.nack), (None, Some(true)));
+    assert_eq!(server.ack("utxob2"), Some(false));
+    // ACK without a consignment is refused
+    let res = client.clone().post_ack(&url, s!("utxob3")).unwrap();
+    assert!(!res.success);
+    assert_eq!(server.ack("utxob3"), None);
+
+    // media
+    let media_path = tmp_dir.join("rgb_lib_api_test_mock_media");
+    std::fs::write(&media_path, b"media").unwrap();
+    client
+        .clone()
+        .post_media(&url, s!("attachment"), media_path.clone())
+        .unwrap();
+    let res = client.clone().get_media(&url, s!("attachment")).unwrap();
+    assert_eq!(base64::decode(res.media.unwrap()).unwrap(), b"media");
+
+    std::fs::remove_file(consignment_path).unwrap();
+    std::fs::remove_file(other_consignment_path).unwrap();
+    std::fs::remove_file(media_path).unwrap();
+}
+
+#[test]
+fn mock_faults() {
+    let server = MockProxy::start();
+    let url = server.url();
+
+    // timeout
+    server.inject_fault("getinfo", Fault::Timeout(Duration::from_secs(2)), 1);
+    let client = build_proxy_client(Duration::from_millis(200), None).unwrap();
+    let result = client.get_info(&url);
+    assert!(matches!(result, Err(Error::Proxy(ref e)) if e.is_timeout()));
+
+    let client = build_proxy_client(TIMEOUT, None).unwrap();
+
+    // server error
+    server.inject_fault("getinfo", Fault::ServerError, 1);
+    let result = client.clone().get_info(&url);
+    assert!(matches!(result, Err(Error::Proxy(ref e)) if e.status().unwrap().is_server_error()));
+
+    // malformed JSON
+    server.inject_fault("getinfo", Fault::MalformedJson, 1);
+    let result = client.clone().get_info(&url);
+    assert!(matches!(result, Err(Error::Internal(_))));
+
+    // faults are consumed
+    assert!(client.clone().get_info(&url).is_ok());
+
+    // lost ACK
+    let consignment_path = std::env::temp_dir().join("rgb_lib_api_test_mock_lost_ack");
+    std::fs::write(&consignment_path, b"consignment").unwrap();
+    client
+        .clone()
+        .post_consignment(&url, s!("utxob1"), consignment_path.clone())
+        .unwrap();
+    std::fs::remove_file(consignment_path).unwrap();
+    server.inject_fault("ack", Fault::LostAck, 1);
+    let res = client.clone().post_ack(&url, s!("utxob1")).unwrap();
+    assert!(res.success);
+    assert_eq!(server.ack("utxob1"), None);
+    let res = client.clone().get_ack(&url, s!("utxob1")).unwrap();
+    assert_eq!((res.ack, res.nack), (None, None));
+}
+
+#[test]
+fn consignment_encryption() {
+    let key = encryption::generate_key();
+    assert_eq!(key.len(), 64);
+    assert_ne!(key, encryption::generate_key());
+    let consignment = b"consignment".to_vec();
+
+    // encrypted data can be told apart and decrypted with the same key only
+    let encrypted = encryption::encrypt(&key, &consignment).unwrap();
+    assert!(encryption::is_encrypted(&encrypted));
+    assert!(!encryption::is_encrypted(&consignment));
+    assert_ne!(encrypted, encryption::encrypt(&key, &consignment).unwrap());
+    assert_eq!(
+        encryption::decrypt(&key, &encrypted).unwrap(),
+        Some(consignment.clone())
+    );
+    let other_key = encryption::generate_key();
+    assert_eq!(encryption::decrypt(&other_key, &encrypted).unwrap(), None);
+
+    // tampered, truncated or cleartext data cannot be decrypted
+    let mut tampered = encrypted.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(encryption::decrypt(&key, &tampered).unwrap(), None);
+    assert_eq!(encryption::decrypt(&key, &encrypted[..12]).unwrap(), None);
+    assert_eq!(encryption::decrypt(&key, &consignment).unwrap(), None);
+
+    // invalid keys
+    for invalid_key in [s!("invalid"), s!("00"), key.repeat(2)] {
+        assert!(matches!(
+            encryption::check_key(&invalid_key),
+            Err(Error::InvalidConsignmentKey(_))
+        ));
+        assert!(matches!(
+            encryption::encrypt(&invalid_key, &consignment),
+            Err(Error::InvalidConsignmentKey(_))
+        ));
+    }
+}
@@ -21,6 +21,7 @@ pub struct Model {
     pub created_at: i64,
     pub updated_at: i64,
     pub expiration: Option<i64>,
+    pub min_confirmations: u8,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -31,6 +32,7 @@ pub enum Column {
     CreatedAt,
     UpdatedAt,
     Expiration,
+    MinConfirmations,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -60,6 +62,7 @@ impl ColumnTrait for Column {
             Self::CreatedAt => ColumnType::BigInteger.def(),
             Self::UpdatedAt => ColumnType::BigInteger.def(),
             Self::Expiration => ColumnType::BigInteger.def().null(),
+            Self::MinConfirmations => ColumnType::SmallInteger.def(),
         }
     }
 }
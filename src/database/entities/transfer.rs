@@ -19,6 +19,10 @@ pub struct Model {
     pub blinded_utxo: Option<String>,
     pub blinding_secret: Option<String>,
     pub ack: Option<bool>,
+    pub consignment_key: Option<String>,
+    pub proxy_url: Option<String>,
+    pub transport_endpoints: Option<String>,
+    pub recipient_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -29,6 +33,10 @@ pub enum Column {
     BlindedUtxo,
     BlindingSecret,
     Ack,
+    ConsignmentKey,
+    ProxyUrl,
+    TransportEndpoints,
+    RecipientId,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -58,6 +66,10 @@ impl ColumnTrait for Column {
             Self::BlindedUtxo => ColumnType::String(None).def().null(),
             Self::BlindingSecret => ColumnType::String(None).def().null(),
             Self::Ack => ColumnType::Boolean.def().null(),
+            Self::ConsignmentKey => ColumnType::String(None).def().null(),
+            Self::ProxyUrl => ColumnType::String(None).def().null(),
+            Self::TransportEndpoints => ColumnType::String(None).def().null(),
+            Self::RecipientId => ColumnType::String(None).def().null(),
         }
     }
 }
@@ -4,7 +4,7 @@ use futures::executor::block_on;
 use sea_orm::entity::EntityTrait;
 use sea_orm::{
     ActiveValue, ColumnTrait, DatabaseConnection, DeriveActiveEnum, EnumIter, IntoActiveValue,
-    ModelTrait, QueryFilter,
+    ModelTrait, QueryFilter, TransactionTrait,
 };
 use sea_query::query::Condition;
 use std::str::FromStr;
@@ -103,6 +103,14 @@ impl DbColoring {
 }
 
 impl DbTransfer {
+    /// Return the ID the transfer's consignment is stored under on the proxy server, which
+    /// differs from the blinded UTXO for consignments replacing a previous one
+    pub(crate) fn proxy_recipient_id(&self) -> Option<String> {
+        self.recipient_id
+            .clone()
+            .or_else(|| self.blinded_utxo.clone())
+    }
+
     pub(crate) fn related_transfers(
         &self,
         database: Arc<RgbLibDatabase>,
@@ -219,6 +227,50 @@ impl RgbLibDatabase {
         Ok(res.last_insert_id)
     }
 
+    /// Save a batch transfer along with its asset transfers and their colorings and transfers in a
+    /// single DB transaction, so that either all of them are saved or none is
+    ///
+    /// The indexes linking the rows together are set here, as they're only known once the parent
+    /// rows have been inserted.
+    pub(crate) fn set_batch_transfer_with_children(
+        &self,
+        batch_transfer: DbBatchTransferActMod,
+        asset_transfers: Vec<(
+            DbAssetTransferActMod,
+            Vec<DbColoringActMod>,
+            Vec<DbTransferActMod>,
+        )>,
+    ) -> Result<i64, InternalError> {
+        let now = now().unix_timestamp();
+        let mut batch_transfer = batch_transfer;
+        batch_transfer.created_at = ActiveValue::Set(now);
+        batch_transfer.updated_at = ActiveValue::Set(now);
+        block_on(async {
+            let txn = self.get_connection().begin().await?;
+            let batch_transfer_idx = batch_transfer::Entity::insert(batch_transfer)
+                .exec(&txn)
+                .await?
+                .last_insert_id;
+            for (mut asset_transfer, colorings, transfers) in asset_transfers {
+                asset_transfer.batch_transfer_idx = ActiveValue::Set(batch_transfer_idx);
+                let asset_transfer_idx = asset_transfer::Entity::insert(asset_transfer)
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id;
+                for mut coloring in colorings {
+                    coloring.asset_transfer_idx = ActiveValue::Set(asset_transfer_idx);
+                    coloring::Entity::insert(coloring).exec(&txn).await?;
+                }
+                for mut transfer in transfers {
+                    transfer.asset_transfer_idx = ActiveValue::Set(asset_transfer_idx);
+                    transfer::Entity::insert(transfer).exec(&txn).await?;
+                }
+            }
+            txn.commit().await?;
+            Ok::<i64, InternalError>(batch_transfer_idx)
+        })
+    }
+
     pub(crate) fn set_coloring(&self, coloring: DbColoringActMod) -> Result<i64, InternalError> {
         let res = block_on(coloring::Entity::insert(coloring).exec(self.get_connection()))?;
         Ok(res.last_insert_id)
@@ -366,6 +418,22 @@ impl RgbLibDatabase {
         )?)
     }
 
+    pub(crate) fn iter_batch_transfer_transfers(
+        &self,
+        batch_transfer: &DbBatchTransfer,
+    ) -> Result<Vec<DbTransfer>, InternalError> {
+        let asset_transfer_ids: Vec<i64> = self
+            .iter_batch_asset_transfers(batch_transfer)?
+            .into_iter()
+            .map(|t| t.idx)
+            .collect();
+        Ok(self
+            .iter_transfers()?
+            .into_iter()
+            .filter(|t| asset_transfer_ids.contains(&t.asset_transfer_idx))
+            .collect())
+    }
+
     pub(crate) fn iter_batch_transfers(&self) -> Result<Vec<DbBatchTransfer>, InternalError> {
         Ok(block_on(
             batch_transfer::Entity::find().all(self.get_connection()),
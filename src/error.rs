@@ -22,10 +22,26 @@ pub enum Error {
     #[error("Blinded UTXO already used")]
     BlindedUTXOAlreadyUsed,
 
+    /// The requested send cannot be aborted
+    #[error("Send cannot be aborted")]
+    CannotAbortSend,
+
+    /// The requested transfer fee cannot be bumped
+    #[error("Transfer fee cannot be bumped")]
+    CannotBumpFee,
+
+    /// The requested transfer expiration cannot be changed
+    #[error("Transfer expiration cannot be changed")]
+    CannotChangeExpiration,
+
     /// A wallet cannot go online twice with different data
     #[error("Cannot change online object")]
     CannotChangeOnline(),
 
+    /// The requested transfer cannot be accelerated with a child transaction
+    #[error("Transfer cannot be accelerated with CPFP")]
+    CannotCpfp,
+
     /// Requested transfer cannot be deleted
     #[error("Transfer cannot be deleted")]
     CannotDeleteTransfer,
@@ -50,6 +66,10 @@ pub enum Error {
     #[error("Failed issuance. Register status: {0}")]
     FailedIssuance(String),
 
+    /// The electrum server cannot provide a fee estimate
+    #[error("Fee estimation unavailable: {0}")]
+    FeeEstimationUnavailable(String),
+
     /// An error I/O error has been encountered
     #[error("I/O error: {0}")]
     IO(#[from] std::io::Error),
@@ -95,6 +115,14 @@ pub enum Error {
     #[error("Invalid blinded UTXO: {0}")]
     InvalidBlindedUTXO(#[from] bp::seals::txout::blind::ParseError),
 
+    /// The provided BTC recipient is invalid
+    #[error("Invalid BTC recipient: {0}")]
+    InvalidBtcRecipient(String),
+
+    /// The provided consignment key is invalid
+    #[error("Invalid consignment key: {0}")]
+    InvalidConsignmentKey(String),
+
     /// The provided asset description is invalid
     #[error("Invalid description: {0}")]
     InvalidDescription(String),
@@ -103,6 +131,14 @@ pub enum Error {
     #[error("Invalid electrum server: {0}")]
     InvalidElectrum(String),
 
+    /// The provided fee rate is invalid
+    #[error("Invalid fee rate: {0}")]
+    InvalidFeeRate(String),
+
+    /// The provided confirmation target for fee estimation is invalid
+    #[error("Invalid fee target: {0}")]
+    InvalidFeeTarget(String),
+
     /// The provided file path is invalid
     #[error("Invalid file path: {0}")]
     InvalidFilePath(String),
@@ -119,10 +155,22 @@ pub enum Error {
     #[error("Invalid name: {0}")]
     InvalidName(String),
 
+    /// The provided network policy is invalid
+    #[error("Invalid network policy: {0}")]
+    InvalidNetworkPolicy(String),
+
     /// The provided online object is invalid
     #[error("Invalid online object")]
     InvalidOnline(),
 
+    /// The provided proxy options are invalid
+    #[error("Invalid proxy options: {0}")]
+    InvalidProxyOptions(String),
+
+    /// The provided proxy URLs are invalid
+    #[error("Invalid proxy URLs: {0}")]
+    InvalidProxyUrls(String),
+
     /// The provided asset parent ID is invalid
     #[error("Invalid parent ID: {0}")]
     InvalidParentId(String),
@@ -135,10 +183,22 @@ pub enum Error {
     #[error("Invalid pubkey: {0}")]
     InvalidPubkey(#[from] bitcoin::util::bip32::Error),
 
+    /// The provided refresh interval is invalid
+    #[error("Invalid refresh interval: {0}")]
+    InvalidRefreshInterval(String),
+
     /// The provided asset ticker is invalid
     #[error("Invalid ticker: {0}")]
     InvalidTicker(String),
 
+    /// The provided transport endpoint is invalid
+    #[error("Invalid transport endpoint: {0}")]
+    InvalidTransportEndpoint(String),
+
+    /// The maximum number of consignments replacing the one of a recipient has been reached
+    #[error("Maximum number of consignment replacements reached for recipient {0}")]
+    MaxConsignmentReplacements(String),
+
     /// Cannot issue an asset without knowing the amounts
     #[error("Issuance request with no provided amounts")]
     NoIssuanceAmounts,
@@ -147,10 +207,18 @@ pub enum Error {
     #[error("Proxy error: {0}")]
     Proxy(#[from] reqwest::Error),
 
+    /// The proxy server refused to store a consignment, as it has one for the recipient already
+    #[error("Consignment for recipient {0} refused by the proxy server")]
+    RefusedConsignment(String),
+
     /// The requested transfer was not found
     #[error("Transfer with blinded UTXO {0} not found")]
     TransferNotFound(String),
 
+    /// The value of an output spent by the provided PSBT cannot be determined
+    #[error("Unknown PSBT input: {0}")]
+    UnknownPsbtInput(String),
+
     /// The detected RGB schema is unknown
     #[error("Unknown RGB schema: {0}")]
     UnknownRgbSchema(String),
@@ -159,6 +227,10 @@ pub enum Error {
     #[error("Invoice type is not supported")]
     UnsupportedInvoice,
 
+    /// The proxy server reports a protocol version not supported by this library
+    #[error("Unsupported proxy version: {0}")]
+    UnsupportedProxyVersion(String),
+
     /// The requested operation cannot be processed by a watch-only wallet
     #[error("Operation not allowed on watch only wallet")]
     WatchOnly(),
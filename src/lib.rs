@@ -9,6 +9,12 @@
 //! It allows to create and operate an RGB wallet that can issue, send and receive RGB20 and RGB121
 //! assets. The library also manages UTXOs and asset allocations.
 //!
+//! To use a wallet from async code it can be wrapped in an [`AsyncWallet`](wallet::AsyncWallet),
+//! which runs its operations on a dedicated thread.
+//!
+//! Pending transfers can be kept progressing in the background with an
+//! [`AutoRefresh`](wallet::AutoRefresh) worker.
+//!
 //! ## Backend
 //! The library uses BDK for walleting operations and several components from the RGB ecosystem for
 //! RGB asset operations.
@@ -50,6 +56,8 @@
 //!         database_type: DatabaseType::Sqlite,
 //!         pubkey: keys.xpub,
 //!         mnemonic: Some(keys.mnemonic),
+//!         min_confirmations: 1,
+//!         begun_send_duration: None,
 //!     };
 //!     let wallet = Wallet::new(wallet_data)?;
 //!
@@ -0,0 +1,385 @@
+//! Async interface to the wallet
+//!
+//! This module defines the [`AsyncWallet`] structure, which allows operating a [`Wallet`] from
+//! async code.
+//!
+//! The wallet itself stays blocking: the RGB node RPC client and the electrum resolver used to
+//! validate consignments only have blocking versions, so the wallet can't be made natively async
+//! by switching the database, proxy and electrum clients alone. Operations run on a worker thread
+//! instead, which only lives while the wallet has operations to run.
+
+use futures::channel::oneshot;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::error::{Error, InternalError};
+use crate::wallet::auto_refresh::refresh_pending;
+use crate::wallet::{
+    AssetRgb121, AssetRgb20, AssetType, Assets, AutoRefresh, Balance, BlindData, CpfpResult,
+    CreateUtxosResult, DrainToResult, Metadata, NetworkPolicy, Online, ProxyOptions, Recipient,
+    RefreshedTransfer, SendOptions, SendPreview, SendResult, Transfer, TransferListener, Unspent,
+    Wallet, WalletData,
+};
+
+type Job = Box<dyn FnOnce(&mut Wallet) + Send>;
+
+struct QueueState {
+    /// The wallet, taken by the worker thread while it runs
+    wallet: Option<Wallet>,
+    jobs: VecDeque<Job>,
+    running: bool,
+    /// Set when an operation panicked, losing the wallet
+    stopped: bool,
+}
+
+/// Queue of the operations requested on a wallet
+///
+/// Operations are run in order by a worker thread, started when the first one is queued and
+/// exiting as soon as the queue is empty, so idle wallets don't hold a thread.
+#[derive(Clone)]
+struct JobQueue(Arc<Mutex<QueueState>>);
+
+impl JobQueue {
+    fn new(wallet: Wallet) -> Self {
+        JobQueue(Arc::new(Mutex::new(QueueState {
+            wallet: Some(wallet),
+            jobs: VecDeque::new(),
+            running: false,
+            stopped: false,
+        })))
+    }
+
+    fn _state(&self) -> MutexGuard<QueueState> {
+        // jobs never run while holding the lock, so it can't be poisoned by a failed operation
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue `job`, starting the worker thread if needed
+    ///
+    /// Returns false if the queue has stopped after an operation panicked.
+    fn push(&self, job: Job) -> bool {
+        let mut state = self._state();
+        if state.stopped {
+            return false;
+        }
+        state.jobs.push_back(job);
+        if !state.running {
+            state.running = true;
+            let queue = self.clone();
+            thread::spawn(move || queue._run());
+        }
+        true
+    }
+
+    fn _run(self) {
+        let _guard = StopOnPanic(self.clone());
+        let mut wallet = self
+            ._state()
+            .wallet
+            .take()
+            .expect("wallet should be available when starting the worker");
+        loop {
+            let job = {
+                let mut state = self._state();
+                match state.jobs.pop_front() {
+                    Some(job) => job,
+                    None => {
+                        state.wallet = Some(wallet);
+                        state.running = false;
+                        return;
+                    }
+                }
+            };
+            job(&mut wallet);
+        }
+    }
+}
+
+/// Stop the queue if the worker thread panics, dropping the pending operations so their callers
+/// get an error
+struct StopOnPanic(JobQueue);
+
+impl Drop for StopOnPanic {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let mut state = self.0._state();
+            state.stopped = true;
+            state.running = false;
+            state.jobs.clear();
+        }
+    }
+}
+
+/// An RGB wallet that can be operated from async code
+///
+/// The requested operations are run one at a time, in the order they have been requested, on a
+/// worker thread that only lives while there are operations to run. Each method returns a future
+/// that resolves when the operation completes, so the calling executor is never blocked while
+/// waiting for the wallet, its database or the network.
+///
+/// If an operation panics the wallet is lost and all following calls return an error.
+///
+/// This is a wrapper around the blocking [`Wallet`], not a native async implementation:
+/// operations still use `block_on` for the database, the blocking proxy and electrum clients and
+/// the synchronous RGB node RPC client, they just do so on the worker thread.
+pub struct AsyncWallet {
+    queue: JobQueue,
+    auto_refresh: Mutex<Option<AutoRefresh>>,
+}
+
+macro_rules! async_methods {
+    ($(
+        $(#[$attr:meta])*
+        $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub async fn $name(&self, $($arg: $ty),*) -> Result<$ret, Error> {
+                self.run(move |wallet| wallet.$name($($arg),*)).await?
+            }
+        )*
+    };
+}
+
+impl AsyncWallet {
+    /// Create a new RGB wallet based on the provided [`WalletData`]
+    ///
+    /// The wallet is created on a separate thread, see [`Wallet::new`] for details.
+    pub async fn new(wallet_data: WalletData) -> Result<Self, Error> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _ = sender.send(Wallet::new(wallet_data));
+        });
+        let wallet = receiver.await.map_err(|_| InternalError::Unexpected)??;
+        Ok(AsyncWallet::from(wallet))
+    }
+
+    /// Run `operation` on the worker thread, passing it the wallet, and return its result
+    ///
+    /// This allows calling any [`Wallet`] method from async code.
+    pub async fn run<T, F>(&self, operation: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Wallet) -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move |wallet| {
+            let _ = sender.send(operation(wallet));
+        });
+        if !self.queue.push(job) {
+            return Err(InternalError::Unexpected)?;
+        }
+        Ok(receiver.await.map_err(|_| InternalError::Unexpected)?)
+    }
+
+    /// Return the [`WalletData`] for this wallet
+    pub async fn get_wallet_data(&self) -> Result<WalletData, Error> {
+        self.run(|wallet| wallet.get_wallet_data()).await
+    }
+
+    /// Return the data directory path for this wallet
+    pub async fn get_wallet_dir(&self) -> Result<PathBuf, Error> {
+        self.run(|wallet| wallet.get_wallet_dir()).await
+    }
+
+    /// Return a new bitcoin address, see [`Wallet::get_address`]
+    pub async fn get_address(&self) -> Result<String, Error> {
+        self.run(|wallet| wallet.get_address()).await
+    }
+
+    /// Remove the transfer listener, see [`Wallet::remove_transfer_listener`]
+    pub async fn remove_transfer_listener(&self) -> Result<(), Error> {
+        self.run(|wallet| wallet.remove_transfer_listener()).await
+    }
+
+    /// Register a transfer listener, see [`Wallet::set_transfer_listener`]
+    ///
+    /// The listener is called from the worker thread.
+    pub async fn set_transfer_listener(
+        &self,
+        listener: Box<dyn TransferListener>,
+    ) -> Result<(), Error> {
+        self.run(move |wallet| wallet.set_transfer_listener(listener))
+            .await
+    }
+
+    /// Start refreshing pending transfers in the background every `interval_seconds`
+    ///
+    /// Refreshes are queued along with the other operations, see
+    /// [`AutoRefresh`] for details. A refresh is not queued again while the previous one is still
+    /// pending. Any auto-refresh previously started is replaced.
+    pub async fn start_auto_refresh(
+        &self,
+        online: Online,
+        interval_seconds: u32,
+    ) -> Result<(), Error> {
+        let check_online = online.clone();
+        self.run(move |wallet| wallet._check_online(check_online))
+            .await??;
+        let queue = self.queue.clone();
+        let queued = Arc::new(AtomicBool::new(false));
+        let auto_refresh = AutoRefresh::spawn(interval_seconds, move || {
+            if queued.swap(true, Ordering::SeqCst) {
+                return true;
+            }
+            let online = online.clone();
+            let queued = queued.clone();
+            let job: Job = Box::new(move |wallet| {
+                refresh_pending(wallet, online);
+                queued.store(false, Ordering::SeqCst);
+            });
+            queue.push(job)
+        })?;
+        *self
+            .auto_refresh
+            .lock()
+            .map_err(|_| InternalError::Unexpected)? = Some(auto_refresh);
+        Ok(())
+    }
+
+    /// Stop refreshing pending transfers in the background, if started
+    pub async fn stop_auto_refresh(&self) -> Result<(), Error> {
+        let auto_refresh = self
+            .auto_refresh
+            .lock()
+            .map_err(|_| InternalError::Unexpected)?
+            .take();
+        if let Some(mut auto_refresh) = auto_refresh {
+            auto_refresh.stop();
+        }
+        Ok(())
+    }
+
+    async_methods! {
+        /// See [`Wallet::abort_send`]
+        abort_send(txid: String) -> ();
+        /// See [`Wallet::blind`]
+        blind(
+            asset_id: Option<String>,
+            amount: Option<u64>,
+            duration_seconds: Option<u32>,
+            transport_endpoints: Vec<String>,
+            min_confirmations: Option<u8>
+        ) -> BlindData;
+        /// See [`Wallet::bump_fee`]
+        bump_fee(online: Online, txid: String, fee_rate: Option<f32>) -> SendResult;
+        /// See [`Wallet::bump_fee_begin`]
+        bump_fee_begin(online: Online, txid: String, fee_rate: Option<f32>) -> String;
+        /// See [`Wallet::bump_fee_end`]
+        bump_fee_end(online: Online, signed_psbt: String) -> SendResult;
+        /// See [`Wallet::cpfp`]
+        cpfp(online: Online, txid: String, fee_rate: Option<f32>) -> CpfpResult;
+        /// See [`Wallet::cpfp_begin`]
+        cpfp_begin(online: Online, txid: String, fee_rate: Option<f32>) -> String;
+        /// See [`Wallet::cpfp_end`]
+        cpfp_end(online: Online, signed_psbt: String) -> CpfpResult;
+        /// See [`Wallet::create_utxos`]
+        create_utxos(
+            online: Online,
+            up_to: bool,
+            num: Option<u8>,
+            size: Option<u32>,
+            fee_rate: Option<f32>
+        ) -> CreateUtxosResult;
+        /// See [`Wallet::create_utxos_begin`]
+        create_utxos_begin(
+            online: Online,
+            up_to: bool,
+            num: Option<u8>,
+            size: Option<u32>,
+            fee_rate: Option<f32>
+        ) -> String;
+        /// See [`Wallet::create_utxos_end`]
+        create_utxos_end(online: Online, signed_psbt: String) -> CreateUtxosResult;
+        /// See [`Wallet::delete_transfers`]
+        delete_transfers(blinded_utxo: Option<String>, txid: Option<String>) -> ();
+        /// See [`Wallet::drain_to`]
+        drain_to(online: Online, address: String, destroy_assets: bool, fee_rate: Option<f32>)
+            -> DrainToResult;
+        /// See [`Wallet::drain_to_begin`]
+        drain_to_begin(online: Online, address: String, destroy_assets: bool, fee_rate: Option<f32>)
+            -> String;
+        /// See [`Wallet::drain_to_end`]
+        drain_to_end(online: Online, signed_psbt: String) -> DrainToResult;
+        /// See [`Wallet::estimate_fee`]
+        estimate_fee(online: Online, target_blocks: u16) -> f32;
+        /// See [`Wallet::extend_blind_expiration`]
+        extend_blind_expiration(invoice: String, duration_seconds: Option<u32>) -> BlindData;
+        /// See [`Wallet::extend_send_expiration`]
+        extend_send_expiration(txid: String, duration_seconds: Option<u32>) -> Option<i64>;
+        /// See [`Wallet::fail_transfers`]
+        fail_transfers(online: Online, blinded_utxo: Option<String>, txid: Option<String>) -> ();
+        /// See [`Wallet::get_asset_balance`]
+        get_asset_balance(asset_id: String) -> Balance;
+        /// See [`Wallet::get_asset_metadata`]
+        get_asset_metadata(online: Online, asset_id: String) -> Metadata;
+        /// See [`Wallet::go_online`]
+        go_online(
+            skip_consistency_check: bool,
+            electrum_url: String,
+            proxy_urls: Vec<String>,
+            proxy_options: Option<ProxyOptions>,
+            network_policy: Option<NetworkPolicy>
+        ) -> Online;
+        /// See [`Wallet::issue_asset_rgb20`]
+        issue_asset_rgb20(
+            online: Online,
+            ticker: String,
+            name: String,
+            precision: u8,
+            amounts: Vec<u64>
+        ) -> AssetRgb20;
+        /// See [`Wallet::issue_asset_rgb121`]
+        issue_asset_rgb121(
+            online: Online,
+            name: String,
+            description: Option<String>,
+            precision: u8,
+            amounts: Vec<u64>,
+            parent_id: Option<String>,
+            file_path: Option<String>
+        ) -> AssetRgb121;
+        /// See [`Wallet::list_assets`]
+        list_assets(filter_asset_types: Vec<AssetType>) -> Assets;
+        /// See [`Wallet::list_transfers`]
+        list_transfers(asset_id: String) -> Vec<Transfer>;
+        /// See [`Wallet::list_unspents`]
+        list_unspents(settled_only: bool) -> Vec<Unspent>;
+        /// See [`Wallet::preview_send`]
+        preview_send(
+            online: Online,
+            recipient_map: HashMap<String, Vec<Recipient>>,
+            options: SendOptions
+        ) -> SendPreview;
+        /// See [`Wallet::refresh`]
+        refresh(online: Online, asset_id: Option<String>) -> Vec<RefreshedTransfer>;
+        /// See [`Wallet::send`]
+        send(
+            online: Online,
+            recipient_map: HashMap<String, Vec<Recipient>>,
+            donation: bool,
+            options: SendOptions
+        ) -> SendResult;
+        /// See [`Wallet::send_begin`]
+        send_begin(
+            online: Online,
+            recipient_map: HashMap<String, Vec<Recipient>>,
+            donation: bool,
+            options: SendOptions
+        ) -> String;
+        /// See [`Wallet::send_end`]
+        send_end(online: Online, signed_psbt: String) -> SendResult;
+    }
+}
+
+impl From<Wallet> for AsyncWallet {
+    fn from(wallet: Wallet) -> Self {
+        AsyncWallet {
+            queue: JobQueue::new(wallet),
+            auto_refresh: Mutex::new(None),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Background refresh of pending transfers
+//!
+//! This module defines the [`AutoRefresh`] worker, which periodically processes the pending
+//! transfers of a wallet, so they keep progressing even if the application doesn't call
+//! [`Wallet::refresh`] itself.
+
+use amplify::s;
+use slog::{debug, error, warn};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::{Error, InternalError};
+use crate::wallet::{Online, Wallet};
+
+/// A background worker refreshing the pending transfers of a wallet
+///
+/// Every `interval_seconds` the worker fails the expired transfers and refreshes all pending
+/// ones, logging any error and retrying at the next round. Status changes are reported to the
+/// wallet's [`TransferListener`](crate::wallet::TransferListener), if any.
+///
+/// The worker stops when [`stop`](AutoRefresh::stop) is called, the `AutoRefresh` is dropped or
+/// the lock on the wallet is poisoned by a panic in another thread.
+pub struct AutoRefresh {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AutoRefresh {
+    /// Start refreshing the provided wallet every `interval_seconds`
+    ///
+    /// The wallet is shared with the application through the provided mutex, which the worker
+    /// holds for the duration of each refresh, so foreground calls are never run concurrently
+    /// with it.
+    pub fn start(
+        wallet: Arc<Mutex<Wallet>>,
+        online: Online,
+        interval_seconds: u32,
+    ) -> Result<Self, Error> {
+        match wallet.lock() {
+            Ok(wallet) => wallet._check_online(online.clone())?,
+            Err(e) => {
+                error!(
+                    e.into_inner().logger,
+                    "Cannot auto-refresh, wallet lock is poisoned"
+                );
+                return Err(InternalError::Unexpected)?;
+            }
+        }
+        Self::spawn(interval_seconds, move || match wallet.lock() {
+            Ok(mut wallet) => {
+                refresh_pending(&mut wallet, online.clone());
+                true
+            }
+            Err(e) => {
+                error!(
+                    e.into_inner().logger,
+                    "Auto-refresh stopped, wallet lock is poisoned"
+                );
+                false
+            }
+        })
+    }
+
+    /// Spawn a worker calling `tick` every `interval_seconds`, until it returns false or the
+    /// worker is stopped
+    pub(crate) fn spawn(
+        interval_seconds: u32,
+        mut tick: impl FnMut() -> bool + Send + 'static,
+    ) -> Result<Self, Error> {
+        if interval_seconds == 0 {
+            return Err(Error::InvalidRefreshInterval(s!("must be greater than 0")));
+        }
+        let interval = Duration::from_secs(interval_seconds as u64);
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let worker_stopped = stopped.clone();
+        let handle = thread::spawn(move || loop {
+            let (lock, cvar) = &*worker_stopped;
+            let guard = lock.lock().expect("auto-refresh");
+            let (guard, _) = cvar
+                .wait_timeout_while(guard, interval, |stopped| !*stopped)
+                .expect("auto-refresh");
+            if *guard {
+                break;
+            }
+            drop(guard);
+            if !tick() {
+                *lock.lock().expect("auto-refresh") = true;
+                break;
+            }
+        });
+        Ok(AutoRefresh {
+            stopped,
+            handle: Some(handle),
+        })
+    }
+
+    /// Whether the worker is still running
+    pub fn is_running(&self) -> bool {
+        !*self.stopped.0.lock().expect("auto-refresh")
+    }
+
+    /// Stop the worker, waiting for the refresh in progress, if any, to complete
+    ///
+    /// Must not be called while holding the lock on the wallet.
+    pub fn stop(&mut self) {
+        self._signal_stop();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn _signal_stop(&self) {
+        let (lock, cvar) = &*self.stopped;
+        *lock.lock().expect("auto-refresh") = true;
+        cvar.notify_all();
+    }
+}
+
+impl Drop for AutoRefresh {
+    /// Signal the worker to stop without waiting for it, so the `AutoRefresh` can be dropped
+    /// while holding the lock on the wallet. A refresh in progress completes in the background.
+    fn drop(&mut self) {
+        self._signal_stop();
+    }
+}
+
+/// Fail the expired transfers and refresh the pending ones
+pub(crate) fn refresh_pending(wallet: &mut Wallet, online: Online) {
+    debug!(wallet.logger, "Auto-refreshing pending transfers...");
+    // failures on single transfers are logged by refresh
+    let res = wallet
+        ._handle_expired_transfers()
+        .and_then(|_| wallet.refresh(online, None).map(|_| ()));
+    if let Err(e) = res {
+        warn!(wallet.logger, "Auto-refresh failed: {}", e);
+    }
+}
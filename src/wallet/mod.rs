@@ -6,24 +6,29 @@ use amplify::{bmap, bset, empty, s, Wrapper};
 use amplify_num::hex::FromHex;
 use bdk::bitcoin::secp256k1::Secp256k1;
 use bdk::bitcoin::Network as BdkNetwork;
-use bdk::blockchain::{
-    Blockchain, ConfigurableBlockchain, ElectrumBlockchain, ElectrumBlockchainConfig,
-};
+use bdk::blockchain::{Blockchain, ElectrumBlockchain};
 use bdk::database::any::SqliteDbConfiguration as BdkSqliteDbConfiguration;
 use bdk::database::{
     ConfigurableDatabase as BdkConfigurableDatabase, SqliteDatabase as BdkSqliteDatabase,
 };
+use bdk::electrum_client::{
+    Client as BdkElectrumClient, ConfigBuilder as BdkElectrumConfigBuilder,
+    Error as BdkElectrumError,
+};
 use bdk::keys::bip39::{Language, Mnemonic};
 use bdk::keys::{DerivableKey, ExtendedKey};
 use bdk::wallet::AddressIndex;
-use bdk::{FeeRate, KeychainKind, LocalUtxo, SignOptions, SyncOptions, Wallet as BdkWallet};
+use bdk::{
+    Error as BdkError, FeeRate, KeychainKind, LocalUtxo, SignOptions, SyncOptions,
+    Wallet as BdkWallet,
+};
 use bitcoin::consensus::{deserialize, serialize};
-use bitcoin::hashes::{sha256, Hash as Sha256Hash};
+use bitcoin::hashes::{sha256, sha256d, Hash as Sha256Hash};
 use bitcoin::psbt::serialize::Deserialize as BitcoinDeserialize;
 use bitcoin::psbt::PartiallySignedTransaction;
 use bitcoin::util::bip32::ExtendedPubKey;
 use bitcoin::Txid;
-use bitcoin::{Address, OutPoint, Transaction};
+use bitcoin::{Address, OutPoint, Script, Transaction};
 use bp::seals::txout::blind::ConcealedSeal;
 use bp::seals::txout::{CloseMethod, ExplicitSeal};
 use chrono::NaiveDateTime;
@@ -31,10 +36,11 @@ use commit_verify::commit_verify::CommitVerify;
 use electrum_client::{Client as ElectrumClient, ConfigBuilder, ElectrumApi, Param};
 use futures::executor::block_on;
 use internet2::addr::ServiceAddr;
-use invoice::{AmountExt, Beneficiary, Invoice as UniversalInvoice};
+use invoice::{AmountExt, Beneficiary, Details, Invoice as UniversalInvoice};
 use lnpbp::chain::{AssetId, Chain as RgbNetwork};
 use psbt::Psbt;
 use reqwest::blocking::Client as RestClient;
+use reqwest::Url;
 use rgb::blank::{BlankBundle, Error as BlankError};
 use rgb::fungible::allocation::{AllocatedValue, OutpointValue as RgbOutpointValue, UtxobValue};
 use rgb::psbt::{RgbExt, RgbInExt};
@@ -62,23 +68,26 @@ use rgb_rpc::client::Client;
 use rgb_rpc::{ContractValidity, Reveal};
 use sea_orm::{ActiveValue, ConnectOptions, Database, DeriveActiveEnum, EnumIter};
 use serde::{Deserialize, Serialize};
-use slog::{debug, error, info, Logger};
+use slog::{debug, error, info, warn, Logger};
+use std::cell::{Ref, RefCell};
 use std::cmp::min;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::fs;
 use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use stens::AsciiString;
 use stored::Config as StoreConfig;
 use strict_encoding::{strict_deserialize, strict_serialize, StrictDecode, StrictEncode};
 
-use crate::api::proxy::AckResponse;
+use crate::api::encryption;
+use crate::api::proxy::{build_proxy_client, AckResponse, ProxyVersion};
 use crate::api::Proxy;
 use crate::database::entities::asset_rgb121::Model as DbAssetRgb121;
 use crate::database::entities::asset_rgb20::Model as DbAssetRgb20;
@@ -98,6 +107,12 @@ use crate::utils::{
     BitcoinNetwork,
 };
 
+mod async_wallet;
+mod auto_refresh;
+
+pub use async_wallet::AsyncWallet;
+pub use auto_refresh::AutoRefresh;
+
 const RGB_DB_NAME: &str = "rgb_db";
 const BDK_DB_NAME: &str = "bdk_db";
 
@@ -105,25 +120,41 @@ const ASSETS_DIR: &str = "assets";
 const TRANSFER_DIR: &str = "transfers";
 const TRANSFER_DATA_FILE: &str = "transfer_data.txt";
 const SIGNED_PSBT_FILE: &str = "signed.psbt";
+const SEND_JOURNAL_FILE: &str = "send_journal.txt";
 const CONSIGNMENT_FILE: &str = "consignment_out";
 const CONSIGNMENT_RCV_FILE: &str = "rcv_compose.rgbc";
+const CONSIGNMENT_ENC_FILE: &str = "consignment_enc";
+const MEDIA_ENC_FILE: &str = "media_enc";
 const MEDIA_FNAME: &str = "media";
 const MIME_FNAME: &str = "mime";
 
+const INVOICE_EXTENSIONS_PREFIX: &str = "rgb-lib:";
+
 const MIN_BTC_REQUIRED: u64 = 2000;
 
+const MIN_FEE_RATE: f32 = 1.0;
+const MAX_FEE_RATE: f32 = 1000.0;
+const FEE_TARGET_BLOCKS: u16 = 6;
+const MAX_FEE_TARGET_BLOCKS: u16 = 1008;
+
 const UTXO_SIZE: u32 = 1000;
 const UTXO_NUM: u8 = 5;
 
-const MIN_CONFIRMATIONS: u8 = 1;
-
 const MAX_ALLOCATIONS_PER_UTXO: u32 = 5;
+const MAX_CONSIGNMENT_REPLACEMENTS: u32 = 100;
 
-const DURATION_SEND_TRANSFER: i64 = 3600;
+const BNB_MAX_TRIES: u32 = 100_000;
+
+const DURATION_SEND_TRANSFER: u32 = 3600;
 const DURATION_RCV_TRANSFER: u32 = 86400;
+const DURATION_BEGUN_SEND: u32 = 86400;
 
 const ELECTRUM_TIMEOUT: u8 = 4;
 const PROXY_TIMEOUT: u8 = 90;
+const RGB_NODE_TIMEOUT: u8 = 10;
+const NETWORK_RETRIES: u8 = 3;
+const BACKOFF_INITIAL_MS: u32 = 500;
+const BACKOFF_MAX_MS: u32 = 8000;
 
 /// The type of an asset
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -333,6 +364,8 @@ pub struct BlindData {
     pub blinding_secret: u64,
     /// Expiration of the `blinded_utxo`
     pub expiration_timestamp: Option<i64>,
+    /// Key the sender will use to encrypt the consignment
+    pub consignment_key: String,
 }
 
 /// An RGB blinded UTXO
@@ -350,6 +383,24 @@ impl BlindedUTXO {
     }
 }
 
+/// The result of a CPFP operation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CpfpResult {
+    /// TXID of the broadcast child transaction
+    pub txid: String,
+    /// Fee paid by the child transaction, in sats
+    pub fee: u64,
+}
+
+/// The result of a UTXO creation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreateUtxosResult {
+    /// Number of created UTXOs
+    pub num_utxos: u8,
+    /// Fee paid by the transaction, in sats
+    pub fee: u64,
+}
+
 /// Supported database types
 #[derive(Clone)]
 pub enum DatabaseType {
@@ -357,11 +408,202 @@ pub enum DatabaseType {
     Sqlite,
 }
 
+/// The result of a drain operation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DrainToResult {
+    /// TXID of the broadcast transaction
+    pub txid: String,
+    /// Fee paid by the transaction, in sats
+    pub fee: u64,
+}
+
+/// The strategy used to select the UTXOs to spend for each asset of a send
+///
+/// When no strategy is given, UTXOs are spent in the order they're stored in the wallet database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputSelection {
+    /// Spend the UTXOs holding the largest amounts of the asset first
+    LargestFirst,
+    /// Spend the single UTXO holding the smallest amount of the asset that covers the amount
+    /// being sent, falling back to `LargestFirst` if no single UTXO is enough
+    SmallestSufficient,
+    /// Look for UTXOs holding exactly the amount of the asset being sent, so no asset change is
+    /// needed, falling back to `SmallestSufficient` if there are none
+    BranchAndBound,
+    /// Spend UTXOs holding allocations of other assets, which need to be moved with blank
+    /// transitions, only if the other ones are not enough
+    AvoidMerging,
+}
+
+/// Return the indexes of the `amounts`, taken in the provided `order`, needed to cover
+/// `amount_needed`, or `None` if they are not enough
+fn select_in_order(
+    amounts: &[u64],
+    order: impl Iterator<Item = usize>,
+    amount_needed: u64,
+) -> Option<Vec<usize>> {
+    let mut selected = vec![];
+    let mut total = 0;
+    for idx in order {
+        selected.push(idx);
+        total += amounts[idx];
+        if total >= amount_needed {
+            return Some(selected);
+        }
+    }
+    None
+}
+
+/// Depth-first search of the `amounts` (sorted according to `order`) adding up to `target`,
+/// starting from position `pos`
+///
+/// `remaining` holds the sum of the amounts from each position to the end of `order`, so
+/// branches that cannot reach the target are skipped.
+fn bnb_search(
+    amounts: &[u64],
+    order: &[usize],
+    remaining: &[u64],
+    pos: usize,
+    target: u64,
+    selected: &mut Vec<usize>,
+    tries: &mut u32,
+) -> bool {
+    if target == 0 {
+        return true;
+    }
+    if pos == order.len() || remaining[pos] < target || *tries >= BNB_MAX_TRIES {
+        return false;
+    }
+    *tries += 1;
+    let amount = amounts[order[pos]];
+    if amount <= target {
+        selected.push(order[pos]);
+        if bnb_search(
+            amounts,
+            order,
+            remaining,
+            pos + 1,
+            target - amount,
+            selected,
+            tries,
+        ) {
+            return true;
+        }
+        selected.pop();
+    }
+    bnb_search(amounts, order, remaining, pos + 1, target, selected, tries)
+}
+
+/// Return the indexes of the `amounts` to spend to cover `amount_needed` according to the
+/// provided `input_selection`, or `None` if all of them together are not enough
+///
+/// `merging` tells, for each amount, if the UTXO holding it also holds allocations of other
+/// assets.
+fn select_amounts(
+    amounts: &[u64],
+    merging: &[bool],
+    amount_needed: u64,
+    input_selection: Option<InputSelection>,
+) -> Option<Vec<usize>> {
+    // sorting is stable, so UTXOs holding the same amount keep the database order
+    let mut largest_first: Vec<usize> = (0..amounts.len()).collect();
+    largest_first.sort_by(|a, b| amounts[*b].cmp(&amounts[*a]));
+    match input_selection {
+        None => select_in_order(amounts, 0..amounts.len(), amount_needed),
+        Some(InputSelection::LargestFirst) => {
+            select_in_order(amounts, largest_first.into_iter(), amount_needed)
+        }
+        Some(InputSelection::SmallestSufficient) => {
+            match largest_first
+                .iter()
+                .rev()
+                .find(|i| amounts[**i] >= amount_needed)
+            {
+                Some(i) => Some(vec![*i]),
+                None => select_in_order(amounts, largest_first.into_iter(), amount_needed),
+            }
+        }
+        Some(InputSelection::BranchAndBound) => {
+            let mut remaining = vec![0; largest_first.len() + 1];
+            for pos in (0..largest_first.len()).rev() {
+                remaining[pos] = remaining[pos + 1] + amounts[largest_first[pos]];
+            }
+            let mut selected = vec![];
+            let mut tries = 0;
+            if amount_needed > 0
+                && bnb_search(
+                    amounts,
+                    &largest_first,
+                    &remaining,
+                    0,
+                    amount_needed,
+                    &mut selected,
+                    &mut tries,
+                )
+            {
+                Some(selected)
+            } else {
+                select_amounts(
+                    amounts,
+                    merging,
+                    amount_needed,
+                    Some(InputSelection::SmallestSufficient),
+                )
+            }
+        }
+        Some(InputSelection::AvoidMerging) => {
+            let (separate, merged): (Vec<usize>, Vec<usize>) =
+                largest_first.into_iter().partition(|i| !merging[*i]);
+            match separate
+                .iter()
+                .rev()
+                .find(|i| amounts[**i] >= amount_needed)
+            {
+                Some(i) => Some(vec![*i]),
+                None => select_in_order(amounts, separate.into_iter().chain(merged), amount_needed),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct InfoBatchTransfer {
     change_utxo_idx: i64,
     blank_allocations: HashMap<String, u64>,
     donation: bool,
+    #[serde(default)]
+    replaced_txid: Option<String>,
+    #[serde(default)]
+    min_confirmations: Option<u8>,
+    #[serde(default)]
+    duration_seconds: Option<u32>,
+    #[serde(default)]
+    retry_without_nacks: bool,
+    #[serde(default)]
+    retried_txid: Option<String>,
+    #[serde(default)]
+    change_script: Option<Script>,
+    #[serde(default)]
+    begun_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+enum SendJournalStep {
+    Posting,
+    Saving,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct InfoSendJournal {
+    step: SendJournalStep,
+    posted_consignments: HashMap<String, InfoPostedConsignment>,
+}
+
+/// Where the consignment for a recipient has been posted
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct InfoPostedConsignment {
+    proxy_url: String,
+    recipient_id: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -371,6 +613,61 @@ struct InfoAssetTransfer {
     asset_type: AssetType,
 }
 
+/// Invoice fields not supported by the lnpbp invoice format, carried in its details
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct InvoiceExtensions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consignment_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    transport_endpoints: Vec<String>,
+}
+
+impl InvoiceExtensions {
+    fn from_invoice(invoice: &UniversalInvoice) -> Result<Self, Error> {
+        let extensions: InvoiceExtensions = match invoice
+            .details()
+            .as_ref()
+            .and_then(|d| d.source.strip_prefix(INVOICE_EXTENSIONS_PREFIX))
+        {
+            Some(ext) => serde_json::from_str(ext).map_err(|_| Error::UnsupportedInvoice)?,
+            None => return Ok(InvoiceExtensions::default()),
+        };
+        if let Some(key) = &extensions.consignment_key {
+            encryption::check_key(key)?;
+        }
+        check_transport_endpoints(&extensions.transport_endpoints)?;
+        Ok(extensions)
+    }
+
+    fn add_to_invoice(&self, invoice: &mut UniversalInvoice) -> Result<(), Error> {
+        let source = format!(
+            "{}{}",
+            INVOICE_EXTENSIONS_PREFIX,
+            serde_json::to_string(self).map_err(InternalError::from)?
+        );
+        invoice.set_details(Details {
+            commitment: sha256d::Hash::hash(source.as_bytes()),
+            source,
+        });
+        Ok(())
+    }
+}
+
+/// Check the provided transport endpoints are valid proxy server URLs
+fn check_transport_endpoints(transport_endpoints: &[String]) -> Result<(), Error> {
+    for endpoint in transport_endpoints {
+        let url =
+            Url::parse(endpoint).map_err(|e| Error::InvalidTransportEndpoint(e.to_string()))?;
+        if !["http", "https"].contains(&url.scheme()) {
+            return Err(Error::InvalidTransportEndpoint(format!(
+                "unsupported scheme '{}'",
+                url.scheme()
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// An RGB invoice
 pub struct Invoice {
     /// The RGB invoice in bech32 encoding
@@ -395,11 +692,14 @@ impl Invoice {
             _ => return Err(Error::UnsupportedInvoice),
         };
         let expiration_timestamp = decoded.expiry().as_ref().map(|exp| exp.timestamp());
+        let extensions = InvoiceExtensions::from_invoice(&decoded)?;
         let invoice_data = InvoiceData {
             blinded_utxo,
             asset_id,
             amount,
             expiration_timestamp,
+            consignment_key: extensions.consignment_key,
+            transport_endpoints: extensions.transport_endpoints,
         };
 
         Ok(Invoice {
@@ -426,6 +726,17 @@ impl Invoice {
                 invoice.set_expiry(expiry);
             }
         }
+        if let Some(key) = &invoice_data.consignment_key {
+            encryption::check_key(key)?;
+        }
+        check_transport_endpoints(&invoice_data.transport_endpoints)?;
+        if invoice_data.consignment_key.is_some() || !invoice_data.transport_endpoints.is_empty() {
+            InvoiceExtensions {
+                consignment_key: invoice_data.consignment_key.clone(),
+                transport_endpoints: invoice_data.transport_endpoints.clone(),
+            }
+            .add_to_invoice(&mut invoice)?;
+        }
 
         let bech32_invoice = invoice.to_string();
 
@@ -457,6 +768,170 @@ pub struct InvoiceData {
     pub amount: Option<u64>,
     /// Invoice expiration
     pub expiration_timestamp: Option<i64>,
+    /// Key to encrypt the consignment with, missing in invoices not supporting encryption
+    pub consignment_key: Option<String>,
+    /// URLs of the proxy servers the recipient wants the consignment to be posted to
+    pub transport_endpoints: Vec<String>,
+}
+
+/// Timeouts and retry behavior for the calls to the electrum server, the proxy server and the
+/// RGB node
+///
+/// Failed calls are retried up to `max_retries` times, waiting `backoff_initial_ms`
+/// milliseconds before the first retry and doubling the wait on each subsequent one, up to
+/// `backoff_max_ms`. The `retry_on_*` flags select which failures are considered transient.
+/// The electrum connection is re-established before retrying an electrum call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetworkPolicy {
+    /// Timeout, in seconds, for each call to the proxy server
+    pub proxy_timeout: u8,
+    /// Timeout, in seconds, for each call to the electrum server
+    pub electrum_timeout: u8,
+    /// Time, in seconds, to wait for the RGB node to become available
+    pub rgb_node_timeout: u8,
+    /// Maximum number of retries for a failed call
+    pub max_retries: u8,
+    /// Wait, in milliseconds, before the first retry
+    pub backoff_initial_ms: u32,
+    /// Maximum wait, in milliseconds, between two retries
+    pub backoff_max_ms: u32,
+    /// Whether to retry calls that timed out, except for the ones which may have been processed
+    /// and are never repeated, like POST requests to the proxy server and broadcasts
+    pub retry_on_timeout: bool,
+    /// Whether to retry calls that failed to connect
+    pub retry_on_connect: bool,
+    /// Whether to retry calls that received a server error (HTTP 5xx) response
+    pub retry_on_server_error: bool,
+}
+
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        NetworkPolicy {
+            proxy_timeout: PROXY_TIMEOUT,
+            electrum_timeout: ELECTRUM_TIMEOUT,
+            rgb_node_timeout: RGB_NODE_TIMEOUT,
+            max_retries: NETWORK_RETRIES,
+            backoff_initial_ms: BACKOFF_INITIAL_MS,
+            backoff_max_ms: BACKOFF_MAX_MS,
+            retry_on_timeout: true,
+            retry_on_connect: true,
+            retry_on_server_error: true,
+        }
+    }
+}
+
+impl NetworkPolicy {
+    fn validate(&self) -> Result<(), Error> {
+        if self.proxy_timeout == 0 || self.electrum_timeout == 0 || self.rgb_node_timeout == 0 {
+            return Err(Error::InvalidNetworkPolicy(s!("timeouts must be positive")));
+        }
+        if self.backoff_initial_ms > self.backoff_max_ms {
+            return Err(Error::InvalidNetworkPolicy(s!(
+                "initial backoff cannot exceed the maximum one"
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn proxy_timeout(&self) -> Duration {
+        Duration::from_secs(self.proxy_timeout as u64)
+    }
+
+    /// Wait before the retry following the given number of failed attempts
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let wait = (self.backoff_initial_ms as u64)
+            .checked_shl(attempt)
+            .unwrap_or(u64::MAX);
+        Duration::from_millis(min(wait, self.backoff_max_ms as u64))
+    }
+
+    /// Whether the failed call should be retried
+    ///
+    /// Timeouts are retried only for idempotent calls: a POST that timed out may have been
+    /// processed by the server and repeating it could, for example, post a consignment twice.
+    pub(crate) fn is_retryable(&self, error: &Error, idempotent: bool) -> bool {
+        match error {
+            Error::Proxy(e) => {
+                (self.retry_on_timeout && idempotent && e.is_timeout())
+                    || (self.retry_on_connect && e.is_connect())
+                    || (self.retry_on_server_error
+                        && e.status().map_or(false, |s| s.is_server_error()))
+            }
+            Error::Electrum(e) => self._is_retryable_electrum(e, idempotent),
+            // the RGB node is only reachable through its message bus, errors reported by the
+            // node itself are not transient
+            Error::Internal(InternalError::RgbRpc(rgb_rpc::Error::Esb(_))) => self.retry_on_connect,
+            _ => false,
+        }
+    }
+
+    /// Whether the failed electrum call should be retried
+    ///
+    /// Without retries of its own, the electrum client reports the failure of a call as an empty
+    /// `AllAttemptsErrored`, not telling timeouts apart: it's then retried like a connection
+    /// failure, only if the call is idempotent.
+    fn _is_retryable_electrum(&self, error: &electrum_client::Error, idempotent: bool) -> bool {
+        let io_kind = match error {
+            electrum_client::Error::IOError(e) => e.kind(),
+            electrum_client::Error::SharedIOError(e) => e.kind(),
+            electrum_client::Error::AllAttemptsErrored(errors) => {
+                return match errors.last() {
+                    Some(e) => self._is_retryable_electrum(e, idempotent),
+                    None => self.retry_on_connect && idempotent,
+                }
+            }
+            _ => return false,
+        };
+        match io_kind {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => self.retry_on_timeout && idempotent,
+            _ => self.retry_on_connect,
+        }
+    }
+
+    /// Run `call`, retrying it with exponential backoff while it fails with a retryable error
+    pub(crate) fn retry<T>(
+        &self,
+        logger: &Logger,
+        call: impl FnMut() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self._retry(logger, true, call)
+    }
+
+    /// Run `call` like [`retry`](NetworkPolicy::retry), without retrying it on timeout
+    ///
+    /// To be used for calls that aren't idempotent, like the proxy server POST requests.
+    pub(crate) fn retry_post<T>(
+        &self,
+        logger: &Logger,
+        call: impl FnMut() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self._retry(logger, false, call)
+    }
+
+    fn _retry<T>(
+        &self,
+        logger: &Logger,
+        idempotent: bool,
+        mut call: impl FnMut() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            match call() {
+                Err(e)
+                    if attempt < self.max_retries as u32 && self.is_retryable(&e, idempotent) =>
+                {
+                    let wait = self.backoff(attempt);
+                    warn!(
+                        logger,
+                        "Network call failed ({}), retrying in {:?}", e, wait
+                    );
+                    thread::sleep(wait);
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
 }
 
 /// Data for operations that require the wallet to be online
@@ -466,8 +941,12 @@ pub struct Online {
     pub id: u64,
     /// URL of the electrum server to be used for online operations
     pub electrum_url: String,
-    /// URL of the proxy server to be used for online operations
-    pub proxy_url: String,
+    /// URLs of the proxy servers to be used for online operations, in order of preference
+    pub proxy_urls: Vec<String>,
+    /// Optional settings for the connection to the proxy server
+    pub proxy_options: Option<ProxyOptions>,
+    /// Timeouts and retries used for network calls
+    pub network_policy: NetworkPolicy,
 }
 
 /// Bitcoin transaction outpoint
@@ -500,6 +979,60 @@ impl From<Outpoint> for OutPoint {
     }
 }
 
+/// Credentials to authenticate with the proxy server
+#[derive(Clone, PartialEq, Eq)]
+pub enum ProxyAuth {
+    /// Authentication via a bearer token
+    Bearer {
+        /// The token
+        token: String,
+    },
+    /// HTTP basic authentication
+    Basic {
+        /// User name
+        username: String,
+        /// Optional password
+        password: Option<String>,
+    },
+}
+
+impl fmt::Debug for ProxyAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProxyAuth::Bearer { .. } => f.debug_struct("Bearer").finish_non_exhaustive(),
+            ProxyAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Settings for the connection to the proxy server
+///
+/// They are applied to every request sent to the proxy
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ProxyOptions {
+    /// Optional credentials
+    pub auth: Option<ProxyAuth>,
+    /// Custom headers
+    pub headers: HashMap<String, String>,
+    /// Optional path to a PKCS#12 archive containing the TLS client certificate and its key
+    pub tls_client_cert_path: Option<String>,
+    /// Optional password protecting the PKCS#12 archive
+    pub tls_client_cert_password: Option<String>,
+}
+
+impl fmt::Debug for ProxyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProxyOptions")
+            .field("auth", &self.auth)
+            .field("headers", &self.headers.keys())
+            .field("tls_client_cert_path", &self.tls_client_cert_path)
+            .finish_non_exhaustive()
+    }
+}
+
 /// An RGB recipient
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Recipient {
@@ -507,6 +1040,23 @@ pub struct Recipient {
     pub blinded_utxo: String,
     /// RGB amount
     pub amount: u64,
+    /// Key to encrypt the consignment with, from the recipient's invoice
+    ///
+    /// If not provided the consignment is posted to the proxy in cleartext.
+    pub consignment_key: Option<String>,
+    /// URLs of the proxy servers to post the consignment to, from the recipient's invoice
+    ///
+    /// If empty the consignment is posted to the wallet's own proxy servers.
+    pub transport_endpoints: Vec<String>,
+}
+
+/// A recipient of a plain bitcoin output, paid in the same transaction as the RGB transfers
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct BtcRecipient {
+    /// Bitcoin address
+    pub address: String,
+    /// Amount, in sats
+    pub amount: u64,
 }
 
 /// An RGB allocation
@@ -520,6 +1070,54 @@ pub struct RgbAllocation {
     pub settled: bool,
 }
 
+/// The optional parameters of a send, see [`send_begin`](Wallet::send_begin) for details
+///
+/// The default sends to the RGB recipients only, paying the estimated fee rate and using the
+/// wallet's default confirmations, expiration and input order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SendOptions {
+    /// Recipients of plain bitcoin outputs, paid in the same transaction
+    pub btc_recipients: Vec<BtcRecipient>,
+    /// Whether to prepare the transfer again without the recipients that NACKed it
+    pub retry_without_nacks: bool,
+    /// Fee rate, in sat/vB
+    pub fee_rate: Option<f32>,
+    /// Confirmations needed to settle the transfer
+    pub min_confirmations: Option<u8>,
+    /// Time, in seconds, recipients have to ACK the transfer
+    pub duration_seconds: Option<u32>,
+    /// Strategy used to select the UTXOs to spend for each asset
+    pub input_selection: Option<InputSelection>,
+}
+
+/// The result of a send operation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SendResult {
+    /// TXID of the transfer transaction
+    pub txid: String,
+    /// Fee paid by the transfer transaction, in sats
+    pub fee: u64,
+}
+
+/// The expected effects of a send, as returned by [`preview_send`](Wallet::preview_send)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SendPreview {
+    /// UTXOs spent for each asset, by asset ID
+    pub asset_inputs: HashMap<String, Vec<Outpoint>>,
+    /// UTXOs without allocations spent to cover the fee and the BTC recipients
+    pub btc_inputs: Vec<Outpoint>,
+    /// Estimated fee, in sats, missing if new UTXOs are needed
+    pub fee: Option<u64>,
+    /// UTXO receiving the RGB change and the allocations moved with blank transitions
+    pub change_utxo: Option<Outpoint>,
+    /// RGB change for each asset, by asset ID
+    pub change_allocations: HashMap<String, u64>,
+    /// Amounts of other assets moved to the change UTXO with blank transitions, by asset ID
+    pub blank_allocations: HashMap<String, u64>,
+    /// Whether new UTXOs need to be created with [`create_utxos`](Wallet::create_utxos) first
+    pub new_utxos_needed: bool,
+}
+
 /// The status of a [`Transfer`]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "u16", db_type = "Integer")]
@@ -565,6 +1163,8 @@ pub struct Transfer {
     pub blinding_secret: Option<u64>,
     /// Expiration of the transfer
     pub expiration: Option<i64>,
+    /// URL of the proxy server the transfer data has been exchanged through
+    pub proxy_url: Option<String>,
 }
 
 impl Transfer {
@@ -589,10 +1189,65 @@ impl Transfer {
             change_utxo: td.change_utxo,
             blinding_secret,
             expiration: td.expiration,
+            proxy_url: x.proxy_url,
         }
     }
 }
 
+/// The outcome of refreshing a pending batch transfer, as reported by [`Wallet::refresh`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshedTransfer {
+    /// TXID of the transfer, if known
+    pub txid: Option<String>,
+    /// Blinded UTXO of the transfer, if incoming
+    pub blinded_utxo: Option<String>,
+    /// Status of the transfer before the refresh
+    pub old_status: TransferStatus,
+    /// Status of the transfer after the refresh
+    pub new_status: TransferStatus,
+    /// Description of the error that prevented refreshing the transfer, if any
+    pub failure: Option<String>,
+    /// The transfer prepared again without the recipients that NACKed this one, if any
+    pub retried: Option<RetriedTransfer>,
+}
+
+/// An outgoing batch transfer prepared again by [`Wallet::refresh`] without the recipients that
+/// NACKed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetriedTransfer {
+    /// TXID of the new transfer
+    pub txid: String,
+    /// Blinded UTXOs of the recipients that NACKed the transfer and have been dropped
+    pub dropped_blinded_utxos: Vec<String>,
+    /// PSBT of the new transfer, for watch-only wallets, which need to sign it and pass it to
+    /// [`send_end`](Wallet::send_end)
+    pub unsigned_psbt: Option<String>,
+}
+
+/// A change in the status of a [`Transfer`]
+#[derive(Clone, Debug)]
+pub struct TransferStatusChange {
+    /// ID of the asset the transfer is for, if known
+    pub asset_id: Option<String>,
+    /// The transfer, reporting its new status
+    pub transfer: Transfer,
+    /// Status of the transfer before the change, `None` if it has just been created
+    pub old_status: Option<TransferStatus>,
+}
+
+/// A listener for [`Transfer`] status changes
+///
+/// Once registered with [`Wallet::set_transfer_listener`], it gets called each time a transfer
+/// is created by [`Wallet::send_end`] or its status is changed by the wallet, e.g. during a
+/// [`Wallet::refresh`] or by [`Wallet::fail_transfers`].
+///
+/// Calls are made synchronously from the thread operating the wallet, so implementations should
+/// return quickly and must not call the wallet themselves.
+pub trait TransferListener: Send + Sync {
+    /// Called when the status of a transfer changes
+    fn on_transfer_status_change(&self, change: TransferStatusChange);
+}
+
 /// A wallet unspent
 #[derive(Clone, Debug)]
 pub struct Unspent {
@@ -648,6 +1303,111 @@ pub struct WalletData {
     pub pubkey: String,
     /// Wallet mnemonic phrase
     pub mnemonic: Option<String>,
+    /// Number of confirmations the transaction of a transfer needs before the transfer is
+    /// settled, unless a different one is requested when creating the transfer
+    ///
+    /// Incoming transfers anchored to a transaction signalling RBF wait for a confirmation even
+    /// if 0 is requested.
+    pub min_confirmations: u8,
+    /// Time, in seconds, after which a send prepared with [`send_begin`](Wallet::send_begin) or
+    /// [`bump_fee_begin`](Wallet::bump_fee_begin) and never signed is deleted, releasing the
+    /// change address it reserved. If `None`, a default of 1 day is used. Set it to 0 to keep
+    /// prepared sends until they're aborted with [`abort_send`](Wallet::abort_send).
+    ///
+    /// Set it longer than external signing of the PSBTs can take, as a deleted send cannot be
+    /// completed anymore.
+    pub begun_send_duration: Option<u32>,
+}
+
+/// Check the provided fee rate, in sat/vB, is within the allowed range
+fn check_fee_rate(fee_rate: f32) -> Result<FeeRate, Error> {
+    if !(MIN_FEE_RATE..=MAX_FEE_RATE).contains(&fee_rate) {
+        return Err(Error::InvalidFeeRate(format!(
+            "{} is not between {} and {} sat/vB",
+            fee_rate, MIN_FEE_RATE, MAX_FEE_RATE
+        )));
+    }
+    Ok(FeeRate::from_sat_per_vb(fee_rate))
+}
+
+/// Return the expiration timestamp of a transfer lasting `duration_seconds` from now, or
+/// `default_duration` if no duration is provided. A duration of 0 means no expiration.
+fn get_expiration(duration_seconds: Option<u32>, default_duration: u32) -> Option<i64> {
+    match duration_seconds.unwrap_or(default_duration) {
+        0 => None,
+        duration => Some(now().unix_timestamp() + duration as i64),
+    }
+}
+
+/// Return the ID the `replacement`-th consignment for the provided blinded UTXO is posted under
+///
+/// The proxy server refuses to replace a stored consignment, so the ones replacing it after a
+/// fee bump or a retry without the NACKing recipients are posted under new IDs. The original
+/// consignment (`replacement` 0) is posted under the blinded UTXO itself, replacements under
+/// `<blinded_utxo>_<replacement>`, up to [`MAX_CONSIGNMENT_REPLACEMENTS`]. This scheme is specific
+/// to this library, other RGB wallets only look for the consignment under the blinded UTXO.
+fn consignment_recipient_id(blinded_utxo: &str, replacement: u32) -> String {
+    if replacement == 0 {
+        blinded_utxo.to_string()
+    } else {
+        format!("{}_{}", blinded_utxo, replacement)
+    }
+}
+
+/// Point the provided recipient to the proxy server its previous consignment has been posted to,
+/// among the provided transfers, where it looks for the replacement
+fn use_posted_proxy_url(mut recipient: Recipient, transfers: &[DbTransfer]) -> Recipient {
+    if let Some(proxy_url) = transfers
+        .iter()
+        .find(|t| t.blinded_utxo.as_ref() == Some(&recipient.blinded_utxo))
+        .and_then(|t| t.proxy_url.clone())
+    {
+        recipient.transport_endpoints = vec![proxy_url];
+    }
+    recipient
+}
+
+/// Return the ID the provided attachment is posted under, encrypted, for the recipient of the
+/// provided blinded UTXO
+///
+/// Media is shared by all the recipients posting to a proxy server, so each recipient expecting
+/// encrypted data gets its own copy, posted under an ID only it and the sender know.
+fn encrypted_media_id(attachment_id: &str, blinded_utxo: &str) -> String {
+    format!("{}_{}", attachment_id, blinded_utxo)
+}
+
+/// Convert an error from the electrum client used by BDK, which is a different version of the
+/// one used by this library, keeping the details needed to tell if the call can be retried
+fn _bdk_electrum_error(error: BdkElectrumError) -> Error {
+    Error::Electrum(match error {
+        BdkElectrumError::IOError(e) => electrum_client::Error::IOError(e),
+        BdkElectrumError::SharedIOError(e) => electrum_client::Error::SharedIOError(e),
+        BdkElectrumError::AllAttemptsErrored(errors) if errors.is_empty() => {
+            electrum_client::Error::AllAttemptsErrored(vec![])
+        }
+        e => electrum_client::Error::Message(e.to_string()),
+    })
+}
+
+/// Whether the provided error, returned by a transaction lookup, means the electrum server doesn't
+/// know the transaction
+///
+/// The server answers lookups of unknown transactions with a protocol error, while failures to
+/// reach it are reported as other electrum errors.
+fn is_unknown_tx_error(error: &Error) -> bool {
+    matches!(error, Error::Electrum(electrum_client::Error::Protocol(_)))
+}
+
+/// Whether the transaction in the provided electrum verbose details signals RBF (BIP 125)
+fn signals_rbf(tx_details: &serde_json::Value) -> Result<bool, Error> {
+    let tx_hex = tx_details
+        .get("hex")
+        .and_then(|h| h.as_str())
+        .ok_or(InternalError::Unexpected)?;
+    let tx: Transaction =
+        deserialize(&Vec::<u8>::from_hex(tx_hex).map_err(|_| InternalError::Unexpected)?)
+            .map_err(InternalError::from)?;
+    Ok(tx.input.iter().any(|i| i.sequence < 0xfffffffe))
 }
 
 /// An RGB wallet
@@ -662,10 +1422,14 @@ pub struct Wallet {
     wallet_dir: PathBuf,
     bdk_wallet: BdkWallet<BdkSqliteDatabase>,
     rest_client: RestClient,
+    public_rest_client: RestClient,
+    network_policy: NetworkPolicy,
     online: Option<Online>,
-    bdk_blockchain: Option<ElectrumBlockchain>,
-    electrum_client: Option<ElectrumClient>,
+    proxy_versions: RefCell<HashMap<String, ProxyVersion>>,
+    bdk_blockchain: RefCell<Option<ElectrumBlockchain>>,
+    electrum_client: RefCell<Option<ElectrumClient>>,
     rgb_client: Option<Client>,
+    transfer_listener: Option<Box<dyn TransferListener>>,
 }
 
 impl Wallet {
@@ -755,9 +1519,9 @@ impl Wallet {
         let connection = db_cnn.map_err(InternalError::from)?;
         block_on(Migrator::up(&connection, None)).map_err(InternalError::from)?;
         let database = RgbLibDatabase::new(connection);
-        let rest_client = RestClient::builder()
-            .timeout(Duration::from_secs(PROXY_TIMEOUT as u64))
-            .build()?;
+        let network_policy = NetworkPolicy::default();
+        let rest_client = build_proxy_client(network_policy.proxy_timeout(), None)?;
+        let public_rest_client = rest_client.clone();
 
         Ok(Wallet {
             wallet_data,
@@ -768,83 +1532,312 @@ impl Wallet {
             wallet_dir,
             bdk_wallet,
             rest_client,
+            public_rest_client,
+            network_policy,
             online: None,
-            bdk_blockchain: None,
-            electrum_client: None,
+            proxy_versions: RefCell::new(HashMap::new()),
+            bdk_blockchain: RefCell::new(None),
+            electrum_client: RefCell::new(None),
             rgb_client: None,
+            transfer_listener: None,
         })
     }
 
-    fn _bdk_blockchain(&self) -> Result<&ElectrumBlockchain, InternalError> {
-        match self.bdk_blockchain {
-            Some(ref x) => Ok(x),
-            None => Err(InternalError::Unexpected),
+    fn _bdk_blockchain(&self) -> Result<Ref<ElectrumBlockchain>, InternalError> {
+        let bdk_blockchain = self.bdk_blockchain.borrow();
+        if bdk_blockchain.is_none() {
+            return Err(InternalError::Unexpected);
         }
+        Ok(Ref::map(bdk_blockchain, |b| {
+            b.as_ref().expect("checked to be available")
+        }))
     }
 
-    fn _electrum_client(&self) -> Result<&ElectrumClient, InternalError> {
-        match self.electrum_client {
-            Some(ref x) => Ok(x),
-            None => Err(InternalError::Unexpected),
+    fn _electrum_client(&self) -> Result<Ref<ElectrumClient>, InternalError> {
+        let electrum_client = self.electrum_client.borrow();
+        if electrum_client.is_none() {
+            return Err(InternalError::Unexpected);
         }
+        Ok(Ref::map(electrum_client, |c| {
+            c.as_ref().expect("checked to be available")
+        }))
     }
 
-    fn _rgb_client(&mut self) -> Result<&mut Client, Error> {
-        match self.rgb_client {
-            Some(ref mut x) => Ok(x),
-            None => Err(InternalError::Unexpected)?,
-        }
+    /// Connect the electrum and BDK clients to the electrum server of the [`Online`] object
+    ///
+    /// The clients don't retry failed calls themselves, retries follow the [`NetworkPolicy`]
+    /// and are run by [`_electrum_call`](Wallet::_electrum_call).
+    fn _connect_electrum(&self) -> Result<(), Error> {
+        let electrum_url = match self.online {
+            Some(ref online) => online.electrum_url.clone(),
+            None => return Err(InternalError::Unexpected)?,
+        };
+        let timeout = Some(self.network_policy.electrum_timeout);
+        let electrum_config = ConfigBuilder::new().timeout(timeout)?.retry(0).build();
+        let electrum_client = ElectrumClient::from_config(&electrum_url, electrum_config)?;
+        let bdk_config = BdkElectrumConfigBuilder::new()
+            .timeout(timeout)
+            .map_err(_bdk_electrum_error)?
+            .retry(0)
+            .build();
+        let bdk_client = BdkElectrumClient::from_config(&electrum_url, bdk_config)
+            .map_err(_bdk_electrum_error)?;
+        *self.electrum_client.borrow_mut() = Some(electrum_client);
+        *self.bdk_blockchain.borrow_mut() = Some(ElectrumBlockchain::from(bdk_client));
+        Ok(())
     }
 
-    fn _get_tx_details(&self, txid: String) -> Result<serde_json::Value, Error> {
-        let call = (
-            s!("blockchain.transaction.get"),
-            vec![Param::String(txid), Param::Bool(true)],
-        );
-        Ok(self._electrum_client()?.raw_call(&call)?)
+    /// Run `call` with the retry policy, reconnecting to the electrum server before each retry
+    ///
+    /// Calls that aren't `idempotent`, like broadcasts, are not retried on timeout.
+    fn _electrum_call<T>(
+        &self,
+        idempotent: bool,
+        mut call: impl FnMut() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut retrying = false;
+        let attempt = || {
+            if retrying {
+                self._connect_electrum()?;
+            }
+            retrying = true;
+            call()
+        };
+        if idempotent {
+            self.network_policy.retry(&self.logger, attempt)
+        } else {
+            self.network_policy.retry_post(&self.logger, attempt)
+        }
     }
 
-    fn _sync_db_txos(&self) -> Result<(), Error> {
-        debug!(self.logger, "Syncing TXOs...");
-        self.bdk_wallet
-            .sync(self._bdk_blockchain()?, SyncOptions { progress: None })
-            .map_err(|e| Error::FailedBdkSync(e.to_string()))?;
+    /// Run `call`, which uses the BDK electrum blockchain, like
+    /// [`_electrum_call`](Wallet::_electrum_call)
+    ///
+    /// Non-network errors, and network ones once retries are exhausted, are reported with the
+    /// `wrap` error variant.
+    fn _bdk_electrum_call<T>(
+        &self,
+        idempotent: bool,
+        call: impl Fn(&ElectrumBlockchain) -> Result<T, BdkError>,
+        wrap: fn(String) -> Error,
+    ) -> Result<T, Error> {
+        self._electrum_call(idempotent, || {
+            call(&*self._bdk_blockchain()?).map_err(|e| match e {
+                BdkError::Electrum(e) => _bdk_electrum_error(e),
+                e => wrap(e.to_string()),
+            })
+        })
+        .map_err(|e| match e {
+            Error::Electrum(e) => wrap(e.to_string()),
+            e => e,
+        })
+    }
 
-        let db_outpoints: Vec<String> = self
-            .database
-            .iter_txos()?
-            .into_iter()
-            .filter(|t| !t.spent)
-            .map(|u| u.outpoint().to_string())
-            .collect();
-        let bdk_utxos: Vec<LocalUtxo> = self
-            .bdk_wallet
-            .list_unspent()
-            .map_err(InternalError::from)?;
-        let new_utxos: Vec<DbTxoActMod> = bdk_utxos
-            .into_iter()
-            .filter(|u| !db_outpoints.contains(&u.outpoint.to_string()))
-            .map(DbTxoActMod::from)
-            .collect();
-        for new_utxo in new_utxos.iter().cloned() {
-            self.database.set_txo(new_utxo)?;
+    fn _proxy_urls(&self) -> Result<Vec<String>, InternalError> {
+        match self.online {
+            Some(ref x) => Ok(x.proxy_urls.clone()),
+            None => Err(InternalError::Unexpected),
         }
+    }
 
+    /// Check the provided proxy server supports one of this library's protocol versions
+    ///
+    /// The negotiated version is cached, so each proxy server is only asked for it once.
+    fn _check_proxy_version(&self, proxy_url: &str) -> Result<(), Error> {
+        if !self.proxy_versions.borrow().contains_key(proxy_url) {
+            let proxy_version = self
+                ._proxy_call(proxy_url, |c| c.get_info(proxy_url))?
+                .supported_version()?;
+            self.proxy_versions
+                .borrow_mut()
+                .insert(proxy_url.to_string(), proxy_version);
+        }
         Ok(())
     }
 
-    fn _broadcast_psbt(
+    /// Run `call` with the retry policy, passing it the client for the provided proxy server
+    ///
+    /// The configured [`ProxyOptions`] are only used for the wallet's own proxy servers, not for
+    /// the ones from invoices of other wallets.
+    fn _proxy_call<T>(
         &self,
-        signed_psbt: PartiallySignedTransaction,
-    ) -> Result<Transaction, Error> {
-        let tx = signed_psbt.extract_tx();
-        self._bdk_blockchain()?
-            .broadcast(&tx)
-            .map_err(|e| Error::FailedBroadcast(e.to_string()))?;
-        debug!(self.logger, "Broadcasted TX with ID '{}'", tx.txid());
+        proxy_url: &str,
+        call: impl Fn(RestClient) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self._proxy_request(proxy_url, true, call)
+    }
 
-        for input in tx.clone().input {
-            let mut db_txo: DbTxoActMod = self
+    /// Run `call` like [`_proxy_call`](Wallet::_proxy_call), for POST requests
+    ///
+    /// POST requests are not idempotent, so they're not retried if they time out.
+    fn _proxy_post<T>(
+        &self,
+        proxy_url: &str,
+        call: impl Fn(RestClient) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self._proxy_request(proxy_url, false, call)
+    }
+
+    fn _proxy_request<T>(
+        &self,
+        proxy_url: &str,
+        idempotent: bool,
+        call: impl Fn(RestClient) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let own_proxy = match self.online {
+            Some(ref online) => online.proxy_urls.iter().any(|u| u == proxy_url),
+            None => false,
+        };
+        let rest_client = if own_proxy {
+            &self.rest_client
+        } else {
+            &self.public_rest_client
+        };
+        if idempotent {
+            self.network_policy
+                .retry(&self.logger, || call(rest_client.clone()))
+        } else {
+            self.network_policy
+                .retry_post(&self.logger, || call(rest_client.clone()))
+        }
+    }
+
+    /// Run `call` on the provided proxy servers in order, until one returns a result
+    ///
+    /// Returns the first result along with the URL of the proxy server that returned it, or
+    /// `None` if no proxy server had a result. Proxy servers failing the call are skipped,
+    /// an error is returned only if all of them fail. Calls that aren't `idempotent` are not
+    /// retried on timeout.
+    fn _proxy_failover<T>(
+        &self,
+        proxy_urls: &[String],
+        idempotent: bool,
+        call: impl Fn(RestClient, &str) -> Result<Option<T>, Error>,
+    ) -> Result<Option<(String, T)>, Error> {
+        let mut last_err = None;
+        let mut responded = false;
+        for proxy_url in proxy_urls {
+            match self._proxy_request(proxy_url, idempotent, |c| call(c, proxy_url)) {
+                Ok(Some(res)) => return Ok(Some((proxy_url.clone(), res))),
+                Ok(None) => responded = true,
+                Err(e) => {
+                    warn!(self.logger, "Proxy server '{}' failed: {}", proxy_url, e);
+                    last_err = Some(e);
+                }
+            }
+        }
+        match last_err {
+            Some(e) if !responded => Err(e),
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the fee paid by the transaction of the provided PSBT, in sats
+    ///
+    /// The value of inputs missing both `witness_utxo` and `non_witness_utxo`, which signers may
+    /// strip, is taken from the wallet's transactions.
+    fn _psbt_fee(&self, psbt: &PartiallySignedTransaction) -> Result<u64, Error> {
+        let mut input_sum = 0;
+        for (input, txin) in psbt.inputs.iter().zip(&psbt.unsigned_tx.input) {
+            let outpoint = txin.previous_output;
+            let value = if let Some(witness_utxo) = &input.witness_utxo {
+                witness_utxo.value
+            } else {
+                let prev_tx = match &input.non_witness_utxo {
+                    Some(tx) => Some(tx.clone()),
+                    None => self
+                        .bdk_wallet
+                        .get_tx(&outpoint.txid, true)
+                        .map_err(InternalError::from)?
+                        .and_then(|d| d.transaction),
+                };
+                prev_tx
+                    .and_then(|tx| tx.output.get(outpoint.vout as usize).map(|o| o.value))
+                    .ok_or_else(|| Error::UnknownPsbtInput(outpoint.to_string()))?
+            };
+            input_sum += value;
+        }
+        let output_sum: u64 = psbt.unsigned_tx.output.iter().map(|o| o.value).sum();
+        Ok(input_sum
+            .checked_sub(output_sum)
+            .ok_or(InternalError::Unexpected)?)
+    }
+
+    fn _rgb_client(&mut self) -> Result<&mut Client, Error> {
+        match self.rgb_client {
+            Some(ref mut x) => Ok(x),
+            None => Err(InternalError::Unexpected)?,
+        }
+    }
+
+    /// Run `call` on the RGB node client with the retry policy
+    fn _rgb_call<T>(
+        &mut self,
+        mut call: impl FnMut(&mut Client) -> Result<T, rgb_rpc::Error>,
+    ) -> Result<T, Error> {
+        let network_policy = self.network_policy.clone();
+        let logger = self.logger.clone();
+        network_policy.retry(&logger, || {
+            Ok(call(self._rgb_client()?).map_err(InternalError::from)?)
+        })
+    }
+
+    fn _get_tx_details(&self, txid: String) -> Result<serde_json::Value, Error> {
+        let call = (
+            s!("blockchain.transaction.get"),
+            vec![Param::String(txid), Param::Bool(true)],
+        );
+        self._electrum_call(true, || Ok(self._electrum_client()?.raw_call(&call)?))
+    }
+
+    fn _sync_db_txos(&self) -> Result<(), Error> {
+        debug!(self.logger, "Syncing TXOs...");
+        self._bdk_electrum_call(
+            true,
+            |b| self.bdk_wallet.sync(b, SyncOptions { progress: None }),
+            Error::FailedBdkSync,
+        )?;
+
+        let db_outpoints: Vec<String> = self
+            .database
+            .iter_txos()?
+            .into_iter()
+            .filter(|t| !t.spent)
+            .map(|u| u.outpoint().to_string())
+            .collect();
+        let bdk_utxos: Vec<LocalUtxo> = self
+            .bdk_wallet
+            .list_unspent()
+            .map_err(InternalError::from)?;
+        let new_utxos: Vec<DbTxoActMod> = bdk_utxos
+            .into_iter()
+            .filter(|u| !db_outpoints.contains(&u.outpoint.to_string()))
+            .map(DbTxoActMod::from)
+            .collect();
+        for new_utxo in new_utxos.iter().cloned() {
+            self.database.set_txo(new_utxo)?;
+        }
+
+        Ok(())
+    }
+
+    fn _broadcast_psbt(
+        &self,
+        signed_psbt: PartiallySignedTransaction,
+    ) -> Result<Transaction, Error> {
+        let tx = signed_psbt.extract_tx();
+        self._bdk_electrum_call(false, |b| b.broadcast(&tx), Error::FailedBroadcast)?;
+        debug!(self.logger, "Broadcasted TX with ID '{}'", tx.txid());
+
+        self._set_inputs_spent(&tx)?;
+        self._sync_db_txos()?;
+
+        Ok(tx)
+    }
+
+    fn _set_inputs_spent(&self, tx: &Transaction) -> Result<(), Error> {
+        for input in &tx.input {
+            let mut db_txo: DbTxoActMod = self
                 .database
                 .get_txo(Outpoint {
                     txid: input.previous_output.txid.to_string(),
@@ -855,10 +1848,7 @@ impl Wallet {
             db_txo.spent = ActiveValue::Set(true);
             self.database.update_txo(db_txo)?;
         }
-
-        self._sync_db_txos()?;
-
-        Ok(tx)
+        Ok(())
     }
 
     fn _check_online(&self, online: Online) -> Result<(), Error> {
@@ -878,6 +1868,24 @@ impl Wallet {
         Ok(())
     }
 
+    /// Return the outpoints of the TXOs that can hold RGB allocations or have been used for
+    /// them, which plain bitcoin transactions cannot spend
+    fn _get_rgb_outpoints(&self) -> Result<Vec<OutPoint>, Error> {
+        let colored_txos: Vec<i64> = self
+            .database
+            .iter_colorings()?
+            .into_iter()
+            .map(|c| c.txo_idx)
+            .collect();
+        Ok(self
+            .database
+            .iter_txos()?
+            .into_iter()
+            .filter(|t| t.colorable || colored_txos.contains(&t.idx))
+            .map(OutPoint::from)
+            .collect())
+    }
+
     fn _get_uncolorable_btc_sum(&self, unspents: Vec<LocalUnspent>) -> u64 {
         unspents
             .iter()
@@ -893,6 +1901,7 @@ impl Wallet {
 
     fn _handle_expired_transfers(&mut self) -> Result<(), Error> {
         self._sync_db_txos()?;
+        self._delete_stale_begun_sends()?;
         let now = now().unix_timestamp();
         let expired_transfers: Vec<DbBatchTransfer> = self
             .database
@@ -905,7 +1914,65 @@ impl Wallet {
             if updated_transfer.is_none() {
                 let mut updated_transfer: DbBatchTransferActMod = transfer.clone().into();
                 updated_transfer.status = ActiveValue::Set(TransferStatus::Failed);
-                self.database.update_batch_transfer(&mut updated_transfer)?;
+                let updated = self.database.update_batch_transfer(&mut updated_transfer)?;
+                self._notify_status_change(&updated, Some(transfer.status));
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the data of sends prepared by `send_begin` or `bump_fee_begin` and never signed,
+    /// once they're older than the wallet's
+    /// [`begun_send_duration`](WalletData::begun_send_duration), unless it's 0
+    fn _delete_stale_begun_sends(&self) -> Result<(), Error> {
+        let duration = match self
+            .wallet_data
+            .begun_send_duration
+            .unwrap_or(DURATION_BEGUN_SEND)
+        {
+            0 => return Ok(()),
+            duration => duration as i64,
+        };
+        let transfers_dir = self.wallet_dir.join(TRANSFER_DIR);
+        if !transfers_dir.is_dir() {
+            return Ok(());
+        }
+        let known_txids: HashSet<String> = self
+            .database
+            .iter_batch_transfers()?
+            .into_iter()
+            .filter_map(|t| t.txid)
+            .collect();
+        let now = now().unix_timestamp();
+        for entry in fs::read_dir(transfers_dir)? {
+            let transfer_dir = entry?.path();
+            let name = transfer_dir
+                .file_name()
+                .expect("valid directory name")
+                .to_string_lossy()
+                .to_string();
+            let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+            if known_txids.contains(&name)
+                || !info_file.exists()
+                || transfer_dir.join(SIGNED_PSBT_FILE).exists()
+            {
+                continue;
+            }
+            let serialized_info = fs::read_to_string(info_file)?;
+            let info_contents: InfoBatchTransfer =
+                serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+            // sends prepared before the time was recorded fall back to the directory's one
+            let begun_at = match info_contents.begun_at {
+                Some(begun_at) => begun_at,
+                None => fs::metadata(&transfer_dir)?
+                    .modified()?
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_err(InternalError::from)?
+                    .as_secs() as i64,
+            };
+            if now - begun_at >= duration {
+                debug!(self.logger, "Deleting stale send '{}'", name);
+                fs::remove_dir_all(transfer_dir)?;
             }
         }
         Ok(())
@@ -957,19 +2024,67 @@ impl Wallet {
         }
     }
 
+    /// Abort the send with the provided `txid`, prepared by [`send_begin`](Wallet::send_begin)
+    /// or [`bump_fee_begin`](Wallet::bump_fee_begin) but not signed, deleting its data.
+    ///
+    /// Sends that have been passed to [`send_end`](Wallet::send_end) cannot be aborted. Prepared
+    /// sends that are never completed are also deleted automatically after the wallet's
+    /// [`begun_send_duration`](WalletData::begun_send_duration).
+    pub fn abort_send(&self, txid: String) -> Result<(), Error> {
+        info!(self.logger, "Aborting send '{}'...", txid);
+        if Txid::from_str(&txid).is_err() {
+            return Err(Error::BatchTransferNotFound(txid));
+        }
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(&txid);
+        if !transfer_dir.join(TRANSFER_DATA_FILE).exists() {
+            return Err(Error::BatchTransferNotFound(txid));
+        }
+        if transfer_dir.join(SIGNED_PSBT_FILE).exists()
+            || self
+                .database
+                .iter_batch_transfers()?
+                .iter()
+                .any(|t| t.txid.as_ref() == Some(&txid))
+        {
+            return Err(Error::CannotAbortSend);
+        }
+        fs::remove_dir_all(transfer_dir)?;
+        Ok(())
+    }
+
     /// Blind an UTXO and return the resulting [`BlindData`]
     ///
     /// Optional [`Asset`] ID and duration (in secods) can be specified
+    ///
+    /// A new consignment key is generated and included in the invoice, so the sender can post the
+    /// consignment encrypted to the proxy
+    ///
+    /// The `transport_endpoints` are included in the invoice as well, to let the sender know which
+    /// proxy servers to post the consignment to. If none are provided, the proxy URLs of the
+    /// wallet are used when it's online.
+    ///
+    /// The transfer is settled once its transaction has `min_confirmations` confirmations, or the
+    /// wallet's [`min_confirmations`](WalletData::min_confirmations) if none is provided. With 0
+    /// confirmations, the transfer is settled as soon as its transaction is in the mempool only
+    /// if it doesn't signal RBF: the sender could otherwise replace it, so a confirmation is
+    /// still awaited.
     pub fn blind(
         &mut self,
         asset_id: Option<String>,
         amount: Option<u64>,
         duration_seconds: Option<u32>,
+        transport_endpoints: Vec<String>,
+        min_confirmations: Option<u8>,
     ) -> Result<BlindData, Error> {
         info!(
             self.logger,
             "Blinding for asset '{:?}' with duration '{:?}'...", asset_id, duration_seconds
         );
+        check_transport_endpoints(&transport_endpoints)?;
+        let transport_endpoints = match (&self.online, transport_endpoints.is_empty()) {
+            (Some(online), true) => online.proxy_urls.clone(),
+            _ => transport_endpoints,
+        };
         let (asset_type, rgb_asset_id) = if let Some(cid) = asset_id.clone() {
             let asset_type = self.database.get_asset_or_fail(cid.clone())?;
             let contract_id = ContractId::from_str(&cid).map_err(InternalError::from)?;
@@ -989,19 +2104,15 @@ impl Wallet {
         let seal = seal::Revealed::new(CloseMethod::OpretFirst, OutPoint::from(utxo.clone()));
         let concealed_seal = seal.to_concealed_seal();
         let blinded_utxo = concealed_seal.to_string();
+        let consignment_key = encryption::generate_key();
 
-        let created_at = now().unix_timestamp();
-        let (expiration, expiry) = if duration_seconds == Some(0) {
-            (None, None)
-        } else {
-            let duration_seconds = duration_seconds.unwrap_or(DURATION_RCV_TRANSFER) as i64;
-            let expiration = created_at + duration_seconds;
-            let expiry = NaiveDateTime::from_timestamp_opt(expiration, 0);
-            (Some(expiration), expiry)
-        };
+        let expiration = get_expiration(duration_seconds, DURATION_RCV_TRANSFER);
         let batch_transfer = DbBatchTransferActMod {
             status: ActiveValue::Set(TransferStatus::WaitingCounterparty),
             expiration: ActiveValue::Set(expiration),
+            min_confirmations: ActiveValue::Set(
+                min_confirmations.unwrap_or(self.wallet_data.min_confirmations),
+            ),
             ..Default::default()
         };
         let batch_transfer_idx = self.database.set_batch_transfer(batch_transfer)?;
@@ -1023,6 +2134,12 @@ impl Wallet {
             amount: ActiveValue::Set(s!("0")),
             blinded_utxo: ActiveValue::Set(Some(blinded_utxo.clone())),
             blinding_secret: ActiveValue::Set(Some(seal.blinding.to_string())),
+            consignment_key: ActiveValue::Set(Some(consignment_key.clone())),
+            transport_endpoints: ActiveValue::Set(if transport_endpoints.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&transport_endpoints).map_err(InternalError::from)?)
+            }),
             ..Default::default()
         };
         self.database.set_transfer(transfer)?;
@@ -1037,48 +2154,38 @@ impl Wallet {
 
         let beneficiary = Beneficiary::BlindUtxo(concealed_seal);
         let mut invoice = UniversalInvoice::new(beneficiary, amount, rgb_asset_id);
-        if let Some(exp) = expiry {
-            invoice.set_expiry(exp);
+        if let Some(expiry) = expiration.and_then(|e| NaiveDateTime::from_timestamp_opt(e, 0)) {
+            invoice.set_expiry(expiry);
         }
+        InvoiceExtensions {
+            consignment_key: Some(consignment_key.clone()),
+            transport_endpoints,
+        }
+        .add_to_invoice(&mut invoice)?;
 
         Ok(BlindData {
             invoice: invoice.to_string(),
             blinded_utxo,
             blinding_secret: seal.blinding,
             expiration_timestamp: expiration,
+            consignment_key,
         })
     }
 
-    fn _create_split_tx(
-        &self,
-        inputs: &[OutPoint],
-        num_utxos_to_create: u8,
-        size: u32,
-    ) -> Result<PartiallySignedTransaction, bdk::Error> {
-        let mut tx_builder = self.bdk_wallet.build_tx();
-        tx_builder.add_utxos(inputs)?;
-        tx_builder.manually_selected_only();
-        for _i in 0..num_utxos_to_create {
-            tx_builder.add_recipient(self._get_new_address().script_pubkey(), size as u64);
-        }
-        Ok(tx_builder.finish()?.0)
-    }
-
-    /// Create new UTXOs. See the [`create_utxos_begin`](Wallet::create_utxos_begin) function for
-    /// details.
+    /// Bump the fee of a pending outgoing transfer. See the
+    /// [`bump_fee_begin`](Wallet::bump_fee_begin) function for details.
     ///
-    /// This is the full version, requiring a wallet with private keys and [`Online`] data
-    pub fn create_utxos(
+    /// This is the full version, requiring a wallet with private keys
+    pub fn bump_fee(
         &mut self,
         online: Online,
-        up_to: bool,
-        num: Option<u8>,
-        size: Option<u32>,
-    ) -> Result<u8, Error> {
-        info!(self.logger, "Creating UTXOs...");
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<SendResult, Error> {
+        info!(self.logger, "Bumping fee of transfer '{}'...", txid);
         self._check_xprv()?;
 
-        let unsigned_psbt = self.create_utxos_begin(online.clone(), up_to, num, size)?;
+        let unsigned_psbt = self.bump_fee_begin(online.clone(), txid, fee_rate)?;
 
         let mut psbt =
             PartiallySignedTransaction::from_str(&unsigned_psbt).map_err(InternalError::from)?;
@@ -1086,97 +2193,571 @@ impl Wallet {
             .sign(&mut psbt, SignOptions::default())
             .map_err(InternalError::from)?;
 
-        self.create_utxos_end(online, psbt.to_string())
+        self.bump_fee_end(online, psbt.to_string())
     }
 
-    /// Prepare the PSBT to create new UTXOs to hold RGB allocations.
+    /// Prepare the PSBT to replace the transaction of the outgoing batch transfer with the provided
+    /// `txid` with one paying a higher fee.
     ///
-    /// If `up_to` is false, just create the required UTXOs.
-    /// If `up_to` is true, create as many UTXOs as needed to reach the requested number or return
-    /// an error if none need to be created.
-    ///
-    /// Providing the optional `num` parameter requests that many UTXOs, if it's not specified the
-    /// default number is used.
+    /// Only transfers in status [`TransferStatus::WaitingConfirmations`] whose transaction is
+    /// still unconfirmed can be bumped. The replacement spends the same inputs, so the RGB
+    /// transitions move the same allocations, and new consignments referencing it are prepared.
     ///
-    /// If not enough bitcoin funds are available to create the requested (or default) number of
-    /// UTXOs, the number is decremented by one until it is possible to complete the operation. If
-    /// the number reaches zero, an error is returned.
+    /// As required by BIP125, the `fee_rate`, in sat/vB, needs to be at least 1 sat/vB higher than
+    /// the one of the transaction being replaced and the total fee needs to cover the replaced one
+    /// plus the relay of the replacement at 1 sat/vB. UTXOs that cannot hold RGB allocations are
+    /// added as inputs if the ones of the replaced transaction don't cover the higher fee. If no
+    /// `fee_rate` is provided, the rate estimated by
+    /// [`estimate_fee`](Wallet::estimate_fee) for confirmation within 6 blocks is used instead.
     ///
-    /// This is the first half of the partial version, requiring no private keys nor [`Online`] data.
+    /// This is the first half of the partial version, requiring no private keys.
     /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
-    /// to be fed to the [`create_utxos_end`](Wallet::create_utxos_end) function.
+    /// to be fed to the [`bump_fee_end`](Wallet::bump_fee_end) function.
     ///
     /// Returns a PSBT ready to be signed
-    pub fn create_utxos_begin(
+    pub fn bump_fee_begin(
         &mut self,
         online: Online,
-        up_to: bool,
-        num: Option<u8>,
-        size: Option<u32>,
+        txid: String,
+        fee_rate: Option<f32>,
     ) -> Result<String, Error> {
-        info!(self.logger, "Creating UTXOs (begin)...");
+        info!(self.logger, "Bumping fee (begin) of transfer '{}'...", txid);
         self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(fee_rate)?;
 
-        self._sync_db_txos()?;
-
-        let unspent_txos = self.database.get_unspent_txos()?;
-        let unspents: Vec<LocalUnspent> =
-            self.database
-                .get_rgb_allocations(unspent_txos.clone(), false, None, None, None)?;
-        let allocatable = self
-            ._get_available_allocations(unspents.clone(), vec![], None)?
-            .len() as u8;
+        let batch_transfer = self.database.get_batch_transfer_or_fail(txid.clone())?;
+        if !batch_transfer.waiting_confirmations()
+            || batch_transfer.incoming(self.database.clone())?
+        {
+            return Err(Error::CannotBumpFee);
+        }
+        let tx_details = self._get_tx_details(txid.clone())?;
+        if tx_details.get("confirmations").is_some() {
+            return Err(Error::CannotBumpFee);
+        }
 
-        let mut utxos_to_create = num.unwrap_or(UTXO_NUM);
-        if up_to {
-            if allocatable >= utxos_to_create {
-                return Err(Error::AllocationsAlreadyAvailable);
-            } else {
-                utxos_to_create -= allocatable
-            }
+        // the replacement needs a higher fee rate than the replaced transaction
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid.clone());
+        let replaced_psbt = self._get_signed_psbt(transfer_dir.clone())?;
+        let replaced_fee = self._psbt_fee(&replaced_psbt)?;
+        let replaced_tx = replaced_psbt.extract_tx();
+        let replaced_fee_rate = replaced_fee as f32 / (replaced_tx.weight() as f32 / 4.0);
+        if fee_rate.as_sat_per_vb() < replaced_fee_rate + MIN_FEE_RATE {
+            return Err(Error::InvalidFeeRate(format!(
+                "{} is lower than the minimum of {} sat/vB required to replace the transaction",
+                fee_rate.as_sat_per_vb(),
+                replaced_fee_rate + MIN_FEE_RATE
+            )));
         }
-        debug!(self.logger, "Will try to create {} UTXOs", utxos_to_create);
 
-        let inputs: Vec<OutPoint> = unspent_txos
+        // restore transfer data
+        let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+        let serialized_info = fs::read_to_string(info_file)?;
+        let info_contents: InfoBatchTransfer =
+            serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+        let transfer_info_map = self._get_transfer_info_map(&transfer_dir)?;
+        let change_utxo = self
+            .database
+            .iter_txos()?
             .into_iter()
-            .filter(|u| !u.colorable)
+            .find(|t| t.idx == info_contents.change_utxo_idx)
+            .ok_or(InternalError::Unexpected)?;
+
+        // prepare BDK PSBT spending the same inputs, letting BDK add UTXOs that cannot hold RGB
+        // allocations if needed to cover the higher fee, except the ones created by the
+        // transaction being replaced
+        self._sync_db_txos()?;
+        let unspendable: Vec<OutPoint> = self
+            .database
+            .get_unspent_txos()?
+            .into_iter()
+            .filter(|t| t.colorable || t.txid == txid)
             .map(OutPoint::from)
             .collect();
-        let inputs: &[OutPoint] = &inputs;
-        let new_btc_amount = self._get_uncolorable_btc_sum(unspents);
-        let utxo_size = size.unwrap_or(UTXO_SIZE);
-        let max_possible_utxos = new_btc_amount / utxo_size as u64;
-        let mut num_try_creating = min(utxos_to_create, max_possible_utxos as u8);
-        while num_try_creating > 0 {
-            match self._create_split_tx(inputs, num_try_creating, utxo_size) {
-                Ok(_v) => break,
-                Err(_e) => num_try_creating -= 1,
-            };
+        let inputs: Vec<OutPoint> = replaced_tx
+            .input
+            .iter()
+            .map(|i| i.previous_output)
+            .collect();
+        let mut btc_outputs = vec![];
+        for output in &replaced_tx.output {
+            if output.script_pubkey.is_op_return()
+                || self
+                    .bdk_wallet
+                    .is_mine(&output.script_pubkey)
+                    .map_err(InternalError::from)?
+            {
+                continue;
+            }
+            btc_outputs.push((output.script_pubkey.clone(), output.value));
         }
+        let change_script = self._get_change_script()?;
+        let mut psbt = self._prepare_psbt(
+            inputs,
+            btc_outputs,
+            fee_rate,
+            change_script.clone(),
+            Some(unspendable),
+        )?;
 
-        if num_try_creating == 0 {
-            Err(Error::InsufficientBitcoins)
-        } else {
-            Ok(self
-                ._create_split_tx(inputs, num_try_creating, utxo_size)
-                .map_err(InternalError::from)?
-                .to_string())
+        // BIP125 also requires the replacement to pay for its own relay on top of the replaced fee
+        let fee = self._psbt_fee(&psbt)?;
+        let vsize = (fee as f32 / fee_rate.as_sat_per_vb()).ceil();
+        let min_fee = replaced_fee + (vsize * MIN_FEE_RATE).ceil() as u64;
+        if fee < min_fee {
+            return Err(Error::InvalidFeeRate(format!(
+                "the replacement would pay {} sat, lower than the minimum of {} sat required to \
+                 replace the transaction",
+                fee, min_fee
+            )));
         }
-    }
 
-    /// Broadcast the provided PSBT to create new UTXOs.
-    ///
+        // prepare RGB PSBT, allowing the blinded UTXOs of the replaced transfer to be used again
+        let asset_transfers = self.database.iter_asset_transfers()?;
+        let failed_batch_transfer_ids: Vec<i64> = self
+            .database
+            .iter_batch_transfers()?
+            .into_iter()
+            .filter(|t| t.failed() || t.idx == batch_transfer.idx)
+            .map(|t| t.idx)
+            .collect();
+        let ignored_asset_transfer_ids: Vec<i64> = asset_transfers
+            .into_iter()
+            .filter(|t| failed_batch_transfer_ids.contains(&t.batch_transfer_idx))
+            .map(|t| t.idx)
+            .collect();
+        let begin_transfer_dir =
+            self._get_transfer_begin_dir(transfer_info_map.values().map(|i| &i.recipients));
+        self._prepare_rgb_psbt(
+            &mut psbt,
+            change_utxo,
+            transfer_info_map,
+            begin_transfer_dir.clone(),
+            info_contents.donation,
+            info_contents.min_confirmations,
+            info_contents.duration_seconds,
+            info_contents.retry_without_nacks,
+            Some(txid),
+            None,
+            change_script,
+            ignored_asset_transfer_ids,
+        )?;
+
+        // rename transfer directory
+        let new_txid = psbt.clone().extract_tx().txid().to_string();
+        let new_transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(new_txid);
+        fs::rename(begin_transfer_dir, new_transfer_dir)?;
+
+        Ok(psbt.to_string())
+    }
+
+    /// Complete the fee bump by POSTing the new consignments to the proxy servers the recipients
+    /// received the replaced ones from, broadcasting the provided PSBT and updating the transfer.
+    ///
+    /// The proxy servers keep the replaced consignments, so the new ones are posted under new
+    /// recipient IDs, where the recipients look for them once the replaced transaction is gone.
+    ///
+    /// This is the second half of the partial version. The provided PSBT, prepared with the
+    /// [`bump_fee_begin`](Wallet::bump_fee_begin) function, needs to have already been signed.
+    ///
+    /// Returns the txid of the replacement transaction that's been broadcast and the fee it pays
+    pub fn bump_fee_end(
+        &mut self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<SendResult, Error> {
+        info!(self.logger, "Bumping fee (end)...");
+        self._check_online(online)?;
+
+        // save signed PSBT
+        let psbt =
+            PartiallySignedTransaction::from_str(&signed_psbt).map_err(Error::InvalidPsbt)?;
+        let fee = self._psbt_fee(&psbt)?;
+        let txid = psbt.clone().extract_tx().txid().to_string();
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid.clone());
+        let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+        let serialized_info = fs::read_to_string(info_file)?;
+        let info_contents: InfoBatchTransfer =
+            serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+        let replaced_txid = info_contents.replaced_txid.ok_or(Error::CannotBumpFee)?;
+        let batch_transfer = self
+            .database
+            .get_batch_transfer_or_fail(replaced_txid.clone())?;
+        if !batch_transfer.waiting_confirmations() {
+            return Err(Error::CannotBumpFee);
+        }
+        let psbt_out = transfer_dir.join(SIGNED_PSBT_FILE);
+        fs::write(psbt_out, psbt.to_string())?;
+
+        // post the new consignment(s) where the recipients got the replaced ones, under new IDs as
+        // the proxy server keeps the replaced ones
+        let asset_transfer_ids: Vec<i64> = self
+            .database
+            .iter_batch_asset_transfers(&batch_transfer)?
+            .into_iter()
+            .map(|t| t.idx)
+            .collect();
+        let transfers: Vec<DbTransfer> = self
+            .database
+            .iter_transfers()?
+            .into_iter()
+            .filter(|t| asset_transfer_ids.contains(&t.asset_transfer_idx))
+            .collect();
+        let mut transfer_info_map = self._get_transfer_info_map(&transfer_dir)?;
+        for info in transfer_info_map.values_mut() {
+            info.recipients = std::mem::take(&mut info.recipients)
+                .into_iter()
+                .map(|r| use_posted_proxy_url(r, &transfers))
+                .collect();
+        }
+        let posted_consignments = self._post_send_data(&transfer_dir, &transfer_info_map, true)?;
+
+        // broadcast the replacement and drop the outputs of the replaced transaction
+        let replaced_dir = self
+            .wallet_dir
+            .join(TRANSFER_DIR)
+            .join(replaced_txid.clone());
+        let replaced_tx = self._get_signed_psbt(replaced_dir.clone())?.extract_tx();
+        self._broadcast_psbt(psbt)?;
+        for vout in 0..replaced_tx.output.len() {
+            if let Some(db_txo) = self.database.get_txo(Outpoint {
+                txid: replaced_txid.clone(),
+                vout: vout as u32,
+            })? {
+                let mut db_txo: DbTxoActMod = db_txo.into();
+                db_txo.spent = ActiveValue::Set(true);
+                self.database.update_txo(db_txo)?;
+            }
+        }
+
+        for transfer in transfers {
+            let posted = transfer
+                .blinded_utxo
+                .as_ref()
+                .and_then(|b| posted_consignments.get(b));
+            if let Some(posted) = posted {
+                let mut updated_transfer: DbTransferActMod = transfer.into();
+                updated_transfer.proxy_url = ActiveValue::Set(Some(posted.proxy_url.clone()));
+                updated_transfer.recipient_id = ActiveValue::Set(Some(posted.recipient_id.clone()));
+                self.database.update_transfer(&mut updated_transfer)?;
+            }
+        }
+        let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.into();
+        updated_batch_transfer.txid = ActiveValue::Set(Some(txid.clone()));
+        self.database
+            .update_batch_transfer(&mut updated_batch_transfer)?;
+        fs::remove_dir_all(replaced_dir)?;
+
+        Ok(SendResult { txid, fee })
+    }
+
+    /// Accelerate the confirmation of a pending incoming transfer with a child transaction. See
+    /// the [`cpfp_begin`](Wallet::cpfp_begin) function for details.
+    ///
+    /// This is the full version, requiring a wallet with private keys
+    pub fn cpfp(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<CpfpResult, Error> {
+        info!(self.logger, "Accelerating transfer '{}'...", txid);
+        self._check_xprv()?;
+
+        let unsigned_psbt = self.cpfp_begin(online.clone(), txid, fee_rate)?;
+
+        let mut psbt =
+            PartiallySignedTransaction::from_str(&unsigned_psbt).map_err(InternalError::from)?;
+        self.bdk_wallet
+            .sign(&mut psbt, SignOptions::default())
+            .map_err(InternalError::from)?;
+
+        self.cpfp_end(online, psbt.to_string())
+    }
+
+    /// Prepare the PSBT of a child transaction accelerating the confirmation of the transaction
+    /// of the incoming batch transfer with the provided `txid` (CPFP).
+    ///
+    /// Only transfers in status [`TransferStatus::WaitingConfirmations`] whose transaction is
+    /// still unconfirmed can be accelerated, if the transaction has outputs owned by the wallet
+    /// that cannot hold RGB allocations, like the ones paid to BTC recipients of a send. The child
+    /// spends them, adding other UTXOs that cannot hold RGB allocations if needed. The received
+    /// allocations are assigned to the blinded UTXO, which is not spent, so they're not affected.
+    ///
+    /// The child pays enough for the two transactions together to pay the `fee_rate`, in sat/vB.
+    /// If no `fee_rate` is provided, the rate estimated by [`estimate_fee`](Wallet::estimate_fee)
+    /// for confirmation within 6 blocks is used instead.
+    ///
+    /// This is the first half of the partial version, requiring no private keys.
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`cpfp_end`](Wallet::cpfp_end) function.
+    ///
+    /// Returns a PSBT ready to be signed
+    pub fn cpfp_begin(
+        &self,
+        online: Online,
+        txid: String,
+        fee_rate: Option<f32>,
+    ) -> Result<String, Error> {
+        info!(self.logger, "Accelerating (begin) transfer '{}'...", txid);
+        self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(fee_rate)?;
+
+        let batch_transfer = self.database.get_batch_transfer_or_fail(txid.clone())?;
+        if !batch_transfer.waiting_confirmations()
+            || !batch_transfer.incoming(self.database.clone())?
+        {
+            return Err(Error::CannotCpfp);
+        }
+        let tx_details = self._get_tx_details(txid.clone())?;
+        if tx_details.get("confirmations").is_some() {
+            return Err(Error::CannotCpfp);
+        }
+
+        // the parent spends outputs of the sender, so they're looked up to compute its fee
+        let get_tx = |txid: &Txid| {
+            self._electrum_call(true, || {
+                Ok(self._electrum_client()?.transaction_get(txid)?)
+            })
+        };
+        let parent_tx = get_tx(&Txid::from_str(&txid).expect("should be a valid TXID"))?;
+        let mut input_sum = 0;
+        for input in &parent_tx.input {
+            let prev_tx = get_tx(&input.previous_output.txid)?;
+            input_sum += prev_tx
+                .output
+                .get(input.previous_output.vout as usize)
+                .ok_or(InternalError::Unexpected)?
+                .value;
+        }
+        let output_sum: u64 = parent_tx.output.iter().map(|o| o.value).sum();
+        let parent_fee = input_sum.saturating_sub(output_sum);
+        let parent_vsize = (parent_tx.weight() as f32 / 4.0).ceil();
+        let parent_fee_rate = parent_fee as f32 / parent_vsize;
+        if parent_fee_rate >= fee_rate.as_sat_per_vb() {
+            return Err(Error::InvalidFeeRate(format!(
+                "the transaction already pays {} sat/vB",
+                parent_fee_rate
+            )));
+        }
+
+        // spend the outputs of the parent owned by the wallet that cannot hold RGB allocations
+        self._sync_db_txos()?;
+        let rgb_outpoints = self._get_rgb_outpoints()?;
+        let mut parent_outputs = vec![];
+        for (vout, output) in parent_tx.output.iter().enumerate() {
+            let outpoint = OutPoint {
+                txid: parent_tx.txid(),
+                vout: vout as u32,
+            };
+            if self
+                .bdk_wallet
+                .is_mine(&output.script_pubkey)
+                .map_err(InternalError::from)?
+                && !rgb_outpoints.contains(&outpoint)
+            {
+                parent_outputs.push(outpoint);
+            }
+        }
+        if parent_outputs.is_empty() {
+            return Err(Error::CannotCpfp);
+        }
+
+        // prepare the child at the target fee rate to estimate its size, then raise its fee so
+        // the package pays the target fee rate
+        let change_script = self._get_change_script()?;
+        let prepare_child = |fee: Option<u64>| -> Result<PartiallySignedTransaction, Error> {
+            let mut builder = self.bdk_wallet.build_tx();
+            builder
+                .add_utxos(&parent_outputs)
+                .map_err(InternalError::from)?
+                .unspendable(rgb_outpoints.clone())
+                .drain_to(change_script.clone())
+                .enable_rbf();
+            match fee {
+                Some(fee) => builder.fee_absolute(fee),
+                None => builder.fee_rate(fee_rate),
+            };
+            Ok(builder
+                .finish()
+                .map_err(|e| match e {
+                    bdk::Error::InsufficientFunds { .. } => Error::InsufficientBitcoins,
+                    _ => Error::from(InternalError::from(e)),
+                })?
+                .0)
+        };
+        let child_fee = self._psbt_fee(&prepare_child(None)?)?;
+        let child_vsize = (child_fee as f32 / fee_rate.as_sat_per_vb()).ceil();
+        let package_fee = (fee_rate.as_sat_per_vb() * (parent_vsize + child_vsize)).ceil() as u64;
+        let psbt = prepare_child(Some(package_fee - parent_fee))?;
+
+        Ok(psbt.to_string())
+    }
+
+    /// Broadcast the provided PSBT of a child transaction accelerating an incoming transfer.
+    ///
+    /// This is the second half of the partial version, requiring [`Online`] data but no private keys.
+    /// The provided PSBT, prepared with the [`cpfp_begin`](Wallet::cpfp_begin) function, needs to
+    /// have already been signed.
+    ///
+    /// Returns the txid of the child transaction that's been broadcast and the fee it pays
+    pub fn cpfp_end(&self, online: Online, signed_psbt: String) -> Result<CpfpResult, Error> {
+        info!(self.logger, "Accelerating (end)...");
+        self._check_online(online)?;
+
+        let signed_psbt =
+            PartiallySignedTransaction::from_str(&signed_psbt).map_err(Error::InvalidPsbt)?;
+        let fee = self._psbt_fee(&signed_psbt)?;
+        let tx = self._broadcast_psbt(signed_psbt)?;
+
+        Ok(CpfpResult {
+            txid: tx.txid().to_string(),
+            fee,
+        })
+    }
+
+    fn _create_split_tx(
+        &self,
+        inputs: &[OutPoint],
+        num_utxos_to_create: u8,
+        size: u32,
+        fee_rate: FeeRate,
+    ) -> Result<PartiallySignedTransaction, bdk::Error> {
+        let mut tx_builder = self.bdk_wallet.build_tx();
+        tx_builder.add_utxos(inputs)?;
+        tx_builder.manually_selected_only();
+        tx_builder.fee_rate(fee_rate);
+        for _i in 0..num_utxos_to_create {
+            tx_builder.add_recipient(self._get_new_address().script_pubkey(), size as u64);
+        }
+        Ok(tx_builder.finish()?.0)
+    }
+
+    /// Create new UTXOs. See the [`create_utxos_begin`](Wallet::create_utxos_begin) function for
+    /// details.
+    ///
+    /// This is the full version, requiring a wallet with private keys and [`Online`] data
+    pub fn create_utxos(
+        &mut self,
+        online: Online,
+        up_to: bool,
+        num: Option<u8>,
+        size: Option<u32>,
+        fee_rate: Option<f32>,
+    ) -> Result<CreateUtxosResult, Error> {
+        info!(self.logger, "Creating UTXOs...");
+        self._check_xprv()?;
+
+        let unsigned_psbt = self.create_utxos_begin(online.clone(), up_to, num, size, fee_rate)?;
+
+        let mut psbt =
+            PartiallySignedTransaction::from_str(&unsigned_psbt).map_err(InternalError::from)?;
+        self.bdk_wallet
+            .sign(&mut psbt, SignOptions::default())
+            .map_err(InternalError::from)?;
+
+        self.create_utxos_end(online, psbt.to_string())
+    }
+
+    /// Prepare the PSBT to create new UTXOs to hold RGB allocations.
+    ///
+    /// If `up_to` is false, just create the required UTXOs.
+    /// If `up_to` is true, create as many UTXOs as needed to reach the requested number or return
+    /// an error if none need to be created.
+    ///
+    /// Providing the optional `num` parameter requests that many UTXOs, if it's not specified the
+    /// default number is used.
+    ///
+    /// If not enough bitcoin funds are available to create the requested (or default) number of
+    /// UTXOs, the number is decremented by one until it is possible to complete the operation. If
+    /// the number reaches zero, an error is returned.
+    ///
+    /// The transaction pays the provided `fee_rate`, in sat/vB. If no `fee_rate` is provided, the
+    /// rate estimated by [`estimate_fee`](Wallet::estimate_fee) for confirmation within 6 blocks is
+    /// used instead.
+    ///
+    /// This is the first half of the partial version, requiring no private keys nor [`Online`] data.
+    /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
+    /// to be fed to the [`create_utxos_end`](Wallet::create_utxos_end) function.
+    ///
+    /// Returns a PSBT ready to be signed
+    pub fn create_utxos_begin(
+        &mut self,
+        online: Online,
+        up_to: bool,
+        num: Option<u8>,
+        size: Option<u32>,
+        fee_rate: Option<f32>,
+    ) -> Result<String, Error> {
+        info!(self.logger, "Creating UTXOs (begin)...");
+        self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(fee_rate)?;
+
+        self._sync_db_txos()?;
+
+        let unspent_txos = self.database.get_unspent_txos()?;
+        let unspents: Vec<LocalUnspent> =
+            self.database
+                .get_rgb_allocations(unspent_txos.clone(), false, None, None, None)?;
+        let allocatable = self
+            ._get_available_allocations(unspents.clone(), vec![], None)?
+            .len() as u8;
+
+        let mut utxos_to_create = num.unwrap_or(UTXO_NUM);
+        if up_to {
+            if allocatable >= utxos_to_create {
+                return Err(Error::AllocationsAlreadyAvailable);
+            } else {
+                utxos_to_create -= allocatable
+            }
+        }
+        debug!(self.logger, "Will try to create {} UTXOs", utxos_to_create);
+
+        let inputs: Vec<OutPoint> = unspent_txos
+            .into_iter()
+            .filter(|u| !u.colorable)
+            .map(OutPoint::from)
+            .collect();
+        let inputs: &[OutPoint] = &inputs;
+        let new_btc_amount = self._get_uncolorable_btc_sum(unspents);
+        let utxo_size = size.unwrap_or(UTXO_SIZE);
+        let max_possible_utxos = new_btc_amount / utxo_size as u64;
+        let mut num_try_creating = min(utxos_to_create, max_possible_utxos as u8);
+        while num_try_creating > 0 {
+            match self._create_split_tx(inputs, num_try_creating, utxo_size, fee_rate) {
+                Ok(_v) => break,
+                Err(_e) => num_try_creating -= 1,
+            };
+        }
+
+        if num_try_creating == 0 {
+            Err(Error::InsufficientBitcoins)
+        } else {
+            Ok(self
+                ._create_split_tx(inputs, num_try_creating, utxo_size, fee_rate)
+                .map_err(InternalError::from)?
+                .to_string())
+        }
+    }
+
+    /// Broadcast the provided PSBT to create new UTXOs.
+    ///
     /// This is the second half of the partial version, requiring [`Online`] data but no private keys.
     /// The provided PSBT, prepared with the [`create_utxos_begin`](Wallet::create_utxos_begin)
     /// function, needs to have already been signed.
     ///
-    /// Returns the number of created UTXOs
-    pub fn create_utxos_end(&self, online: Online, signed_psbt: String) -> Result<u8, Error> {
+    /// Returns the number of created UTXOs and the fee paid
+    pub fn create_utxos_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<CreateUtxosResult, Error> {
         info!(self.logger, "Creating UTXOs (end)...");
         self._check_online(online)?;
 
         let signed_psbt =
             PartiallySignedTransaction::from_str(&signed_psbt).map_err(Error::InvalidPsbt)?;
+        let fee = self._psbt_fee(&signed_psbt)?;
         let tx = self._broadcast_psbt(signed_psbt)?;
 
         let mut num_utxos_created = 0;
@@ -1197,7 +2778,10 @@ impl Wallet {
             }
         }
 
-        Ok(num_utxos_created)
+        Ok(CreateUtxosResult {
+            num_utxos: num_utxos_created,
+            fee,
+        })
     }
 
     fn _delete_batch_transfer(&self, batch_transfer: &DbBatchTransfer) -> Result<(), Error> {
@@ -1284,14 +2868,16 @@ impl Wallet {
         online: Online,
         address: String,
         destroy_assets: bool,
-    ) -> Result<String, Error> {
+        fee_rate: Option<f32>,
+    ) -> Result<DrainToResult, Error> {
         info!(
             self.logger,
             "Draining to '{}' destroying asset '{}'...", address, destroy_assets
         );
         self._check_xprv()?;
 
-        let unsigned_psbt = self.drain_to_begin(online.clone(), address, destroy_assets)?;
+        let unsigned_psbt =
+            self.drain_to_begin(online.clone(), address, destroy_assets, fee_rate)?;
 
         let mut psbt =
             PartiallySignedTransaction::from_str(&unsigned_psbt).map_err(InternalError::from)?;
@@ -1308,6 +2894,10 @@ impl Wallet {
     /// Warning: setting `destroy_assets` to true is dangerous, only do this if you know what
     /// you're doing!
     ///
+    /// The transaction pays the provided `fee_rate`, in sat/vB. If no `fee_rate` is provided, the
+    /// rate estimated by [`estimate_fee`](Wallet::estimate_fee) for confirmation within 6 blocks is
+    /// used instead.
+    ///
     /// This is the first half of the partial version, requiring no private keys.
     /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
     /// to be fed to the [`drain_to_end`](Wallet::drain_to_end) function.
@@ -1318,33 +2908,25 @@ impl Wallet {
         online: Online,
         address: String,
         destroy_assets: bool,
+        fee_rate: Option<f32>,
     ) -> Result<String, Error> {
         info!(
             self.logger,
             "Draining (begin) to '{}' destroying asset '{}'...", address, destroy_assets
         );
         self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(fee_rate)?;
 
         let address = Address::from_str(&address).map(|x| x.script_pubkey())?;
 
         let mut tx_builder = self.bdk_wallet.build_tx();
-        tx_builder.drain_wallet().drain_to(address);
+        tx_builder
+            .drain_wallet()
+            .drain_to(address)
+            .fee_rate(fee_rate);
 
         if !destroy_assets {
-            let colored_txos: Vec<i64> = self
-                .database
-                .iter_colorings()?
-                .into_iter()
-                .map(|c| c.txo_idx)
-                .collect();
-            let unspendable: Vec<OutPoint> = self
-                .database
-                .iter_txos()?
-                .into_iter()
-                .filter(|t| t.colorable || colored_txos.contains(&t.idx))
-                .map(OutPoint::from)
-                .collect();
-            tx_builder.unspendable(unspendable);
+            tx_builder.unspendable(self._get_rgb_outpoints()?);
         }
 
         Ok(tx_builder
@@ -1363,16 +2945,160 @@ impl Wallet {
     /// The provided PSBT, prepared with the [`drain_to_begin`](Wallet::drain_to_begin) function,
     /// needs to have already been signed.
     ///
-    /// Returns the txid of the transaction that's been broadcast
-    pub fn drain_to_end(&self, online: Online, signed_psbt: String) -> Result<String, Error> {
+    /// Returns the txid of the transaction that's been broadcast and the fee paid
+    pub fn drain_to_end(
+        &self,
+        online: Online,
+        signed_psbt: String,
+    ) -> Result<DrainToResult, Error> {
         info!(self.logger, "Draining (end)...");
         self._check_online(online)?;
 
         let signed_psbt =
             PartiallySignedTransaction::from_str(&signed_psbt).map_err(Error::InvalidPsbt)?;
+        let fee = self._psbt_fee(&signed_psbt)?;
         let tx = self._broadcast_psbt(signed_psbt)?;
 
-        Ok(tx.txid().to_string())
+        Ok(DrainToResult {
+            txid: tx.txid().to_string(),
+            fee,
+        })
+    }
+
+    fn _estimate_fee(&self, target_blocks: u16) -> Result<f32, Error> {
+        if target_blocks == 0 || target_blocks > MAX_FEE_TARGET_BLOCKS {
+            return Err(Error::InvalidFeeTarget(format!(
+                "{} is not between 1 and {} blocks",
+                target_blocks, MAX_FEE_TARGET_BLOCKS
+            )));
+        }
+
+        // the server answers with BTC/kvB, or a negative value if it has no estimate
+        let estimate = self._electrum_call(true, || {
+            Ok(self
+                ._electrum_client()?
+                .estimate_fee(target_blocks as usize)?)
+        });
+        let btc_per_kvb = match estimate {
+            Ok(btc_per_kvb) if btc_per_kvb > 0.0 => btc_per_kvb,
+            // regtest servers usually lack the data to estimate fees, or fail the call outright
+            _ if self.bitcoin_network == BitcoinNetwork::Regtest => {
+                debug!(self.logger, "No fee estimate on regtest, using the minimum");
+                return Ok(MIN_FEE_RATE);
+            }
+            Ok(_) => {
+                return Err(Error::FeeEstimationUnavailable(format!(
+                    "no estimate for confirmation within {} blocks",
+                    target_blocks
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+        let sat_per_vb = (btc_per_kvb * 100_000.0) as f32;
+        Ok(sat_per_vb.clamp(MIN_FEE_RATE, MAX_FEE_RATE))
+    }
+
+    fn _get_fee_rate(&self, fee_rate: Option<f32>) -> Result<FeeRate, Error> {
+        match fee_rate {
+            Some(fee_rate) => check_fee_rate(fee_rate),
+            None => {
+                let fee_rate = self._estimate_fee(FEE_TARGET_BLOCKS)?;
+                debug!(self.logger, "Using estimated fee rate {} sat/vB", fee_rate);
+                Ok(FeeRate::from_sat_per_vb(fee_rate))
+            }
+        }
+    }
+
+    /// Estimate the fee rate, in sat/vB, needed for a transaction to be confirmed within
+    /// `target_blocks` blocks, as reported by the electrum server
+    ///
+    /// The estimate is capped to the range of fee rates accepted by the wallet. On regtest, where
+    /// the server usually has no estimate, the minimum fee rate is returned.
+    pub fn estimate_fee(&self, online: Online, target_blocks: u16) -> Result<f32, Error> {
+        info!(
+            self.logger,
+            "Estimating fee for {} blocks...", target_blocks
+        );
+        self._check_online(online)?;
+
+        self._estimate_fee(target_blocks)
+    }
+
+    /// Set the expiration of the pending incoming transfer for the provided `invoice`, as returned
+    /// by [`blind`](Wallet::blind), to `duration_seconds` from now and return the updated
+    /// [`BlindData`], including the re-issued invoice
+    ///
+    /// If no duration is provided, the default of 1 day is used. A duration of 0 clears the
+    /// expiration. Only transfers in status [`TransferStatus::WaitingCounterparty`] can be
+    /// updated.
+    pub fn extend_blind_expiration(
+        &mut self,
+        invoice: String,
+        duration_seconds: Option<u32>,
+    ) -> Result<BlindData, Error> {
+        info!(
+            self.logger,
+            "Extending expiration of invoice '{}' by '{:?}'...", invoice, duration_seconds
+        );
+        let mut invoice_data = Invoice::new(invoice)?.invoice_data();
+        let transfer = self
+            .database
+            .get_transfer_or_fail(invoice_data.blinded_utxo.clone())?;
+        let (_, batch_transfer) = transfer.related_transfers(self.database.clone())?;
+        if !batch_transfer.waiting_counterparty()
+            || !batch_transfer.incoming(self.database.clone())?
+        {
+            return Err(Error::CannotChangeExpiration);
+        }
+
+        let expiration = get_expiration(duration_seconds, DURATION_RCV_TRANSFER);
+        let mut updated_transfer: DbBatchTransferActMod = batch_transfer.into();
+        updated_transfer.expiration = ActiveValue::Set(expiration);
+        self.database.update_batch_transfer(&mut updated_transfer)?;
+
+        invoice_data.expiration_timestamp = expiration;
+        let invoice = Invoice::from_invoice_data(invoice_data)?;
+        Ok(BlindData {
+            invoice: invoice.bech32_invoice(),
+            blinded_utxo: invoice.invoice_data.blinded_utxo,
+            blinding_secret: transfer
+                .blinding_secret
+                .expect("incoming transfer should have a blinding secret")
+                .parse::<u64>()
+                .expect("DB should contain a valid u64 value"),
+            expiration_timestamp: expiration,
+            consignment_key: transfer.consignment_key.unwrap_or_default(),
+        })
+    }
+
+    /// Set the expiration of the outgoing batch transfer with the provided `txid` to
+    /// `duration_seconds` from now and return the new expiration timestamp
+    ///
+    /// If no duration is provided, the default of 1 hour is used. A duration of 0 clears the
+    /// expiration. Only transfers in status [`TransferStatus::WaitingCounterparty`] can be
+    /// updated.
+    pub fn extend_send_expiration(
+        &mut self,
+        txid: String,
+        duration_seconds: Option<u32>,
+    ) -> Result<Option<i64>, Error> {
+        info!(
+            self.logger,
+            "Extending expiration of transfer '{}' by '{:?}'...", txid, duration_seconds
+        );
+        let batch_transfer = self.database.get_batch_transfer_or_fail(txid)?;
+        if !batch_transfer.waiting_counterparty()
+            || batch_transfer.incoming(self.database.clone())?
+        {
+            return Err(Error::CannotChangeExpiration);
+        }
+
+        let expiration = get_expiration(duration_seconds, DURATION_SEND_TRANSFER);
+        let mut updated_transfer: DbBatchTransferActMod = batch_transfer.into();
+        updated_transfer.expiration = ActiveValue::Set(expiration);
+        self.database.update_batch_transfer(&mut updated_transfer)?;
+
+        Ok(expiration)
     }
 
     fn _fail_batch_transfer(
@@ -1386,7 +3112,8 @@ impl Wallet {
             let mut updated_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
             updated_transfer.status = ActiveValue::Set(TransferStatus::Failed);
             updated_transfer.expiration = ActiveValue::Set(Some(now().unix_timestamp()));
-            self.database.update_batch_transfer(&mut updated_transfer)?;
+            let updated = self.database.update_batch_transfer(&mut updated_transfer)?;
+            self._notify_status_change(&updated, Some(batch_transfer.status));
         } else if throw_err {
             return Err(Error::CannotFailTransfer);
         }
@@ -1470,6 +3197,67 @@ impl Wallet {
             .address
     }
 
+    /// Return the script BTC change is drained to
+    ///
+    /// The last change address is reused until it receives funds, so sends that are aborted or
+    /// never completed don't consume addresses. A change address is reserved by the pending send
+    /// draining to it, though, so a new one is derived if the last one is reserved and concurrent
+    /// sends never share a change address.
+    fn _get_change_script(&self) -> Result<Script, Error> {
+        let reserved_scripts = self._get_reserved_change_scripts()?;
+        let mut address_index = AddressIndex::LastUnused;
+        loop {
+            let script = self
+                .bdk_wallet
+                .get_internal_address(address_index)
+                .map_err(InternalError::from)?
+                .address
+                .script_pubkey();
+            if !reserved_scripts.contains(&script) {
+                return Ok(script);
+            }
+            address_index = AddressIndex::New;
+        }
+    }
+
+    /// Return the scripts BTC change is drained to by prepared and pending sends
+    ///
+    /// Failed and settled sends don't reserve their change script, as it has either never been
+    /// broadcast or it's known to BDK as used.
+    fn _get_reserved_change_scripts(&self) -> Result<HashSet<Script>, Error> {
+        let mut reserved_scripts = HashSet::new();
+        let transfers_dir = self.wallet_dir.join(TRANSFER_DIR);
+        if !transfers_dir.is_dir() {
+            return Ok(reserved_scripts);
+        }
+        let completed_txids: HashSet<String> = self
+            .database
+            .iter_batch_transfers()?
+            .into_iter()
+            .filter(|t| !t.pending())
+            .filter_map(|t| t.txid)
+            .collect();
+        for entry in fs::read_dir(transfers_dir)? {
+            let transfer_dir = entry?.path();
+            let name = transfer_dir
+                .file_name()
+                .expect("valid directory name")
+                .to_string_lossy()
+                .to_string();
+            let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+            if completed_txids.contains(&name) || !info_file.exists() {
+                continue;
+            }
+            let serialized_info = fs::read_to_string(info_file)?;
+            let info_contents: InfoBatchTransfer =
+                serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+            if let Some(change_script) = info_contents.change_script {
+                reserved_scripts.insert(change_script);
+            }
+        }
+        Ok(reserved_scripts)
+    }
+
     /// Return a new bitcoin address
     pub fn get_address(&self) -> String {
         info!(self.logger, "Getting address...");
@@ -1495,10 +3283,7 @@ impl Wallet {
         self.database.get_asset_or_fail(asset_id.clone())?;
 
         let contract_id = ContractId::from_str(&asset_id).map_err(InternalError::from)?;
-        let contract_state = self
-            ._rgb_client()?
-            .contract_state(contract_id)
-            .map_err(InternalError::from)?;
+        let contract_state = self._rgb_call(|c| c.contract_state(contract_id))?;
         let node_id = NodeId::from_inner(contract_id.into_inner());
         let metadata = match contract_state.metadata.get(&node_id) {
             Some(m) => Ok(RgbMetadata::from_inner(m.clone())),
@@ -1617,9 +3402,7 @@ impl Wallet {
         }
 
         let asset_ids: Vec<String> = self
-            ._rgb_client()?
-            .list_contracts()
-            .map_err(InternalError::from)?
+            ._rgb_call(|c| c.list_contracts())?
             .iter()
             .map(|id| id.to_string())
             .collect();
@@ -1629,7 +3412,87 @@ impl Wallet {
                 "DB assets do not match with ones stored in RGB"
             )));
         }
-
+
+        Ok(())
+    }
+
+    /// Complete or roll back the sends that were interrupted while running
+    /// [`send_end`](Wallet::send_end)
+    ///
+    /// Sends that were interrupted before all their transfer data was posted go back to their
+    /// unsigned state, so they can be ended again or aborted. The other ones are saved to DB, as
+    /// long as their transaction has been broadcast in case of donations.
+    fn _resume_interrupted_sends(&self) -> Result<(), Error> {
+        let transfers_dir = self.wallet_dir.join(TRANSFER_DIR);
+        if !transfers_dir.is_dir() {
+            return Ok(());
+        }
+        let saved_txids: HashSet<String> = self
+            .database
+            .iter_batch_transfers()?
+            .into_iter()
+            .filter_map(|t| t.txid)
+            .collect();
+        for entry in fs::read_dir(transfers_dir)? {
+            let transfer_dir = entry?.path();
+            let journal_file = transfer_dir.join(SEND_JOURNAL_FILE);
+            if !journal_file.exists() {
+                continue;
+            }
+            let txid = transfer_dir
+                .file_name()
+                .expect("valid directory name")
+                .to_string_lossy()
+                .to_string();
+            if saved_txids.contains(&txid) {
+                fs::remove_file(journal_file)?;
+                continue;
+            }
+            let serialized_journal = fs::read_to_string(journal_file)?;
+            let journal: InfoSendJournal =
+                serde_json::from_str(&serialized_journal).map_err(InternalError::from)?;
+            if journal.step == SendJournalStep::Posting {
+                self._rollback_send(&txid, &transfer_dir)?;
+                continue;
+            }
+
+            let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+            let serialized_info = fs::read_to_string(info_file)?;
+            let info_contents: InfoBatchTransfer =
+                serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+            let status = if info_contents.donation {
+                match self._get_tx_details(txid.clone()) {
+                    Ok(_) => {}
+                    Err(e) if is_unknown_tx_error(&e) => {
+                        self._rollback_send(&txid, &transfer_dir)?;
+                        continue;
+                    }
+                    Err(e) => {
+                        // try again at the next go_online, when the server may be reachable
+                        warn!(
+                            self.logger,
+                            "Cannot check if interrupted send '{}' was broadcast: {}", txid, e
+                        );
+                        continue;
+                    }
+                }
+                let tx = self._get_signed_psbt(transfer_dir.clone())?.extract_tx();
+                self._set_inputs_spent(&tx)?;
+                TransferStatus::WaitingConfirmations
+            } else {
+                TransferStatus::WaitingCounterparty
+            };
+            debug!(self.logger, "Completing interrupted send '{}'", txid);
+            let transfer_info_map = self._get_transfer_info_map(&transfer_dir)?;
+            self._complete_send(
+                txid,
+                &transfer_dir,
+                info_contents,
+                transfer_info_map,
+                journal.posted_consignments,
+                status,
+            )?;
+        }
         Ok(())
     }
 
@@ -1637,44 +3500,70 @@ impl Wallet {
         &mut self,
         skip_consistency_check: bool,
         electrum_url: String,
-        proxy_url: String,
+        proxy_urls: Vec<String>,
+        proxy_options: Option<ProxyOptions>,
+        network_policy: NetworkPolicy,
     ) -> Result<Online, Error> {
+        network_policy.validate()?;
+        if proxy_urls.is_empty() {
+            return Err(Error::InvalidProxyUrls(s!("no proxy URL provided")));
+        }
+        if proxy_urls.iter().collect::<HashSet<_>>().len() != proxy_urls.len() {
+            return Err(Error::InvalidProxyUrls(s!("duplicate proxy URLs provided")));
+        }
         let online_id = now().unix_timestamp_nanos() as u64;
         let online = Online {
             id: online_id,
             electrum_url: electrum_url.clone(),
-            proxy_url: proxy_url.clone(),
+            proxy_urls: proxy_urls.clone(),
+            proxy_options: proxy_options.clone(),
+            network_policy: network_policy.clone(),
         };
         self.online = Some(online.clone());
+        self.rest_client =
+            build_proxy_client(network_policy.proxy_timeout(), proxy_options.as_ref())?;
+        self.public_rest_client = build_proxy_client(network_policy.proxy_timeout(), None)?;
+        self.network_policy = network_policy.clone();
 
         // check electrum server
-        let electrum_config = ConfigBuilder::new()
-            .timeout(Some(ELECTRUM_TIMEOUT))?
-            .build();
-        self.electrum_client = Some(
-            ElectrumClient::from_config(&electrum_url, electrum_config)
-                .map_err(|e| Error::InvalidElectrum(e.to_string()))?,
-        );
+        self._connect_electrum()
+            .map_err(|e| Error::InvalidElectrum(e.to_string()))?;
         if self.bitcoin_network != BitcoinNetwork::Regtest {
             self._get_tx_details(get_txid(self.bitcoin_network))
                 .map_err(|e| Error::InvalidElectrum(e.to_string()))?;
         }
 
-        // check proxy server
-        self.rest_client.clone().get_info(&proxy_url)?;
-
-        // BDK setup
-        let config = ElectrumBlockchainConfig {
-            url: electrum_url.clone(),
-            socks5: None,
-            retry: 3,
-            timeout: Some(5),
-            stop_gap: 20,
-        };
-        self.bdk_blockchain = Some(
-            ElectrumBlockchain::from_config(&config)
-                .map_err(|e| Error::InvalidElectrum(e.to_string()))?,
-        );
+        // check proxy servers and negotiate their protocol version, unreachable ones and the
+        // ones with an unsupported version are skipped as long as at least one is available
+        let mut proxy_err = None;
+        let mut available_proxies = 0;
+        for proxy_url in &proxy_urls {
+            match self
+                ._proxy_call(proxy_url, |c| c.get_info(proxy_url))
+                .and_then(|info_res| info_res.supported_version())
+            {
+                Ok(proxy_version) => {
+                    debug!(
+                        self.logger,
+                        "Using protocol version {} for proxy '{}'", proxy_version, proxy_url
+                    );
+                    self.proxy_versions
+                        .borrow_mut()
+                        .insert(proxy_url.clone(), proxy_version);
+                    available_proxies += 1;
+                }
+                Err(e) => {
+                    warn!(
+                        self.logger,
+                        "Proxy server '{}' unavailable: {}", proxy_url, e
+                    );
+                    proxy_err = Some(e);
+                }
+            }
+        }
+        if available_proxies == 0 {
+            return Err(proxy_err.expect("at least a proxy server should have been checked"));
+        }
 
         // RGB setup
         let rgb_network = RgbNetwork::from(self.bitcoin_network);
@@ -1709,19 +3598,27 @@ impl Wallet {
             Client::with(rpc_endpoint, "rgb-ffi".to_string(), rgb_network)
                 .expect("Error initializing client"),
         );
-        let mut tries_left: usize = 20;
+        let rgb_node_timeout = Duration::from_secs(network_policy.rgb_node_timeout as u64);
+        let started = Instant::now();
+        let mut attempt = 0;
         while let Err(_assets) = self._rgb_client()?.list_contracts() {
-            if tries_left < 1 {
+            let elapsed = started.elapsed();
+            if elapsed >= rgb_node_timeout {
                 return Err(InternalError::CannotQueryRgbNode)?;
             }
             debug!(
                 self.logger,
-                "Trying to contact rgbd, tries left {}", tries_left
+                "Trying to contact rgbd, attempt {}",
+                attempt + 1
             );
-            tries_left -= 1;
-            std::thread::sleep(Duration::from_millis(500));
+            std::thread::sleep(min(
+                network_policy.backoff(attempt),
+                rgb_node_timeout - elapsed,
+            ));
+            attempt += 1;
         }
 
+        self._resume_interrupted_sends()?;
         if !skip_consistency_check {
             self._check_consistency()?;
         }
@@ -1733,25 +3630,54 @@ impl Wallet {
     ///
     /// Setting `skip_consistency_check` to true bypases the check and allows operating an
     /// inconsistent wallet. Warning: this is dangerous, only do this if you know what you're doing!
+    ///
+    /// Multiple `proxy_urls` can be provided, in order of preference: transfer data is posted to
+    /// the first available one and all of them are checked for incoming transfers. At least one
+    /// of them needs to be available when going online.
+    ///
+    /// Optional [`ProxyOptions`] can be provided to authenticate with the proxy servers.
+    ///
+    /// An optional [`NetworkPolicy`] sets timeouts and retries for network calls, the default
+    /// one is used if none is provided.
+    ///
+    /// Sends interrupted while running [`send_end`](Wallet::send_end) are completed or rolled
+    /// back before the consistency check.
     pub fn go_online(
         &mut self,
         skip_consistency_check: bool,
         electrum_url: String,
-        proxy_url: String,
+        proxy_urls: Vec<String>,
+        proxy_options: Option<ProxyOptions>,
+        network_policy: Option<NetworkPolicy>,
     ) -> Result<Online, Error> {
         info!(self.logger, "Going online...");
+        let network_policy = network_policy.unwrap_or_default();
         if let Some(online) = self.online.clone() {
-            if electrum_url == online.electrum_url && proxy_url == online.proxy_url {
+            if electrum_url == online.electrum_url
+                && proxy_urls == online.proxy_urls
+                && proxy_options == online.proxy_options
+                && network_policy == online.network_policy
+            {
                 Ok(online)
             } else {
                 Err(Error::CannotChangeOnline())
             }
         } else {
-            let online = self._go_online(skip_consistency_check, electrum_url, proxy_url);
+            let online = self._go_online(
+                skip_consistency_check,
+                electrum_url,
+                proxy_urls,
+                proxy_options,
+                network_policy,
+            );
             if online.is_err() {
                 self.online = None;
-                self.bdk_blockchain = None;
-                self.electrum_client = None;
+                self.proxy_versions = RefCell::new(HashMap::new());
+                self.network_policy = NetworkPolicy::default();
+                self.rest_client = build_proxy_client(self.network_policy.proxy_timeout(), None)?;
+                self.public_rest_client = self.rest_client.clone();
+                self.bdk_blockchain = RefCell::new(None);
+                self.electrum_client = RefCell::new(None);
                 self.rgb_client = None;
             }
             online
@@ -1814,10 +3740,7 @@ impl Wallet {
         let _rgb_asset =
             Rgb20Asset::try_from(&asset).expect("create_rgb20 does not match RGB20 schema");
         let force = true;
-        let status = self
-            ._rgb_client()?
-            .register_contract(asset.clone(), force, |_| ())
-            .map_err(InternalError::from)?;
+        let status = self._rgb_call(|c| c.register_contract(asset.clone(), force, |_| ()))?;
         if !matches!(status, ContractValidity::Valid) {
             return Err(Error::FailedIssuance(format!("{:?}", status)));
         }
@@ -1955,10 +3878,7 @@ impl Wallet {
         let _rgb_asset =
             Rgb121Asset::try_from(&asset).expect("create_rgb121 does not match RGB121 schema");
         let force = true;
-        let status = self
-            ._rgb_client()?
-            .register_contract(asset.clone(), force, |_| ())
-            .map_err(InternalError::from)?;
+        let status = self._rgb_call(|c| c.register_contract(asset.clone(), force, |_| ()))?;
         if !matches!(status, ContractValidity::Valid) {
             return Err(Error::FailedIssuance(format!("{:?}", status)));
         }
@@ -2183,6 +4103,114 @@ impl Wallet {
         PartiallySignedTransaction::from_str(&psbt_str).map_err(Error::InvalidPsbt)
     }
 
+    /// Return the proxy servers to contact for the given transfer
+    ///
+    /// This is the one the consignment has been exchanged through, if known, otherwise the
+    /// transport endpoints from the transfer's invoice followed by the wallet's proxy servers.
+    fn _transfer_proxy_urls(&self, transfer: &DbTransfer) -> Result<Vec<String>, Error> {
+        if let Some(proxy_url) = &transfer.proxy_url {
+            return Ok(vec![proxy_url.clone()]);
+        }
+        let mut proxy_urls: Vec<String> = match &transfer.transport_endpoints {
+            Some(endpoints) => serde_json::from_str(endpoints).map_err(InternalError::from)?,
+            None => vec![],
+        };
+        for proxy_url in self._proxy_urls()? {
+            if !proxy_urls.contains(&proxy_url) {
+                proxy_urls.push(proxy_url);
+            }
+        }
+        Ok(proxy_urls)
+    }
+
+    fn _refuse_consignment(
+        &self,
+        proxy_url: &str,
+        blinded_utxo: String,
+        updated_batch_transfer: &mut DbBatchTransferActMod,
+    ) -> Result<Option<DbBatchTransfer>, Error> {
+        self._check_proxy_version(proxy_url)?;
+        let nack_res =
+            self._proxy_post(proxy_url, |c| c.post_nack(proxy_url, blinded_utxo.clone()))?;
+        debug!(self.logger, "Consignment NACK response: {:?}", nack_res);
+        updated_batch_transfer.status = ActiveValue::Set(TransferStatus::Failed);
+        Ok(Some(
+            self.database
+                .update_batch_transfer(updated_batch_transfer)?,
+        ))
+    }
+
+    /// Return the consignment posted for the provided incoming transfer under the provided
+    /// `recipient_id`, if any, along with the URL of the proxy server it has been found on
+    ///
+    /// Consignments that cannot be decrypted with the transfer's key are returned as `None`, as
+    /// are cleartext ones if the transfer requested encryption.
+    fn _fetch_consignment(
+        &self,
+        transfer: &DbTransfer,
+        recipient_id: &str,
+    ) -> Result<Option<(String, Option<Vec<u8>>)>, Error> {
+        let proxy_urls = self._transfer_proxy_urls(transfer)?;
+        let consignment_res = self._proxy_failover(&proxy_urls, true, |c, proxy_url| {
+            let consignment_res = c.get_consignment(proxy_url, recipient_id.to_string())?;
+            debug!(
+                self.logger,
+                "Consignment GET response from '{}': {:?}", proxy_url, consignment_res
+            );
+            Ok(consignment_res.consignment)
+        })?;
+        let (proxy_url, consignment) = if let Some(res) = consignment_res {
+            res
+        } else {
+            return Ok(None);
+        };
+        let consignment_bytes = base64::decode(consignment).map_err(InternalError::from)?;
+        let decrypted = match &transfer.consignment_key {
+            Some(key) => encryption::decrypt(key, &consignment_bytes)?,
+            None if !encryption::is_encrypted(&consignment_bytes) => Some(consignment_bytes),
+            None => None,
+        };
+        Ok(Some((proxy_url, decrypted)))
+    }
+
+    /// Return the amount assigned to the UTXO of the provided incoming transfer by the transitions
+    /// in the provided bundle
+    fn _get_received_amount(
+        &self,
+        transfer: &DbTransfer,
+        transition_bundle: &TransitionBundle,
+    ) -> Result<u64, Error> {
+        let transfer_data = self.database.get_transfer_data(transfer)?;
+        let detailed_transfer = Transfer::from_db_transfer(transfer.clone(), transfer_data);
+        let blinding = detailed_transfer
+            .blinding_secret
+            .expect("incoming transfer should have a blinding secret");
+        let unblinded_utxo = detailed_transfer
+            .unblinded_utxo
+            .ok_or(InternalError::Unexpected)?;
+        let known_concealed = seal::Revealed {
+            method: CloseMethod::OpretFirst,
+            blinding,
+            txid: Some(Txid::from_str(&unblinded_utxo.txid).expect("should be a valid TXID")),
+            vout: unblinded_utxo.vout as u32,
+        }
+        .to_concealed_seal();
+        let mut amount = 0;
+        for transition in transition_bundle.known_transitions() {
+            let owned_rights = transition.owned_rights();
+            for (_owned_right_type, typed_assignment) in owned_rights.iter() {
+                for assignment in typed_assignment.to_value_assignments() {
+                    if let Assignment::ConfidentialSeal { seal, state } = assignment {
+                        if seal == known_concealed {
+                            amount += state.value;
+                        }
+                    };
+                }
+            }
+        }
+        Ok(amount)
+    }
+
     fn _wait_consignment(
         &mut self,
         batch_transfer: &DbBatchTransfer,
@@ -2194,21 +4222,17 @@ impl Wallet {
             .clone()
             .expect("transfer should have a blinded UTXO");
 
-        // check if a consignment has been posted
-        let proxy_url = self.online.clone().expect("should be online").proxy_url;
-        let consignment_res = self
-            .rest_client
-            .clone()
-            .get_consignment(&proxy_url, blinded_utxo.clone())?;
-        debug!(
-            self.logger,
-            "Consignment GET response: {:?}", consignment_res
-        );
-        let consignment = if let Some(cons) = consignment_res.consignment {
-            cons
-        } else {
-            return Ok(None);
-        };
+        // check if a consignment has been posted to any of the proxy servers
+        let (proxy_url, consignment_bytes) =
+            match self._fetch_consignment(&transfer, &blinded_utxo)? {
+                Some(res) => res,
+                None => return Ok(None),
+            };
+        if transfer.proxy_url.is_none() {
+            let mut updated_transfer: DbTransferActMod = transfer.clone().into();
+            updated_transfer.proxy_url = ActiveValue::Set(Some(proxy_url.clone()));
+            self.database.update_transfer(&mut updated_transfer)?;
+        }
 
         let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
 
@@ -2219,7 +4243,17 @@ impl Wallet {
             .join(blinded_utxo.clone());
         let consignment_path = transfer_dir.join(CONSIGNMENT_RCV_FILE);
         fs::create_dir_all(transfer_dir)?;
-        let consignment_bytes = base64::decode(consignment).map_err(InternalError::from)?;
+        let consignment_bytes = match consignment_bytes {
+            Some(bytes) => bytes,
+            None => {
+                debug!(self.logger, "Consignment cannot be decrypted");
+                return self._refuse_consignment(
+                    &proxy_url,
+                    blinded_utxo,
+                    &mut updated_batch_transfer,
+                );
+            }
+        };
         fs::write(consignment_path.clone(), consignment_bytes).expect("Unable to write file");
         let consignment =
             StateTransfer::strict_file_load(&consignment_path).map_err(InternalError::from)?;
@@ -2238,9 +4272,10 @@ impl Wallet {
             }
         }
 
-        // validate consignment
-        let proxy_url = self.online.clone().expect("should be online").proxy_url;
-        let validation_status = Validator::validate(&consignment, self._electrum_client()?);
+        // validate consignment, failing transaction lookups would make it invalid so first make
+        // sure the electrum server is reachable
+        self._electrum_call(true, || Ok(self._electrum_client()?.ping()?))?;
+        let validation_status = Validator::validate(&consignment, &*self._electrum_client()?);
         if valid
             && !vec![Validity::Valid, Validity::ValidExceptEndpoints]
                 .contains(&validation_status.validity())
@@ -2256,13 +4291,28 @@ impl Wallet {
                 for ass in assigments {
                     if let Assignment::Revealed { seal: _, state } = ass {
                         let attachment_id = state.id;
-                        let media_res = self
-                            .rest_client
-                            .clone()
-                            .get_media(&proxy_url, attachment_id.to_string().clone())?;
+                        let media_id = match transfer.consignment_key {
+                            Some(_) => {
+                                encrypted_media_id(&attachment_id.to_string(), &blinded_utxo)
+                            }
+                            None => attachment_id.to_string(),
+                        };
+                        let media_res = self._proxy_call(&proxy_url, |c| {
+                            c.get_media(&proxy_url, media_id.clone())
+                        })?;
                         debug!(self.logger, "Media GET response: {:?}", media_res);
-                        if let Some(media) = media_res.media {
-                            let file_bytes = base64::decode(media).map_err(InternalError::from)?;
+                        let media_bytes = match media_res.media {
+                            Some(media) => base64::decode(media).map_err(InternalError::from)?,
+                            None => {
+                                valid = false;
+                                break;
+                            }
+                        };
+                        let media_bytes = match &transfer.consignment_key {
+                            Some(key) => encryption::decrypt(key, &media_bytes)?,
+                            None => Some(media_bytes),
+                        };
+                        if let Some(file_bytes) = media_bytes {
                             let file_hash: sha256::Hash = Sha256Hash::hash(&file_bytes[..]);
                             let real_attachment_id = AttachmentId::commit(&file_hash);
                             if attachment_id != real_attachment_id {
@@ -2290,25 +4340,14 @@ impl Wallet {
         let contract_consignment: Contract =
             strict_deserialize(ser_cons).map_err(InternalError::from)?;
 
-        let status = self
-            ._rgb_client()?
-            .register_contract(contract_consignment, true, |_| ())
-            .map_err(InternalError::from)?;
+        let status =
+            self._rgb_call(|c| c.register_contract(contract_consignment.clone(), true, |_| ()))?;
         if !matches!(status, ContractValidity::Valid) {
             valid = false;
         }
 
         if !valid {
-            let nack_res = self
-                .rest_client
-                .clone()
-                .post_nack(&proxy_url, blinded_utxo)?;
-            debug!(self.logger, "Consignment NACK response: {:?}", nack_res);
-            updated_batch_transfer.status = ActiveValue::Set(TransferStatus::Failed);
-            return Ok(Some(
-                self.database
-                    .update_batch_transfer(&mut updated_batch_transfer)?,
-            ));
+            return self._refuse_consignment(&proxy_url, blinded_utxo, &mut updated_batch_transfer);
         }
 
         debug!(self.logger, "Consignment is valid");
@@ -2317,10 +4356,9 @@ impl Wallet {
             .last()
             .expect("there should be at least an anchored bundle");
         let txid = anchor.txid;
-        let ack_res = self
-            .rest_client
-            .clone()
-            .post_ack(&proxy_url, blinded_utxo)?;
+        self._check_proxy_version(&proxy_url)?;
+        let ack_res =
+            self._proxy_post(&proxy_url, |c| c.post_ack(&proxy_url, blinded_utxo.clone()))?;
         debug!(self.logger, "Consignment ACK response: {:?}", ack_res);
 
         // add asset info to transfer if missing
@@ -2398,35 +4436,7 @@ impl Wallet {
         }
 
         // get and update transfer amount
-        let mut amount = 0;
-        let known_transitions = transition_bundle.known_transitions();
-        let transfer_data = self.database.get_transfer_data(&transfer)?;
-        let detailed_transfer = Transfer::from_db_transfer(transfer.clone(), transfer_data);
-        let blinding = detailed_transfer
-            .blinding_secret
-            .expect("incoming transfer should have a blinding secret");
-        let unblinded_utxo = detailed_transfer
-            .unblinded_utxo
-            .ok_or(InternalError::Unexpected)?;
-        let known_concealed = seal::Revealed {
-            method: CloseMethod::OpretFirst,
-            blinding,
-            txid: Some(Txid::from_str(&unblinded_utxo.txid).expect("should be a valid TXID")),
-            vout: unblinded_utxo.vout as u32,
-        }
-        .to_concealed_seal();
-        for transition in known_transitions {
-            let owned_rights = transition.owned_rights();
-            for (_owned_right_type, typed_assignment) in owned_rights.iter() {
-                for assignment in typed_assignment.to_value_assignments() {
-                    if let Assignment::ConfidentialSeal { seal, state } = assignment {
-                        if seal == known_concealed {
-                            amount += state.value;
-                        }
-                    };
-                }
-            }
-        }
+        let amount = self._get_received_amount(&transfer, transition_bundle)?;
         debug!(self.logger, "Received '{}' of contract '{}'", amount, cid);
         let transfer_colorings = self
             .database
@@ -2463,7 +4473,6 @@ impl Wallet {
         debug!(self.logger, "Waiting ACK...");
         let asset_transfers: Vec<DbAssetTransfer> =
             self.database.iter_batch_asset_transfers(batch_transfer)?;
-        let proxy_url = self.online.clone().expect("should be online").proxy_url;
         for asset_transfer in &asset_transfers {
             let transfers: Vec<DbTransfer> = self
                 .database
@@ -2473,32 +4482,33 @@ impl Wallet {
                 .collect();
 
             for transfer in transfers {
-                let ack_res = self.rest_client.clone().get_ack(
-                    &proxy_url,
-                    transfer
-                        .blinded_utxo
-                        .clone()
-                        .expect("transfer should have a blinded UTXO"),
-                )?;
-                debug!(self.logger, "Consignment ACK/NACK response: {:?}", ack_res);
-
-                match ack_res {
-                    AckResponse {
-                        ack: Some(true), ..
-                    } => {
-                        let mut updated_transfer: DbTransferActMod = transfer.clone().into();
-                        updated_transfer.ack = ActiveValue::Set(Some(true));
-                        self.database.update_transfer(&mut updated_transfer)?;
-                    }
-                    AckResponse {
-                        nack: Some(true), ..
-                    } => {
-                        let mut updated_transfer: DbTransferActMod = transfer.clone().into();
-                        updated_transfer.ack = ActiveValue::Set(Some(false));
-                        self.database.update_transfer(&mut updated_transfer)?;
-                    }
-                    _ => (),
-                };
+                let recipient_id = transfer
+                    .proxy_recipient_id()
+                    .expect("transfer should have a blinded UTXO");
+                let proxy_urls = self._transfer_proxy_urls(&transfer)?;
+                let ack_res = self._proxy_failover(&proxy_urls, true, |c, proxy_url| {
+                    let ack_res = c.get_ack(proxy_url, recipient_id.clone())?;
+                    debug!(
+                        self.logger,
+                        "Consignment ACK/NACK response from '{}': {:?}", proxy_url, ack_res
+                    );
+                    Ok(match ack_res {
+                        AckResponse {
+                            ack: Some(true), ..
+                        } => Some(true),
+                        AckResponse {
+                            nack: Some(true), ..
+                        } => Some(false),
+                        _ => None,
+                    })
+                })?;
+
+                if let Some((proxy_url, ack)) = ack_res {
+                    let mut updated_transfer: DbTransferActMod = transfer.clone().into();
+                    updated_transfer.ack = ActiveValue::Set(Some(ack));
+                    updated_transfer.proxy_url = ActiveValue::Set(Some(proxy_url));
+                    self.database.update_transfer(&mut updated_transfer)?;
+                }
             }
         }
 
@@ -2532,6 +4542,96 @@ impl Wallet {
         ))
     }
 
+    /// Look for a consignment replacing the one of the provided incoming transfer, posted by a
+    /// sender that has bumped the fee of the anchoring transaction or retried the transfer
+    /// without the recipients that NACKed it, and switch to it if it assigns the same amount to
+    /// the transfer's UTXO
+    ///
+    /// Replacements are posted under new recipient IDs, the latest valid one is used and ACKed,
+    /// as a retried transfer is only broadcast once its recipients ACK it.
+    fn _check_replaced_anchor(&self, batch_transfer: &DbBatchTransfer) -> Result<(), Error> {
+        let (_, transfer) = self.database.get_incoming_transfer(batch_transfer)?;
+        let blinded_utxo = transfer
+            .blinded_utxo
+            .clone()
+            .expect("transfer should have a blinded UTXO");
+        let mut replacements = vec![];
+        while (replacements.len() as u32) < MAX_CONSIGNMENT_REPLACEMENTS {
+            let recipient_id =
+                consignment_recipient_id(&blinded_utxo, replacements.len() as u32 + 1);
+            match self._fetch_consignment(&transfer, &recipient_id)? {
+                Some((proxy_url, consignment_bytes)) => {
+                    replacements.push((recipient_id, proxy_url, consignment_bytes))
+                }
+                None => break,
+            }
+        }
+
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(blinded_utxo);
+        let consignment_path = transfer_dir.join(CONSIGNMENT_RCV_FILE);
+        let old_consignment =
+            StateTransfer::strict_file_load(&consignment_path).map_err(InternalError::from)?;
+        for (recipient_id, proxy_url, consignment_bytes) in replacements.into_iter().rev() {
+            let consignment_bytes = match consignment_bytes {
+                Some(bytes) => bytes,
+                None => continue,
+            };
+            let consignment: StateTransfer = match strict_deserialize(consignment_bytes.clone()) {
+                Ok(consignment) => consignment,
+                Err(_) => continue,
+            };
+            let anchored_bundles = consignment.anchored_bundles();
+            let (anchor, transition_bundle) = match anchored_bundles.last() {
+                Some(anchored_bundle) => anchored_bundle,
+                None => continue,
+            };
+            let txid = anchor.txid.to_string();
+            if Some(txid.clone()) == batch_transfer.txid {
+                return Ok(());
+            }
+            if consignment.contract_id() != old_consignment.contract_id() {
+                continue;
+            }
+
+            self._electrum_call(true, || Ok(self._electrum_client()?.ping()?))?;
+            let validation_status = Validator::validate(&consignment, &*self._electrum_client()?);
+            if ![Validity::Valid, Validity::ValidExceptEndpoints]
+                .contains(&validation_status.validity())
+            {
+                debug!(self.logger, "Replacement consignment is invalid");
+                continue;
+            }
+            let amount = self._get_received_amount(&transfer, transition_bundle)?;
+            if amount.to_string() != transfer.amount {
+                debug!(
+                    self.logger,
+                    "Replacement consignment changes the received amount"
+                );
+                continue;
+            }
+
+            debug!(self.logger, "Anchor replaced by TX '{}'", txid);
+            self._check_proxy_version(&proxy_url)?;
+            let ack_res =
+                self._proxy_post(&proxy_url, |c| c.post_ack(&proxy_url, recipient_id.clone()))?;
+            debug!(
+                self.logger,
+                "Replacement consignment ACK response: {:?}", ack_res
+            );
+            fs::write(consignment_path, consignment_bytes)?;
+            let mut updated_transfer: DbTransferActMod = transfer.into();
+            updated_transfer.proxy_url = ActiveValue::Set(Some(proxy_url));
+            updated_transfer.recipient_id = ActiveValue::Set(Some(recipient_id));
+            self.database.update_transfer(&mut updated_transfer)?;
+            let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
+            updated_batch_transfer.txid = ActiveValue::Set(Some(txid));
+            self.database
+                .update_batch_transfer(&mut updated_batch_transfer)?;
+            return Ok(());
+        }
+        Ok(())
+    }
+
     fn _wait_confirmations(
         &mut self,
         batch_transfer: &DbBatchTransfer,
@@ -2544,9 +4644,10 @@ impl Wallet {
         let tx_details = match self._get_tx_details(txid.clone()) {
             Ok(v) => Ok(v),
             Err(e) => {
-                if e.to_string()
-                    .contains("No such mempool or blockchain transaction")
-                {
+                if is_unknown_tx_error(&e) {
+                    if batch_transfer.incoming(self.database.clone())? {
+                        self._check_replaced_anchor(batch_transfer)?;
+                    }
                     return Ok(None);
                 } else {
                     Err(e)
@@ -2559,12 +4660,23 @@ impl Wallet {
             tx_details.get("confirmations")
         );
 
-        if tx_details.get("confirmations").is_none()
-            || tx_details["confirmations"]
-                .as_u64()
-                .expect("confirmations to be a valid u64 number")
-                < MIN_CONFIRMATIONS as u64
+        let confirmations = tx_details.get("confirmations").map_or(0, |c| {
+            c.as_u64().expect("confirmations to be a valid u64 number")
+        });
+        let mut min_confirmations = batch_transfer.min_confirmations as u64;
+        // the sender can still replace a transaction signalling RBF, so an incoming transfer
+        // anchored to one needs a confirmation even if none was requested
+        if min_confirmations == 0
+            && batch_transfer.incoming(self.database.clone())?
+            && signals_rbf(&tx_details)?
         {
+            debug!(
+                self.logger,
+                "Transaction signals RBF, waiting for a confirmation"
+            );
+            min_confirmations = 1;
+        }
+        if confirmations < min_confirmations {
             return Ok(None);
         }
 
@@ -2587,7 +4699,13 @@ impl Wallet {
             let tx = self._get_signed_psbt(transfer_dir.clone())?.extract_tx();
             let txid = tx.txid().to_string();
             for (vout, output) in tx.output.iter().enumerate() {
-                if output.value == 0 {
+                // skip the OP_RETURN and outputs paying external BTC recipients
+                if output.value == 0
+                    || !self
+                        .bdk_wallet
+                        .is_mine(&output.script_pubkey)
+                        .map_err(InternalError::from)?
+                {
                     continue;
                 }
                 let mut db_txo: DbTxoActMod = self
@@ -2645,10 +4763,9 @@ impl Wallet {
             } else {
                 None
             };
-            let status = self
-                ._rgb_client()?
-                .consume_transfer(consignment, true, reveal, |_| ())
-                .map_err(InternalError::from)?;
+            let status = self._rgb_call(|c| {
+                c.consume_transfer(consignment.clone(), true, reveal.clone(), |_| ())
+            })?;
             if !matches!(status, ContractValidity::Valid) {
                 return Err(InternalError::Unexpected)?;
             }
@@ -2656,12 +4773,9 @@ impl Wallet {
 
         if asset_transfers.into_iter().any(|t| !t.user_driven) {
             debug!(self.logger, "Processing disclosure...");
-            self._rgb_client()?
-                .process_disclosure(
-                    Txid::from_str(&txid).expect("transaction should have a valid ID"),
-                    |_| (),
-                )
-                .map_err(InternalError::from)?;
+            let disclosure_txid =
+                Txid::from_str(&txid).expect("transaction should have a valid ID");
+            self._rgb_call(|c| c.process_disclosure(disclosure_txid, |_| ()))?;
         }
 
         let mut updated_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
@@ -2682,23 +4796,225 @@ impl Wallet {
         }
     }
 
+    /// Notify the registered [`TransferListener`], if any, that the status of the user-driven
+    /// transfers in the provided batch transfer has changed from `old_status`
+    ///
+    /// This is called after the status change has been saved, so failing to build the
+    /// notifications is logged instead of being returned to the caller.
+    fn _notify_status_change(
+        &self,
+        batch_transfer: &DbBatchTransfer,
+        old_status: Option<TransferStatus>,
+    ) {
+        let listener = match &self.transfer_listener {
+            Some(listener) if old_status != Some(batch_transfer.status) => listener,
+            _ => return,
+        };
+        match self._get_status_changes(batch_transfer, old_status) {
+            Ok(changes) => {
+                for change in changes {
+                    listener.on_transfer_status_change(change);
+                }
+            }
+            Err(e) => error!(
+                self.logger,
+                "Cannot notify status change of batch transfer {}: {}", batch_transfer.idx, e
+            ),
+        }
+    }
+
+    fn _get_status_changes(
+        &self,
+        batch_transfer: &DbBatchTransfer,
+        old_status: Option<TransferStatus>,
+    ) -> Result<Vec<TransferStatusChange>, Error> {
+        let asset_transfers: Vec<DbAssetTransfer> = self
+            .database
+            .iter_batch_asset_transfers(batch_transfer)?
+            .into_iter()
+            .filter(|t| t.user_driven)
+            .collect();
+        let transfers = self.database.iter_transfers()?;
+        let mut changes = vec![];
+        for asset_transfer in asset_transfers {
+            let asset_id = if let Some(aid) = asset_transfer.asset_rgb20_id.clone() {
+                Some(aid)
+            } else {
+                asset_transfer.asset_rgb121_id.clone()
+            };
+            for transfer in transfers
+                .iter()
+                .filter(|t| t.asset_transfer_idx == asset_transfer.idx)
+            {
+                let transfer_data = self.database.get_transfer_data(transfer)?;
+                debug!(
+                    self.logger,
+                    "Transfer {} status changed from {:?} to {:?}",
+                    transfer.idx,
+                    old_status,
+                    transfer_data.status
+                );
+                changes.push(TransferStatusChange {
+                    asset_id: asset_id.clone(),
+                    transfer: Transfer::from_db_transfer(transfer.clone(), transfer_data),
+                    old_status,
+                });
+            }
+        }
+        Ok(changes)
+    }
+
     fn _refresh_transfer(
         &mut self,
         transfer: &DbBatchTransfer,
     ) -> Result<Option<DbBatchTransfer>, Error> {
         debug!(self.logger, "Refreshing transfer: {:?}", transfer);
-        match transfer.status {
+        let updated_transfer = match transfer.status {
             TransferStatus::WaitingCounterparty => self._wait_counterparty(transfer),
             TransferStatus::WaitingConfirmations => self._wait_confirmations(transfer),
             _ => Ok(None),
+        }?;
+        if let Some(updated) = &updated_transfer {
+            self._notify_status_change(updated, Some(transfer.status));
+        }
+        Ok(updated_transfer)
+    }
+
+    /// Preview the effects of a [`send`](Wallet::send) with the provided parameters, without
+    /// sending anything.
+    ///
+    /// Only the `btc_recipients`, `fee_rate` and `input_selection` of the [`SendOptions`] affect
+    /// the preview, so the same options can be passed to the send afterwards.
+    ///
+    /// Inputs are selected as [`send_begin`](Wallet::send_begin) would, among the UTXOs known to
+    /// the wallet since its last sync. If there aren't enough UTXOs without allocations to cover
+    /// the fee and the BTC recipients, or to receive the change, `new_utxos_needed` is set and no
+    /// fee is estimated.
+    ///
+    /// Nothing is written to the database, the RGB stash or the filesystem and no new addresses
+    /// are derived.
+    pub fn preview_send(
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        options: SendOptions,
+    ) -> Result<SendPreview, Error> {
+        info!(
+            self.logger,
+            "Previewing send to: {:?} {:?}...", recipient_map, options.btc_recipients
+        );
+        self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(options.fee_rate)?;
+        let btc_outputs = self._get_btc_outputs(&options.btc_recipients)?;
+
+        let (transfer_info_map, unspents) =
+            self._select_send_inputs(recipient_map, options.input_selection)?;
+        let asset_inputs: HashMap<String, Vec<Outpoint>> = transfer_info_map
+            .iter()
+            .map(|(asset_id, i)| {
+                let inputs = i
+                    .asset_spend
+                    .input_outpoints
+                    .iter()
+                    .map(|o| Outpoint::from(*o))
+                    .collect();
+                (asset_id.clone(), inputs)
+            })
+            .collect();
+        let change_allocations: HashMap<String, u64> = transfer_info_map
+            .iter()
+            .filter(|(_, i)| i.asset_spend.change_amount > 0)
+            .map(|(asset_id, i)| (asset_id.clone(), i.asset_spend.change_amount))
+            .collect();
+
+        // drain to a peeked change address, which is the same type as the one send_begin uses
+        let drain_script = self
+            .bdk_wallet
+            .get_internal_address(AddressIndex::Peek(0))
+            .map_err(InternalError::from)?
+            .address
+            .script_pubkey();
+        let (fee, all_inputs) = match self._prepare_send_psbt(
+            &transfer_info_map,
+            btc_outputs,
+            fee_rate,
+            &unspents,
+            drain_script,
+        ) {
+            Ok((psbt, all_inputs)) => (Some(self._psbt_fee(&psbt)?), all_inputs),
+            Err(Error::InsufficientAllocationSlots) => {
+                let mut rgb_inputs: Vec<OutPoint> = transfer_info_map
+                    .values()
+                    .flat_map(|i| i.asset_spend.input_outpoints.clone())
+                    .collect();
+                rgb_inputs.sort();
+                rgb_inputs.dedup();
+                (None, rgb_inputs)
+            }
+            Err(e) => return Err(e),
+        };
+        let all_inputs: Vec<Outpoint> = all_inputs.into_iter().map(Outpoint::from).collect();
+        let rgb_inputs: Vec<Outpoint> = asset_inputs.values().flatten().cloned().collect();
+        let btc_inputs: Vec<Outpoint> = all_inputs
+            .iter()
+            .filter(|o| !rgb_inputs.contains(o))
+            .cloned()
+            .collect();
+        let change_utxo = self
+            ._get_available_allocations(unspents.clone(), all_inputs.clone(), None)?
+            .first()
+            .map(|u| u.utxo.outpoint());
+
+        // allocations on the spent UTXOs that aren't being sent are moved with blank transitions
+        let mut blank_allocations: HashMap<String, u64> = HashMap::new();
+        for unspent in unspents
+            .iter()
+            .filter(|u| all_inputs.contains(&u.utxo.outpoint()))
+        {
+            for allocation in unspent.rgb_allocations.iter().filter(|a| a.settled) {
+                let asset_id = match &allocation.asset_id {
+                    Some(asset_id) => asset_id,
+                    None => continue,
+                };
+                if let Some(inputs) = asset_inputs.get(asset_id) {
+                    if inputs.contains(&unspent.utxo.outpoint()) {
+                        continue;
+                    }
+                }
+                *blank_allocations.entry(asset_id.clone()).or_insert(0) += allocation.amount;
+            }
         }
+
+        Ok(SendPreview {
+            asset_inputs,
+            btc_inputs,
+            fee,
+            new_utxos_needed: fee.is_none() || change_utxo.is_none(),
+            change_utxo,
+            change_allocations,
+            blank_allocations,
+        })
     }
 
     /// Refresh the status of pending transfers, optionally filtered by [`Asset`] ID.
     ///
     /// Changes to each transfer depend on its status and whether the wallet is on the receiving or
     /// sending side.
-    pub fn refresh(&mut self, online: Online, asset_id: Option<String>) -> Result<(), Error> {
+    ///
+    /// Each transfer is refreshed independently, so an error on one of them doesn't prevent
+    /// refreshing the others. The returned report lists all the refreshed transfers, with their
+    /// status before and after the refresh and the error that occurred, if any.
+    ///
+    /// Outgoing transfers sent with `retry_without_nacks` that fail because some recipients NACKed
+    /// them are prepared again without those recipients, as reported by the
+    /// [`RetriedTransfer`] in their entry. Wallets with private keys also sign and save the new
+    /// transfer, while watch-only ones get its PSBT to sign and pass to
+    /// [`send_end`](Wallet::send_end).
+    pub fn refresh(
+        &mut self,
+        online: Online,
+        asset_id: Option<String>,
+    ) -> Result<Vec<RefreshedTransfer>, Error> {
         if asset_id.is_some() {
             info!(self.logger, "Refreshing asset {:?}...", asset_id);
             self.database
@@ -2706,7 +5022,7 @@ impl Wallet {
         } else {
             info!(self.logger, "Refreshing assets...");
         }
-        self._check_online(online)?;
+        self._check_online(online.clone())?;
 
         let mut batch_transfers: Vec<DbBatchTransfer> = if let Some(aid) = asset_id {
             let batch_transfers_ids: Vec<i64> = self
@@ -2727,11 +5043,57 @@ impl Wallet {
         .filter(|t| t.pending())
         .collect();
 
+        let mut refreshed_transfers = vec![];
         for transfer in batch_transfers.iter_mut() {
-            self._refresh_transfer(transfer)?;
+            let incoming = transfer.incoming(self.database.clone())?;
+            let blinded_utxo = if incoming {
+                let (_, incoming_transfer) = self.database.get_incoming_transfer(transfer)?;
+                incoming_transfer.blinded_utxo
+            } else {
+                None
+            };
+            let (updated_transfer, mut failure) = match self._refresh_transfer(transfer) {
+                Ok(updated_transfer) => (updated_transfer, None),
+                Err(e) => {
+                    warn!(
+                        self.logger,
+                        "Failed to refresh transfer {:?}: {}", transfer, e
+                    );
+                    (None, Some(e.to_string()))
+                }
+            };
+            let updated_transfer = updated_transfer.unwrap_or_else(|| transfer.clone());
+            let mut retried = None;
+            if !incoming && transfer.waiting_counterparty() && updated_transfer.failed() {
+                match self._retry_without_nacks(online.clone(), &updated_transfer) {
+                    Ok(retried_transfer) => retried = retried_transfer,
+                    Err(e) => {
+                        warn!(
+                            self.logger,
+                            "Failed to retry transfer {:?}: {}", updated_transfer, e
+                        );
+                        failure = Some(e.to_string());
+                    }
+                }
+            }
+            refreshed_transfers.push(RefreshedTransfer {
+                txid: updated_transfer.txid,
+                blinded_utxo,
+                old_status: transfer.status,
+                new_status: updated_transfer.status,
+                failure,
+                retried,
+            });
         }
 
-        Ok(())
+        Ok(refreshed_transfers)
+    }
+
+    /// Remove the [`TransferListener`] registered with
+    /// [`set_transfer_listener`](Wallet::set_transfer_listener), if any
+    pub fn remove_transfer_listener(&mut self) {
+        info!(self.logger, "Removing transfer listener...");
+        self.transfer_listener = None;
     }
 
     fn _select_rgb_inputs(
@@ -2742,28 +5104,37 @@ impl Wallet {
         asset_transfers: Option<Vec<DbAssetTransfer>>,
         batch_transfers: Option<Vec<DbBatchTransfer>>,
         colorings: Option<Vec<DbColoring>>,
+        input_selection: Option<InputSelection>,
     ) -> Result<AssetSpend, Error> {
-        debug!(self.logger, "Selecting inputs for asset '{}'...", asset_id);
-        let mut input_allocations: HashMap<DbTxo, u64> = HashMap::new();
-        let mut amount_input_asset: u64 = 0;
+        debug!(
+            self.logger,
+            "Selecting inputs for asset '{}' with {:?}...", asset_id, input_selection
+        );
+        let mut utxos: Vec<DbTxo> = vec![];
+        let mut amounts: Vec<u64> = vec![];
+        let mut merging: Vec<bool> = vec![];
         for unspent in unspents {
-            let mut asset_allocations: Vec<RgbAllocation> = unspent
-                .rgb_allocations
-                .into_iter()
-                .filter(|a| a.asset_id == Some(asset_id.clone()))
-                .collect();
+            let (asset_allocations, other_allocations): (Vec<RgbAllocation>, Vec<RgbAllocation>) =
+                unspent
+                    .rgb_allocations
+                    .into_iter()
+                    .partition(|a| a.asset_id == Some(asset_id.clone()));
             if asset_allocations.is_empty() {
                 continue;
             }
-            asset_allocations.sort_by(|a, b| b.cmp(a));
-            let amount_allocation: u64 = asset_allocations.iter().map(|a| a.amount).sum();
-            input_allocations.insert(unspent.utxo, amount_allocation);
-            amount_input_asset += amount_allocation;
-            if amount_input_asset >= amount_needed {
-                break;
-            }
+            utxos.push(unspent.utxo);
+            amounts.push(asset_allocations.iter().map(|a| a.amount).sum());
+            merging.push(!other_allocations.is_empty());
         }
-        if amount_input_asset < amount_needed {
+        let selected = select_amounts(&amounts, &merging, amount_needed, input_selection);
+        let input_allocations: HashMap<DbTxo, u64> = selected
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| (utxos[i].clone(), amounts[i]))
+            .collect();
+        let amount_input_asset: u64 = input_allocations.values().sum();
+        if selected.is_none() {
             let ass_balance = self.database.get_asset_balance(
                 asset_id.clone(),
                 asset_transfers,
@@ -2795,17 +5166,63 @@ impl Wallet {
         })
     }
 
+    fn _get_btc_outputs(
+        &self,
+        btc_recipients: &[BtcRecipient],
+    ) -> Result<Vec<(Script, u64)>, Error> {
+        let mut btc_outputs = vec![];
+        for recipient in btc_recipients {
+            let address = Address::from_str(&recipient.address)?;
+            if !address.is_valid_for_network(BdkNetwork::from(self.bitcoin_network)) {
+                return Err(Error::InvalidBtcRecipient(format!(
+                    "address {} is not for the wallet's network",
+                    recipient.address
+                )));
+            }
+            let script = address.script_pubkey();
+            let dust = script.dust_value().as_sat();
+            if recipient.amount < dust {
+                return Err(Error::InvalidBtcRecipient(format!(
+                    "amount {} for address {} is below the dust limit of {} sats",
+                    recipient.amount, recipient.address, dust
+                )));
+            }
+            btc_outputs.push((script, recipient.amount));
+        }
+        Ok(btc_outputs)
+    }
+
+    /// Prepare a BDK PSBT spending the provided inputs
+    ///
+    /// If `unspendable` UTXOs are provided, BDK can add any other wallet UTXO as input to cover the
+    /// fee and the BTC outputs, otherwise only the provided inputs are spent.
     fn _prepare_psbt(
         &self,
         input_outpoints: Vec<OutPoint>,
+        btc_outputs: Vec<(Script, u64)>,
+        fee_rate: FeeRate,
+        drain_script: Script,
+        unspendable: Option<Vec<OutPoint>>,
     ) -> Result<PartiallySignedTransaction, Error> {
         let mut builder = self.bdk_wallet.build_tx();
         builder
             .add_utxos(&input_outpoints)
-            .map_err(InternalError::from)?
-            .manually_selected_only()
-            .drain_to(self._get_new_address().script_pubkey())
-            .fee_rate(FeeRate::from_sat_per_vb(1.5));
+            .map_err(InternalError::from)?;
+        match unspendable {
+            Some(unspendable) => {
+                builder.unspendable(unspendable);
+            }
+            None => {
+                builder.manually_selected_only();
+            }
+        }
+        for (script, amount) in btc_outputs {
+            builder.add_recipient(script, amount);
+        }
+        builder
+            .drain_to(drain_script)
+            .fee_rate(fee_rate)
+            .enable_rbf();
         Ok(builder
             .finish()
             .map_err(|e| match e {
@@ -2815,37 +5232,145 @@ impl Wallet {
             .0)
     }
 
+    /// Select the RGB inputs for each asset in the provided recipient map
+    ///
+    /// Returns the transfer info for each asset and the wallet's spendable unspents
+    fn _select_send_inputs(
+        &self,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        input_selection: Option<InputSelection>,
+    ) -> Result<(BTreeMap<String, InfoAssetTransfer>, Vec<LocalUnspent>), Error> {
+        let batch_transfers = self.database.iter_batch_transfers()?;
+        let pending_batch_transfer_ids: Vec<i64> = batch_transfers
+            .clone()
+            .into_iter()
+            .filter(|t| t.pending())
+            .map(|t| t.idx)
+            .collect();
+        let failed_batch_transfer_ids: Vec<i64> = batch_transfers
+            .clone()
+            .into_iter()
+            .filter(|t| t.failed())
+            .map(|t| t.idx)
+            .collect();
+        let utxos = self.database.get_unspent_txos()?;
+        let unspent_txo_ids: Vec<i64> = utxos.iter().map(|u| u.idx).collect();
+        let colorings = self.database.iter_colorings()?;
+        let unspendable_utxo_ids = self.database.get_unspendable_utxo_ids(
+            unspent_txo_ids,
+            pending_batch_transfer_ids,
+            failed_batch_transfer_ids,
+            colorings.clone(),
+        )?;
+        let spendable_utxos: Vec<DbTxo> = utxos
+            .into_iter()
+            .filter(|t| !unspendable_utxo_ids.contains(&t.idx))
+            .collect();
+        let asset_transfers = self.database.iter_asset_transfers()?;
+        let unspents = self.database.get_rgb_allocations(
+            spendable_utxos,
+            false,
+            Some(colorings.clone()),
+            Some(batch_transfers.clone()),
+            Some(asset_transfers.clone()),
+        )?;
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
+        for (asset_id, recipients) in recipient_map {
+            let asset_type = self.database.get_asset_or_fail(asset_id.clone())?;
+            let amount: u64 = recipients.iter().map(|a| a.amount).sum();
+            let asset_spend = self._select_rgb_inputs(
+                asset_id.clone(),
+                amount,
+                unspents.clone(),
+                Some(asset_transfers.clone()),
+                Some(batch_transfers.clone()),
+                Some(colorings.clone()),
+                input_selection,
+            )?;
+            let transfer_info = InfoAssetTransfer {
+                recipients,
+                asset_spend,
+                asset_type,
+            };
+            transfer_info_map.insert(asset_id.clone(), transfer_info);
+        }
+        Ok((transfer_info_map, unspents))
+    }
+
+    /// Prepare the BDK PSBT spending the RGB inputs of the provided transfers, adding UTXOs
+    /// without allocations until they cover the fee and the BTC outputs
+    ///
+    /// Returns the PSBT and all the outpoints it spends
+    fn _prepare_send_psbt(
+        &self,
+        transfer_info_map: &BTreeMap<String, InfoAssetTransfer>,
+        btc_outputs: Vec<(Script, u64)>,
+        fee_rate: FeeRate,
+        unspents: &[LocalUnspent],
+        drain_script: Script,
+    ) -> Result<(PartiallySignedTransaction, Vec<OutPoint>), Error> {
+        let mut all_inputs: Vec<OutPoint> = transfer_info_map
+            .values()
+            .cloned()
+            .map(|i| i.asset_spend.input_outpoints)
+            .collect::<Vec<Vec<OutPoint>>>()
+            .concat();
+        all_inputs.sort();
+        all_inputs.dedup();
+        let psbt = loop {
+            break match self._prepare_psbt(
+                all_inputs.clone(),
+                btc_outputs.clone(),
+                fee_rate,
+                drain_script.clone(),
+                None,
+            ) {
+                Ok(psbt) => psbt,
+                Err(Error::InsufficientBitcoins) => {
+                    let used_txos: Vec<Outpoint> =
+                        all_inputs.clone().into_iter().map(|o| o.into()).collect();
+                    if let Some(a) = self
+                        ._get_available_allocations(unspents.to_vec(), used_txos, Some(0))?
+                        .pop()
+                    {
+                        all_inputs.push(a.utxo.into());
+                        continue;
+                    } else {
+                        return Err(self._detect_btc_unspendable_err(unspents.to_vec()));
+                    }
+                }
+                Err(e) => return Err(e),
+            };
+        };
+        Ok((psbt, all_inputs))
+    }
+
+    /// Add the RGB data for the provided transfers to the PSBT, saving consignments and transfer
+    /// data to `transfer_dir`
+    ///
+    /// Blinded UTXOs of the transfers belonging to the asset transfers in
+    /// `ignored_asset_transfer_ids` can be used again.
     fn _prepare_rgb_psbt(
         &mut self,
         final_psbt: &mut PartiallySignedTransaction,
-        input_outpoints: Vec<OutPoint>,
+        change_utxo: DbTxo,
         transfer_info_map: BTreeMap<String, InfoAssetTransfer>,
         transfer_dir: PathBuf,
         donation: bool,
-        unspents: Vec<LocalUnspent>,
-        batch_transfers: Vec<DbBatchTransfer>,
-        asset_transfers: Vec<DbAssetTransfer>,
+        min_confirmations: Option<u8>,
+        duration_seconds: Option<u32>,
+        retry_without_nacks: bool,
+        replaced_txid: Option<String>,
+        retried_txid: Option<String>,
+        change_script: Script,
+        ignored_asset_transfer_ids: Vec<i64>,
     ) -> Result<(), Error> {
-        let change_utxo = self._get_utxo(
-            input_outpoints.into_iter().map(|t| t.into()).collect(),
-            Some(unspents),
-        )?;
         debug!(
             self.logger,
             "Change outpoint '{}'",
             change_utxo.outpoint().to_string()
         );
 
-        let batch_transfer_ids: Vec<i64> = batch_transfers
-            .into_iter()
-            .filter(|t| t.failed())
-            .map(|t| t.idx)
-            .collect();
-        let asset_transfer_ids: Vec<i64> = asset_transfers
-            .into_iter()
-            .filter(|t| batch_transfer_ids.contains(&t.batch_transfer_idx))
-            .map(|t| t.idx)
-            .collect();
         let existing_transfers = self.database.iter_transfers()?;
         let mut asset_beneficiaries: BTreeMap<String, BTreeMap<SealEndpoint, u64>> = bmap![];
         for (asset_id, transfer_info) in transfer_info_map.clone() {
@@ -2856,10 +5381,9 @@ impl Wallet {
             let input_outpoints_bt: BTreeSet<OutPoint> =
                 asset_spend.input_outpoints.clone().into_iter().collect();
             let rgb_asset_id = ContractId::from_str(&asset_id).map_err(InternalError::from)?;
-            let transfer = self
-                ._rgb_client()?
-                .consign(rgb_asset_id, vec![], input_outpoints_bt.clone(), |_| ())
-                .map_err(InternalError::from)?;
+            let transfer = self._rgb_call(|c| {
+                c.consign(rgb_asset_id, vec![], input_outpoints_bt.clone(), |_| ())
+            })?;
             let asset_transfer_dir = transfer_dir.join(asset_id.clone());
             if asset_transfer_dir.is_dir() {
                 fs::remove_dir_all(asset_transfer_dir.clone())?;
@@ -2872,10 +5396,7 @@ impl Wallet {
                 .map_err(InternalError::from)?;
 
             // RGB node contract embed
-            let contract = self
-                ._rgb_client()?
-                .contract(rgb_asset_id, vec![], |_| {})
-                .map_err(InternalError::from)?;
+            let contract = self._rgb_call(|c| c.contract(rgb_asset_id, vec![], |_| {}))?;
             let mut psbt = <Psbt as BitcoinDeserialize>::deserialize(&serialize(&final_psbt))
                 .map_err(InternalError::from)?;
             psbt.set_rgb_contract(contract)
@@ -2886,11 +5407,15 @@ impl Wallet {
             for recipient in recipients.clone() {
                 if existing_transfers
                     .iter()
-                    .filter(|t| !asset_transfer_ids.contains(&t.asset_transfer_idx))
+                    .filter(|t| !ignored_asset_transfer_ids.contains(&t.asset_transfer_idx))
                     .any(|t| t.blinded_utxo == Some(recipient.blinded_utxo.clone()))
                 {
                     return Err(Error::BlindedUTXOAlreadyUsed)?;
                 }
+                if let Some(key) = &recipient.consignment_key {
+                    encryption::check_key(key)?;
+                }
+                check_transport_endpoints(&recipient.transport_endpoints)?;
                 out_allocations.push(UtxobValue {
                     value: recipient.amount,
                     seal_confidential: ConcealedSeal::from_str(&recipient.blinded_utxo)
@@ -2962,10 +5487,7 @@ impl Wallet {
             .iter()
             .map(|input| input.previous_outpoint)
             .collect();
-        let state_map = self
-            ._rgb_client()?
-            .outpoint_state(outpoints, |_| ())
-            .map_err(InternalError::from)?;
+        let state_map = self._rgb_call(|c| c.outpoint_state(outpoints.clone(), |_| ()))?;
         let new_outpoints: BTreeMap<u16, (OutPoint, CloseMethod)> = bmap! {
             STATE_TYPE_OWNERSHIP_RIGHT => (OutPoint::from(change_utxo.clone()), CloseMethod::OpretFirst)
         };
@@ -2981,14 +5503,9 @@ impl Wallet {
                 if outpoint_map.is_empty() {
                     continue;
                 }
-                self._rgb_client()?
-                    .contract(cid, vec![], |_| ())
-                    .map_err(InternalError::from)?
+                self._rgb_call(|c| c.contract(cid, vec![], |_| ()))?
             } else {
-                let contract = self
-                    ._rgb_client()?
-                    .contract(cid, vec![], |_| ())
-                    .map_err(InternalError::from)?;
+                let contract = self._rgb_call(|c| c.contract(cid, vec![], |_| ()))?;
                 psbt.set_rgb_contract(contract.clone())
                     .map_err(InternalError::from)?;
                 contract
@@ -3069,10 +5586,8 @@ impl Wallet {
             fs::write(info_file, serialized_info)?;
         }
 
-        let transfer_consignment = self
-            ._rgb_client()?
-            .finalize_transfers(transfers, psbt.clone(), |_| ())
-            .map_err(InternalError::from)?;
+        let transfer_consignment =
+            self._rgb_call(|c| c.finalize_transfers(transfers.clone(), psbt.clone(), |_| ()))?;
 
         for consignment in transfer_consignment.consignments {
             let asset_id = consignment.contract_id().to_string();
@@ -3093,6 +5608,13 @@ impl Wallet {
             change_utxo_idx: change_utxo.idx,
             blank_allocations,
             donation,
+            replaced_txid,
+            min_confirmations,
+            duration_seconds,
+            retry_without_nacks,
+            retried_txid,
+            change_script: Some(change_script),
+            begun_at: Some(now().unix_timestamp()),
         };
         let serialized_info = serde_json::to_string(&info_contents).map_err(InternalError::from)?;
         let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
@@ -3101,47 +5623,384 @@ impl Wallet {
         Ok(())
     }
 
-    fn _post_transfer_data(
+    /// Return the directory where the data of a transfer to the provided recipients is prepared,
+    /// before its TXID is known
+    fn _get_transfer_begin_dir<'a>(
+        &self,
+        recipients: impl Iterator<Item = &'a Vec<Recipient>>,
+    ) -> PathBuf {
+        let mut blinded_utxos: Vec<String> = recipients
+            .flat_map(|r| r.iter().map(|r| r.blinded_utxo.clone()))
+            .collect();
+        blinded_utxos.sort();
+        let mut hasher = DefaultHasher::new();
+        blinded_utxos.hash(&mut hasher);
+        self.wallet_dir
+            .join(TRANSFER_DIR)
+            .join(hasher.finish().to_string())
+    }
+
+    /// Post the consignment in the provided asset transfer directory to each of the `recipients`,
+    /// along with the media of the asset in the provided `asset_dir`, if any
+    ///
+    /// Recipients that provided a consignment key get both the consignment and the media
+    /// encrypted with it.
+    ///
+    /// A `replacement` consignment is posted under the first ID following the blinded UTXO of the
+    /// recipient that has no consignment yet. Returns where each recipient's consignment has
+    /// been posted, by blinded UTXO.
+    fn _post_transfer_data(
+        &self,
+        recipients: Vec<Recipient>,
+        asset_transfer_dir: PathBuf,
+        asset_dir: Option<PathBuf>,
+        replacement: bool,
+    ) -> Result<HashMap<String, InfoPostedConsignment>, Error> {
+        let mut attachments = vec![];
+        if let Some(ass_dir) = &asset_dir {
+            for fp in fs::read_dir(ass_dir)? {
+                let fpath = fp?.path();
+                let file_path = fpath.join(MEDIA_FNAME);
+                let file_bytes = std::fs::read(file_path.clone())?;
+                let file_hash: sha256::Hash = Sha256Hash::hash(&file_bytes[..]);
+                let attachment_id = AttachmentId::commit(&file_hash).to_string();
+                attachments.push((attachment_id, file_path))
+            }
+        }
+
+        let consignment_path = asset_transfer_dir.join(CONSIGNMENT_FILE);
+        let own_proxy_urls = self._proxy_urls()?;
+        let mut posted_consignments = HashMap::new();
+        let mut cleartext_media_proxy_urls = HashSet::new();
+        let mut media_posts = vec![];
+        for recipient in recipients {
+            let proxy_urls = if recipient.transport_endpoints.is_empty() {
+                &own_proxy_urls
+            } else {
+                &recipient.transport_endpoints
+            };
+            let recipient_consignment_path = if let Some(key) = &recipient.consignment_key {
+                // encrypt only once, so posting again after an interruption sends the same data
+                let encrypted_path = asset_transfer_dir.join(format!(
+                    "{}_{}",
+                    CONSIGNMENT_ENC_FILE, recipient.blinded_utxo
+                ));
+                if !encrypted_path.exists() {
+                    let consignment_bytes = fs::read(&consignment_path)?;
+                    fs::write(
+                        &encrypted_path,
+                        encryption::encrypt(key, &consignment_bytes)?,
+                    )?;
+                }
+                encrypted_path
+            } else {
+                consignment_path.clone()
+            };
+            let recipient_id = if replacement {
+                self._get_replacement_recipient_id(
+                    proxy_urls,
+                    &recipient.blinded_utxo,
+                    &recipient_consignment_path,
+                )?
+            } else {
+                recipient.blinded_utxo.clone()
+            };
+            let consignment_res = self._proxy_failover(proxy_urls, false, |c, proxy_url| {
+                self._check_proxy_version(proxy_url)?;
+                let consignment_res = c.post_consignment(
+                    proxy_url,
+                    recipient_id.clone(),
+                    recipient_consignment_path.clone(),
+                )?;
+                debug!(
+                    self.logger,
+                    "Consignment POST response from '{}': {:?}", proxy_url, consignment_res
+                );
+                Ok(Some(consignment_res))
+            })?;
+            let (proxy_url, consignment_res) =
+                consignment_res.expect("posting should fail or return a result");
+            if !consignment_res.success {
+                return Err(Error::RefusedConsignment(recipient_id));
+            }
+            if let Some(key) = &recipient.consignment_key {
+                for (attachment_id, file_path) in &attachments {
+                    let encrypted_path = asset_transfer_dir.join(format!(
+                        "{}_{}_{}",
+                        MEDIA_ENC_FILE, attachment_id, recipient.blinded_utxo
+                    ));
+                    if !encrypted_path.exists() {
+                        let file_bytes = fs::read(file_path)?;
+                        fs::write(&encrypted_path, encryption::encrypt(key, &file_bytes)?)?;
+                    }
+                    media_posts.push((
+                        proxy_url.clone(),
+                        encrypted_media_id(attachment_id, &recipient.blinded_utxo),
+                        encrypted_path,
+                    ));
+                }
+            } else {
+                cleartext_media_proxy_urls.insert(proxy_url.clone());
+            }
+            posted_consignments.insert(
+                recipient.blinded_utxo,
+                InfoPostedConsignment {
+                    proxy_url,
+                    recipient_id,
+                },
+            );
+        }
+
+        for proxy_url in &cleartext_media_proxy_urls {
+            for (attachment_id, file_path) in &attachments {
+                media_posts.push((proxy_url.clone(), attachment_id.clone(), file_path.clone()));
+            }
+        }
+        for (proxy_url, media_id, media_path) in media_posts {
+            let media_res = self._proxy_post(&proxy_url, |c| {
+                c.post_media(&proxy_url, media_id.clone(), media_path.clone())
+            })?;
+            debug!(self.logger, "Attachment POST response: {:?}", media_res);
+        }
+
+        Ok(posted_consignments)
+    }
+
+    /// Return the ID to post a consignment replacing the ones already posted for the provided
+    /// blinded UTXO under
+    ///
+    /// This is the first replacement ID without a consignment, or the one already holding the
+    /// provided consignment, if posting it has been interrupted after it reached the proxy server.
+    fn _get_replacement_recipient_id(
+        &self,
+        proxy_urls: &[String],
+        blinded_utxo: &str,
+        consignment_path: &Path,
+    ) -> Result<String, Error> {
+        let consignment = base64::encode(fs::read(consignment_path)?);
+        let mut replacement = 1;
+        loop {
+            if replacement > MAX_CONSIGNMENT_REPLACEMENTS {
+                return Err(Error::MaxConsignmentReplacements(blinded_utxo.to_string()));
+            }
+            let recipient_id = consignment_recipient_id(blinded_utxo, replacement);
+            let consignment_res = self._proxy_failover(proxy_urls, true, |c, proxy_url| {
+                Ok(c.get_consignment(proxy_url, recipient_id.clone())?
+                    .consignment)
+            })?;
+            match consignment_res {
+                Some((_, posted)) if posted != consignment => replacement += 1,
+                _ => return Ok(recipient_id),
+            }
+        }
+    }
+
+    fn _get_transfer_info_map(
+        &self,
+        transfer_dir: &Path,
+    ) -> Result<BTreeMap<String, InfoAssetTransfer>, Error> {
+        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
+        for ass_transf_dir in fs::read_dir(transfer_dir)? {
+            let asset_transfer_dir = ass_transf_dir?.path();
+            if !asset_transfer_dir.is_dir() {
+                continue;
+            }
+            let info_file = asset_transfer_dir.join(TRANSFER_DATA_FILE);
+            let serialized_info = fs::read_to_string(info_file)?;
+            let info_contents: InfoAssetTransfer =
+                serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+            let asset_id: String = asset_transfer_dir
+                .file_name()
+                .expect("valid directory name")
+                .to_str()
+                .expect("should be possible to convert path to a string")
+                .to_string();
+            transfer_info_map.insert(asset_id, info_contents);
+        }
+        Ok(transfer_info_map)
+    }
+
+    fn _post_send_data(
+        &self,
+        transfer_dir: &Path,
+        transfer_info_map: &BTreeMap<String, InfoAssetTransfer>,
+        replacement: bool,
+    ) -> Result<HashMap<String, InfoPostedConsignment>, Error> {
+        let mut posted_consignments = HashMap::new();
+        for (asset_id, info_contents) in transfer_info_map {
+            let asset_dir = if info_contents.asset_type == AssetType::Rgb121 {
+                let ass_dir = self.wallet_dir.join(ASSETS_DIR).join(asset_id);
+                if ass_dir.is_dir() {
+                    Some(ass_dir)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+            posted_consignments.extend(self._post_transfer_data(
+                info_contents.recipients.clone(),
+                transfer_dir.join(asset_id),
+                asset_dir,
+                replacement,
+            )?);
+        }
+        Ok(posted_consignments)
+    }
+
+    fn _write_send_journal(
+        &self,
+        transfer_dir: &Path,
+        journal: &InfoSendJournal,
+    ) -> Result<(), Error> {
+        // write to a temporary file first, so the journal is never left partially written
+        let serialized_journal = serde_json::to_string(journal).map_err(InternalError::from)?;
+        let journal_file = transfer_dir.join(SEND_JOURNAL_FILE);
+        let tmp_file = journal_file.with_extension("tmp");
+        fs::write(&tmp_file, serialized_journal)?;
+        fs::rename(tmp_file, journal_file)?;
+        Ok(())
+    }
+
+    /// Take a send interrupted before it was broadcast and saved back to its unsigned state
+    fn _rollback_send(&self, txid: &str, transfer_dir: &Path) -> Result<(), Error> {
+        debug!(self.logger, "Rolling back send '{}'", txid);
+        let psbt_file = transfer_dir.join(SIGNED_PSBT_FILE);
+        if psbt_file.exists() {
+            fs::remove_file(psbt_file)?;
+        }
+        fs::remove_file(transfer_dir.join(SEND_JOURNAL_FILE))?;
+        Ok(())
+    }
+
+    fn _complete_send(
         &self,
-        recipients: Vec<Recipient>,
-        asset_transfer_dir: PathBuf,
-        asset_dir: Option<PathBuf>,
+        txid: String,
+        transfer_dir: &Path,
+        info_contents: InfoBatchTransfer,
+        transfer_info_map: BTreeMap<String, InfoAssetTransfer>,
+        posted_consignments: HashMap<String, InfoPostedConsignment>,
+        status: TransferStatus,
     ) -> Result<(), Error> {
-        let mut attachments = vec![];
-        if let Some(ass_dir) = &asset_dir {
-            for fp in fs::read_dir(ass_dir)? {
-                let fpath = fp?.path();
-                let file_path = fpath.join(MEDIA_FNAME);
-                let file_bytes = std::fs::read(file_path.clone())?;
-                let file_hash: sha256::Hash = Sha256Hash::hash(&file_bytes[..]);
-                let attachment_id = AttachmentId::commit(&file_hash).to_string();
-                attachments.push((attachment_id, file_path))
-            }
-        }
+        let min_confirmations = info_contents
+            .min_confirmations
+            .unwrap_or(self.wallet_data.min_confirmations);
+        let expiration = get_expiration(info_contents.duration_seconds, DURATION_SEND_TRANSFER);
+        self._save_transfers(
+            txid.clone(),
+            transfer_info_map,
+            info_contents.blank_allocations,
+            info_contents.change_utxo_idx,
+            posted_consignments,
+            status,
+            min_confirmations,
+            expiration,
+        )?;
+        fs::remove_file(transfer_dir.join(SEND_JOURNAL_FILE))?;
+        let batch_transfer = self.database.get_batch_transfer_or_fail(txid)?;
+        self._notify_status_change(&batch_transfer, None);
+        Ok(())
+    }
 
-        let consignment_path = asset_transfer_dir.join(CONSIGNMENT_FILE);
-        let proxy_url = self.online.clone().expect("should be online").proxy_url;
-        for recipient in recipients {
-            let consignment_res = self.rest_client.clone().post_consignment(
-                &proxy_url,
-                recipient.blinded_utxo.clone(),
-                consignment_path.clone(),
-            )?;
-            debug!(
-                self.logger,
-                "Consignment POST response: {:?}", consignment_res
-            );
+    /// Prepare again the provided batch transfer, failed because of NACKs, without the recipients
+    /// that NACKed it, if it was sent with `retry_without_nacks`
+    ///
+    /// The new transfer pays the same BTC recipients at the same fee rate and its data is posted
+    /// to the same proxy servers, as a replacement of the previous consignments. Like any other
+    /// transfer, it's broadcast once all its recipients have ACKed it. Wallets with private keys
+    /// also sign and save it, while the PSBT is returned for watch-only wallets.
+    fn _retry_without_nacks(
+        &mut self,
+        online: Online,
+        batch_transfer: &DbBatchTransfer,
+    ) -> Result<Option<RetriedTransfer>, Error> {
+        let txid = batch_transfer
+            .txid
+            .clone()
+            .expect("batch transfer should have a txid");
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(&txid);
+        let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+        let serialized_info = fs::read_to_string(info_file)?;
+        let info_contents: InfoBatchTransfer =
+            serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+        if !info_contents.retry_without_nacks {
+            return Ok(None);
+        }
+        let transfers = self
+            .database
+            .iter_batch_transfer_transfers(batch_transfer)?;
+        let dropped_blinded_utxos: Vec<String> = transfers
+            .iter()
+            .filter(|t| t.ack == Some(false))
+            .filter_map(|t| t.blinded_utxo.clone())
+            .collect();
+        let recipient_map: HashMap<String, Vec<Recipient>> = self
+            ._get_transfer_info_map(&transfer_dir)?
+            .into_iter()
+            .map(|(asset_id, info)| {
+                let recipients: Vec<Recipient> = info
+                    .recipients
+                    .into_iter()
+                    .filter(|r| !dropped_blinded_utxos.contains(&r.blinded_utxo))
+                    .map(|r| use_posted_proxy_url(r, &transfers))
+                    .collect();
+                (asset_id, recipients)
+            })
+            .filter(|(_, recipients)| !recipients.is_empty())
+            .collect();
+        if dropped_blinded_utxos.is_empty() || recipient_map.is_empty() {
+            return Ok(None);
         }
+        info!(
+            self.logger,
+            "Retrying transfer '{}' without {:?}...", txid, dropped_blinded_utxos
+        );
 
-        for attachment in attachments {
-            let media_res =
-                self.rest_client
-                    .clone()
-                    .post_media(&proxy_url, attachment.0, attachment.1)?;
-            debug!(self.logger, "Attachment POST response: {:?}", media_res);
+        // pay the same BTC recipients at the same fee rate
+        let retried_psbt = self._get_signed_psbt(transfer_dir)?;
+        let retried_fee = self._psbt_fee(&retried_psbt)?;
+        let retried_tx = retried_psbt.extract_tx();
+        let fee_rate = retried_fee as f32 / (retried_tx.weight() as f32 / 4.0);
+        let mut btc_outputs = vec![];
+        for output in &retried_tx.output {
+            if output.script_pubkey.is_op_return()
+                || self
+                    .bdk_wallet
+                    .is_mine(&output.script_pubkey)
+                    .map_err(InternalError::from)?
+            {
+                continue;
+            }
+            btc_outputs.push((output.script_pubkey.clone(), output.value));
         }
 
-        Ok(())
+        let mut psbt = self._send_begin(
+            recipient_map,
+            btc_outputs,
+            false,
+            true,
+            FeeRate::from_sat_per_vb(fee_rate),
+            info_contents.min_confirmations,
+            info_contents.duration_seconds,
+            None,
+            Some(txid),
+        )?;
+        let new_txid = psbt.clone().extract_tx().txid().to_string();
+        let unsigned_psbt = if self.watch_only {
+            Some(psbt.to_string())
+        } else {
+            self.bdk_wallet
+                .sign(&mut psbt, SignOptions::default())
+                .map_err(InternalError::from)?;
+            self.send_end(online, psbt.to_string())?;
+            None
+        };
+
+        Ok(Some(RetriedTransfer {
+            txid: new_txid,
+            dropped_blinded_utxos,
+            unsigned_psbt,
+        }))
     }
 
     fn _save_transfers(
@@ -3150,18 +6009,19 @@ impl Wallet {
         transfer_info_map: BTreeMap<String, InfoAssetTransfer>,
         blank_allocations: HashMap<String, u64>,
         change_utxo_idx: i64,
+        posted_consignments: HashMap<String, InfoPostedConsignment>,
         status: TransferStatus,
+        min_confirmations: u8,
+        expiration: Option<i64>,
     ) -> Result<(), Error> {
-        let created_at = now().unix_timestamp();
-        let expiration = Some(created_at + DURATION_SEND_TRANSFER);
-
         let batch_transfer = DbBatchTransferActMod {
             txid: ActiveValue::Set(Some(txid)),
             status: ActiveValue::Set(status),
             expiration: ActiveValue::Set(expiration),
+            min_confirmations: ActiveValue::Set(min_confirmations),
             ..Default::default()
         };
-        let batch_transfer_idx = self.database.set_batch_transfer(batch_transfer)?;
+        let mut asset_transfers = vec![];
 
         for (asset_id, transfer_info) in transfer_info_map {
             let asset_spend = transfer_info.asset_spend;
@@ -3169,7 +6029,6 @@ impl Wallet {
 
             let mut asset_transfer = DbAssetTransferActMod {
                 user_driven: ActiveValue::Set(true),
-                batch_transfer_idx: ActiveValue::Set(batch_transfer_idx),
                 ..Default::default()
             };
             match transfer_info.asset_type {
@@ -3180,44 +6039,45 @@ impl Wallet {
                     asset_transfer.asset_rgb121_id = ActiveValue::Set(Some(asset_id))
                 }
             }
-            let asset_transfer_idx = self.database.set_asset_transfer(asset_transfer)?;
 
+            let mut colorings = vec![];
             for (input_idx, amount) in asset_spend.txo_map.clone().into_iter() {
-                let db_coloring = DbColoringActMod {
+                colorings.push(DbColoringActMod {
                     txo_idx: ActiveValue::Set(input_idx),
-                    asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
                     coloring_type: ActiveValue::Set(ColoringType::Input),
                     amount: ActiveValue::Set(amount.to_string()),
                     ..Default::default()
-                };
-                self.database.set_coloring(db_coloring)?;
+                });
             }
             if asset_spend.change_amount > 0 {
-                let db_coloring = DbColoringActMod {
+                colorings.push(DbColoringActMod {
                     txo_idx: ActiveValue::Set(change_utxo_idx),
-                    asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
                     coloring_type: ActiveValue::Set(ColoringType::Change),
                     amount: ActiveValue::Set(asset_spend.change_amount.to_string()),
                     ..Default::default()
-                };
-                self.database.set_coloring(db_coloring)?;
+                });
             }
 
-            for recipient in recipients.clone() {
-                let transfer = DbTransferActMod {
-                    asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
-                    amount: ActiveValue::Set(recipient.amount.to_string()),
-                    blinded_utxo: ActiveValue::Set(Some(recipient.blinded_utxo.clone())),
-                    ..Default::default()
-                };
-                self.database.set_transfer(transfer)?;
-            }
+            let transfers = recipients
+                .into_iter()
+                .map(|recipient| {
+                    let posted = posted_consignments.get(&recipient.blinded_utxo);
+                    DbTransferActMod {
+                        amount: ActiveValue::Set(recipient.amount.to_string()),
+                        proxy_url: ActiveValue::Set(posted.map(|p| p.proxy_url.clone())),
+                        recipient_id: ActiveValue::Set(posted.map(|p| p.recipient_id.clone())),
+                        blinded_utxo: ActiveValue::Set(Some(recipient.blinded_utxo)),
+                        consignment_key: ActiveValue::Set(recipient.consignment_key),
+                        ..Default::default()
+                    }
+                })
+                .collect();
+            asset_transfers.push((asset_transfer, colorings, transfers));
         }
 
         for (asset_id, amt) in blank_allocations {
             let mut asset_transfer = DbAssetTransferActMod {
                 user_driven: ActiveValue::Set(false),
-                batch_transfer_idx: ActiveValue::Set(batch_transfer_idx),
                 ..Default::default()
             };
             match self.database.get_asset_or_fail(asset_id.clone())? {
@@ -3228,17 +6088,19 @@ impl Wallet {
                     asset_transfer.asset_rgb121_id = ActiveValue::Set(Some(asset_id))
                 }
             }
-            let asset_transfer_idx = self.database.set_asset_transfer(asset_transfer)?;
-            let db_coloring = DbColoringActMod {
+            let coloring = DbColoringActMod {
                 txo_idx: ActiveValue::Set(change_utxo_idx),
-                asset_transfer_idx: ActiveValue::Set(asset_transfer_idx),
                 coloring_type: ActiveValue::Set(ColoringType::Change),
                 amount: ActiveValue::Set(amt.to_string()),
                 ..Default::default()
             };
-            self.database.set_coloring(db_coloring)?;
+            asset_transfers.push((asset_transfer, vec![coloring], vec![]));
         }
 
+        // save everything at once, so an interrupted save leaves no partial transfer behind
+        self.database
+            .set_batch_transfer_with_children(batch_transfer, asset_transfers)?;
+
         Ok(())
     }
 
@@ -3250,11 +6112,15 @@ impl Wallet {
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
-    ) -> Result<String, Error> {
-        info!(self.logger, "Sending to: {:?}...", recipient_map);
+        options: SendOptions,
+    ) -> Result<SendResult, Error> {
+        info!(
+            self.logger,
+            "Sending to: {:?} {:?}...", recipient_map, options.btc_recipients
+        );
         self._check_xprv()?;
 
-        let unsigned_psbt = self.send_begin(online.clone(), recipient_map, donation)?;
+        let unsigned_psbt = self.send_begin(online.clone(), recipient_map, donation, options)?;
 
         let mut psbt =
             PartiallySignedTransaction::from_str(&unsigned_psbt).map_err(InternalError::from)?;
@@ -3270,11 +6136,35 @@ impl Wallet {
     /// The `recipient_map` maps [`Asset`] IDs to a vector of [`Recipient`]s. Each recipient
     /// is specified by a `blinded_utxo` and the `amount` to send.
     ///
+    /// The optional parameters are grouped in the provided [`SendOptions`], whose default is
+    /// described below for each of them.
+    ///
+    /// Each of the `btc_recipients` is paid its `amount` of sats with an output to its `address`
+    /// in the same transaction. Like the fee, these are paid with the bitcoins of the UTXOs
+    /// available to the wallet for RGB allocations.
+    ///
     /// If `donation` is true, the resulting transaction will be broadcast (by
     /// [`send_end`](Wallet::send_end)) as soon as it's ready, without the need for recipients to
     /// acknowledge the transfer.
     /// If `donation` is false, all recipients will need to ack the transfer before the transaction
     /// is broadcast (as part of [`refresh`](Wallet::refresh)).
+    /// If `retry_without_nacks` is also true, a transfer NACKed by some of the recipients is
+    /// prepared again without them by [`refresh`](Wallet::refresh), instead of only failing. The
+    /// remaining recipients need to ack the new transfer before its transaction is broadcast.
+    ///
+    /// The transaction pays the provided `fee_rate`, in sat/vB. If no `fee_rate` is provided, the
+    /// rate estimated by [`estimate_fee`](Wallet::estimate_fee) for confirmation within 6 blocks is
+    /// used instead.
+    ///
+    /// The transfer is settled once its transaction has `min_confirmations` confirmations, or the
+    /// wallet's [`min_confirmations`](WalletData::min_confirmations) if none is provided.
+    ///
+    /// If recipients don't ACK the transfer within `duration_seconds` (1 hour by default) from
+    /// when it's saved by [`send_end`](Wallet::send_end), it expires and is set to failed. A
+    /// duration of 0 means the transfer never expires.
+    ///
+    /// The UTXOs to spend for each asset are chosen according to the provided
+    /// [`InputSelection`] strategy, or in database order if none is provided.
     ///
     /// This is the first half of the partial version, requiring no private keys.
     /// Signing of the returned PSBT needs to be carried out separately. The signed PSBT then needs
@@ -3286,125 +6176,106 @@ impl Wallet {
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
+        options: SendOptions,
     ) -> Result<String, Error> {
-        info!(self.logger, "Sending (begin) to: {:?}...", recipient_map);
+        info!(
+            self.logger,
+            "Sending (begin) to: {:?} {:?}...", recipient_map, options.btc_recipients
+        );
         self._check_online(online)?;
+        let fee_rate = self._get_fee_rate(options.fee_rate)?;
+        let btc_outputs = self._get_btc_outputs(&options.btc_recipients)?;
+
+        let psbt = self._send_begin(
+            recipient_map,
+            btc_outputs,
+            donation,
+            options.retry_without_nacks,
+            fee_rate,
+            options.min_confirmations,
+            options.duration_seconds,
+            options.input_selection,
+            None,
+        )?;
+
+        Ok(psbt.to_string())
+    }
+
+    fn _send_begin(
+        &mut self,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        btc_outputs: Vec<(Script, u64)>,
+        donation: bool,
+        retry_without_nacks: bool,
+        fee_rate: FeeRate,
+        min_confirmations: Option<u8>,
+        duration_seconds: Option<u32>,
+        input_selection: Option<InputSelection>,
+        retried_txid: Option<String>,
+    ) -> Result<PartiallySignedTransaction, Error> {
         self._handle_expired_transfers()?;
 
-        let mut blinded_utxos: Vec<String> = recipient_map
-            .values()
-            .map(|r| r.iter().map(|r| r.blinded_utxo.clone()).collect())
-            .collect();
-        blinded_utxos.sort();
-        let mut hasher = DefaultHasher::new();
-        blinded_utxos.hash(&mut hasher);
-        let transfer_dir = self
-            .wallet_dir
-            .join(TRANSFER_DIR)
-            .join(hasher.finish().to_string());
+        let transfer_dir = self._get_transfer_begin_dir(recipient_map.values());
 
         // input selection
-        let batch_transfers = self.database.iter_batch_transfers()?;
-        let pending_batch_transfer_ids: Vec<i64> = batch_transfers
-            .clone()
-            .into_iter()
-            .filter(|t| t.pending())
-            .map(|t| t.idx)
-            .collect();
-        let failed_batch_transfer_ids: Vec<i64> = batch_transfers
-            .clone()
+        let (transfer_info_map, unspents) =
+            self._select_send_inputs(recipient_map, input_selection)?;
+
+        // prepare BDK PSBT
+        let change_script = self._get_change_script()?;
+        let (mut psbt, all_inputs) = self._prepare_send_psbt(
+            &transfer_info_map,
+            btc_outputs,
+            fee_rate,
+            &unspents,
+            change_script.clone(),
+        )?;
+
+        // prepare RGB PSBT
+        let change_utxo = self._get_utxo(
+            all_inputs.into_iter().map(|t| t.into()).collect(),
+            Some(unspents),
+        )?;
+        let failed_batch_transfer_ids: Vec<i64> = self
+            .database
+            .iter_batch_transfers()?
             .into_iter()
             .filter(|t| t.failed())
             .map(|t| t.idx)
             .collect();
-        let utxos = self.database.get_unspent_txos()?;
-        let unspent_txo_ids: Vec<i64> = utxos.iter().map(|u| u.idx).collect();
-        let colorings = self.database.iter_colorings()?;
-        let unspendable_utxo_ids = self.database.get_unspendable_utxo_ids(
-            unspent_txo_ids,
-            pending_batch_transfer_ids,
-            failed_batch_transfer_ids,
-            colorings.clone(),
-        )?;
-        let spendable_utxos: Vec<DbTxo> = utxos
+        let failed_asset_transfer_ids: Vec<i64> = self
+            .database
+            .iter_asset_transfers()?
             .into_iter()
-            .filter(|t| !unspendable_utxo_ids.contains(&t.idx))
+            .filter(|t| failed_batch_transfer_ids.contains(&t.batch_transfer_idx))
+            .map(|t| t.idx)
             .collect();
-        let asset_transfers = self.database.iter_asset_transfers()?;
-        let unspents = self.database.get_rgb_allocations(
-            spendable_utxos,
-            false,
-            Some(colorings.clone()),
-            Some(batch_transfers.clone()),
-            Some(asset_transfers.clone()),
-        )?;
-        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
-        for (asset_id, recipients) in recipient_map {
-            let asset_type = self.database.get_asset_or_fail(asset_id.clone())?;
-            let amount: u64 = recipients.iter().map(|a| a.amount).sum();
-            let asset_spend = self._select_rgb_inputs(
-                asset_id.clone(),
-                amount,
-                unspents.clone(),
-                Some(asset_transfers.clone()),
-                Some(batch_transfers.clone()),
-                Some(colorings.clone()),
-            )?;
-            let transfer_info = InfoAssetTransfer {
-                recipients,
-                asset_spend,
-                asset_type,
-            };
-            transfer_info_map.insert(asset_id.clone(), transfer_info);
-        }
-
-        // prepare BDK PSBT
-        let mut all_inputs: Vec<OutPoint> = transfer_info_map
-            .values()
-            .cloned()
-            .map(|i| i.asset_spend.input_outpoints)
-            .collect::<Vec<Vec<OutPoint>>>()
-            .concat();
-        all_inputs.sort();
-        all_inputs.dedup();
-        let mut psbt = loop {
-            break match self._prepare_psbt(all_inputs.clone()) {
-                Ok(psbt) => psbt,
-                Err(Error::InsufficientBitcoins) => {
-                    let used_txos: Vec<Outpoint> =
-                        all_inputs.clone().into_iter().map(|o| o.into()).collect();
-                    if let Some(a) = self
-                        ._get_available_allocations(unspents.clone(), used_txos.clone(), Some(0))?
-                        .pop()
-                    {
-                        all_inputs.push(a.utxo.into());
-                        continue;
-                    } else {
-                        return Err(self._detect_btc_unspendable_err(unspents));
-                    }
-                }
-                Err(e) => return Err(e),
-            };
-        };
-
-        // prepare RGB PSBT
-        self._prepare_rgb_psbt(
+        if let Err(e) = self._prepare_rgb_psbt(
             &mut psbt,
-            all_inputs,
+            change_utxo,
             transfer_info_map.clone(),
             transfer_dir.clone(),
             donation,
-            unspents,
-            batch_transfers,
-            asset_transfers,
-        )?;
+            min_confirmations,
+            duration_seconds,
+            retry_without_nacks,
+            None,
+            retried_txid,
+            change_script,
+            failed_asset_transfer_ids,
+        ) {
+            if transfer_dir.is_dir() {
+                fs::remove_dir_all(&transfer_dir)?;
+            }
+            return Err(e);
+        }
 
         // rename transfer directory
         let txid = psbt.clone().extract_tx().txid().to_string();
         let new_transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid);
         fs::rename(transfer_dir, new_transfer_dir)?;
 
-        Ok(psbt.to_string())
+        Ok(psbt)
     }
 
     /// Complete the send operation by saving the PSBT to disk, POSTing consignments to the proxy
@@ -3413,16 +6284,27 @@ impl Wallet {
     /// This is the second half of the partial version. The provided PSBT, prepared with the
     /// `send_begin` function, needs to have already been signed.
     ///
-    /// Returns the txid of the signed PSBT that's been saved and optionally broadcast
-    pub fn send_end(&self, online: Online, signed_psbt: String) -> Result<String, Error> {
+    /// If the send is interrupted, it is completed or taken back to its unsigned state the next
+    /// time the wallet goes online. It's also taken back to its unsigned state if posting
+    /// consignments fails.
+    ///
+    /// Returns the txid of the signed PSBT that's been saved and optionally broadcast and the fee
+    /// it pays
+    pub fn send_end(&self, online: Online, signed_psbt: String) -> Result<SendResult, Error> {
         info!(self.logger, "Sending (end)...");
         self._check_online(online)?;
 
-        // save signed PSBT
+        // journal the send, so it can be resumed if interrupted, and save signed PSBT
         let psbt =
             PartiallySignedTransaction::from_str(&signed_psbt).map_err(Error::InvalidPsbt)?;
+        let fee = self._psbt_fee(&psbt)?;
         let txid = psbt.clone().extract_tx().txid().to_string();
         let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid.clone());
+        let mut journal = InfoSendJournal {
+            step: SendJournalStep::Posting,
+            posted_consignments: HashMap::new(),
+        };
+        self._write_send_journal(&transfer_dir, &journal)?;
         let psbt_out = transfer_dir.join(SIGNED_PSBT_FILE);
         fs::write(psbt_out, psbt.to_string())?;
 
@@ -3431,57 +6313,47 @@ impl Wallet {
         let serialized_info = fs::read_to_string(info_file)?;
         let info_contents: InfoBatchTransfer =
             serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
-        let blank_allocations = info_contents.blank_allocations;
-        let change_utxo_idx = info_contents.change_utxo_idx;
-        let donation = info_contents.donation;
-        let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
-        for ass_transf_dir in fs::read_dir(transfer_dir)? {
-            let asset_transfer_dir = ass_transf_dir?.path();
-            if !asset_transfer_dir.is_dir() {
-                continue;
-            }
-            let info_file = asset_transfer_dir.join(TRANSFER_DATA_FILE);
-            let serialized_info = fs::read_to_string(info_file)?;
-            let info_contents: InfoAssetTransfer =
-                serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
-            let asset_id: String = asset_transfer_dir
-                .file_name()
-                .expect("valid directory name")
-                .to_str()
-                .expect("should be possible to convert path to a string")
-                .to_string();
-            transfer_info_map.insert(asset_id.clone(), info_contents.clone());
-
-            // post consignment(s) and optional media
-            let asset_dir = if info_contents.asset_type == AssetType::Rgb121 {
-                let ass_dir = self.wallet_dir.join(ASSETS_DIR).join(asset_id);
-                if ass_dir.is_dir() {
-                    Some(ass_dir)
-                } else {
-                    None
+        let transfer_info_map = self._get_transfer_info_map(&transfer_dir)?;
+
+        // post consignment(s) and optional media, going back to the unsigned send on failure
+        // (the consignments of a retried transfer replace the ones posted for it)
+        let replacement = info_contents.retried_txid.is_some();
+        journal.posted_consignments =
+            match self._post_send_data(&transfer_dir, &transfer_info_map, replacement) {
+                Ok(posted_consignments) => posted_consignments,
+                Err(e) => {
+                    self._rollback_send(&txid, &transfer_dir)?;
+                    return Err(e);
                 }
-            } else {
-                None
             };
-            self._post_transfer_data(info_contents.recipients, asset_transfer_dir, asset_dir)?;
-        }
+        journal.step = SendJournalStep::Saving;
+        self._write_send_journal(&transfer_dir, &journal)?;
 
         // broadcast PSBT if donation and finally save transfer to DB
-        let status = if donation {
+        let status = if info_contents.donation {
             self._broadcast_psbt(psbt)?;
             TransferStatus::WaitingConfirmations
         } else {
             TransferStatus::WaitingCounterparty
         };
-        self._save_transfers(
+        self._complete_send(
             txid.clone(),
+            &transfer_dir,
+            info_contents,
             transfer_info_map,
-            blank_allocations,
-            change_utxo_idx,
+            journal.posted_consignments,
             status,
         )?;
 
-        Ok(txid)
+        Ok(SendResult { txid, fee })
+    }
+
+    /// Register a [`TransferListener`] to be notified of [`Transfer`] status changes
+    ///
+    /// Any previously registered listener is replaced.
+    pub fn set_transfer_listener(&mut self, listener: Box<dyn TransferListener>) {
+        info!(self.logger, "Setting transfer listener...");
+        self.transfer_listener = Some(listener);
     }
 }
 
@@ -0,0 +1,204 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // prepare a send and return its TXID and the script its BTC change is drained to
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let begin_send = |wallet: &mut Wallet| {
+        let psbt = wallet
+            .send_begin(online.clone(), recipient_map.clone(), false, send_options())
+            .unwrap();
+        let tx = PartiallySignedTransaction::from_str(&psbt)
+            .unwrap()
+            .extract_tx();
+        let change_script = tx
+            .output
+            .iter()
+            .find(|o| wallet.bdk_wallet.is_mine(&o.script_pubkey).unwrap())
+            .unwrap()
+            .script_pubkey
+            .clone();
+        (tx.txid().to_string(), change_script)
+    };
+    let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR);
+    let num_batch_transfers = wallet.database.iter_batch_transfers().unwrap().len();
+
+    // abort a prepared send
+    let (txid, change_script) = begin_send(&mut wallet);
+    assert!(transfer_dir.join(&txid).is_dir());
+    wallet.abort_send(txid.clone()).unwrap();
+    assert!(!transfer_dir.join(&txid).exists());
+    assert_eq!(
+        wallet.database.iter_batch_transfers().unwrap().len(),
+        num_batch_transfers
+    );
+
+    // aborting twice fails
+    let result = wallet.abort_send(txid);
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+
+    // preparing the send again doesn't consume a new change address
+    let (txid_1, change_script_1) = begin_send(&mut wallet);
+    assert_eq!(change_script_1, change_script);
+
+    // a prepared send reserves its change address, so a concurrent one gets a new one
+    let (txid_2, change_script_2) = begin_send(&mut wallet);
+    assert_ne!(change_script_2, change_script_1);
+
+    // aborted sends release their change address
+    wallet.abort_send(txid_1).unwrap();
+    wallet.abort_send(txid_2).unwrap();
+    let (txid, new_change_script) = begin_send(&mut wallet);
+    assert_eq!(new_change_script, change_script_2);
+    wallet.abort_send(txid).unwrap();
+}
+
+#[test]
+fn fail_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // invalid or unknown TXID
+    let result = wallet.abort_send(s!("../invalid"));
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+    let result = wallet.abort_send(s!(
+        "0000000000000000000000000000000000000000000000000000000000000000"
+    ));
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+
+    // signed sends cannot be aborted
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id,
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    let result = wallet.abort_send(txid.clone());
+    assert!(matches!(result, Err(Error::CannotAbortSend)));
+    assert!(wallet
+        .get_wallet_dir()
+        .join(TRANSFER_DIR)
+        .join(txid)
+        .is_dir());
+}
+
+#[test]
+fn stale_begun_sends_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // prepare a send and return its TXID
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id,
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let begin_send = |wallet: &mut Wallet| {
+        let psbt = wallet
+            .send_begin(online.clone(), recipient_map.clone(), false, send_options())
+            .unwrap();
+        PartiallySignedTransaction::from_str(&psbt)
+            .unwrap()
+            .extract_tx()
+            .txid()
+            .to_string()
+    };
+    let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR);
+    // move the time a send has been prepared at two days back
+    let backdate = |txid: &str| {
+        let info_file = transfer_dir.join(txid).join(TRANSFER_DATA_FILE);
+        let mut info: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&info_file).unwrap()).unwrap();
+        let begun_at = info["begun_at"].as_i64().unwrap();
+        info["begun_at"] = serde_json::json!(begun_at - 2 * 86400);
+        fs::write(info_file, info.to_string()).unwrap();
+    };
+
+    // with a duration of 0, prepared sends are kept however old
+    wallet.wallet_data.begun_send_duration = Some(0);
+    let txid_old = begin_send(&mut wallet);
+    backdate(&txid_old);
+    let txid_new = begin_send(&mut wallet);
+    assert!(transfer_dir.join(&txid_old).is_dir());
+    assert!(transfer_dir.join(&txid_new).is_dir());
+
+    // by default, the ones older than a day are deleted
+    wallet.wallet_data.begun_send_duration = None;
+    let txid_last = begin_send(&mut wallet);
+    assert!(!transfer_dir.join(&txid_old).exists());
+    assert!(transfer_dir.join(&txid_new).is_dir());
+    assert!(transfer_dir.join(&txid_last).is_dir());
+}
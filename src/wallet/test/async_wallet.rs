@@ -0,0 +1,125 @@
+use futures::executor::block_on;
+use futures::future::join_all;
+use std::sync::{Arc, Mutex};
+
+use super::*;
+
+fn get_test_wallet_data() -> WalletData {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let keys = generate_keys(bitcoin_network);
+    WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network,
+        database_type: DatabaseType::Sqlite,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        min_confirmations: MIN_CONFIRMATIONS,
+        begun_send_duration: None,
+    }
+}
+
+#[test]
+fn success() {
+    initialize();
+
+    let wallet_data = get_test_wallet_data();
+    let wallet = block_on(AsyncWallet::new(wallet_data.clone())).unwrap();
+    assert_eq!(
+        block_on(wallet.get_wallet_data()).unwrap().pubkey,
+        wallet_data.pubkey
+    );
+
+    // go online and issue an asset
+    let online = block_on(wallet.go_online(
+        true,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    ))
+    .unwrap();
+    fund_wallet(block_on(wallet.get_address()).unwrap());
+    mine();
+    let num_utxos = block_on(wallet.create_utxos(online.clone(), false, None, None, FEE_RATE))
+        .unwrap()
+        .num_utxos;
+    assert_eq!(num_utxos, UTXO_NUM);
+    let asset = block_on(wallet.issue_asset_rgb20(
+        online,
+        TICKER.to_string(),
+        NAME.to_string(),
+        PRECISION,
+        vec![AMOUNT],
+    ))
+    .unwrap();
+    let balance = block_on(wallet.get_asset_balance(asset.asset_id.clone())).unwrap();
+    assert_eq!(balance.settled, AMOUNT);
+
+    // concurrent calls are all served
+    let wallet = Arc::new(wallet);
+    let futures = (0..5).map(|_| {
+        let wallet = wallet.clone();
+        let asset_id = asset.asset_id.clone();
+        async move { wallet.list_transfers(asset_id).await }
+    });
+    for transfers in block_on(join_all(futures)) {
+        assert_eq!(transfers.unwrap().len(), 1);
+    }
+
+    // operations run in the order they're requested
+    let order = Arc::new(Mutex::new(vec![]));
+    let futures = (0..5).map(|i| {
+        let order = order.clone();
+        wallet.run(move |_| order.lock().unwrap().push(i))
+    });
+    for result in block_on(join_all(futures)) {
+        result.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+
+    // any wallet method can be called via run
+    let wallet_dir = block_on(wallet.run(|w| w.get_wallet_dir())).unwrap();
+    assert_eq!(wallet_dir, block_on(wallet.get_wallet_dir()).unwrap());
+}
+
+#[test]
+fn from_wallet() {
+    initialize();
+
+    let wallet = get_test_wallet(true);
+    let address = wallet.get_address();
+    let wallet = AsyncWallet::from(wallet);
+    let unspents = block_on(wallet.list_unspents(false)).unwrap();
+    assert!(unspents.is_empty());
+    assert!(!block_on(wallet.get_address()).unwrap().is_empty());
+    assert_ne!(block_on(wallet.get_address()).unwrap(), address);
+}
+
+#[test]
+fn fail() {
+    initialize();
+
+    // wallet creation errors are returned
+    let mut wallet_data = get_test_wallet_data();
+    wallet_data.pubkey = s!("invalid");
+    let result = block_on(AsyncWallet::new(wallet_data));
+    assert!(matches!(result, Err(Error::InvalidPubkey(_))));
+
+    // wallet errors are returned
+    let wallet = block_on(AsyncWallet::new(get_test_wallet_data())).unwrap();
+    let result = block_on(wallet.get_asset_balance(s!("rgb1inexistent")));
+    assert!(matches!(result, Err(Error::AssetNotFound(_))));
+
+    // calls fail after the worker thread stopped
+    let result = block_on(wallet.run(|_| panic!("worker failure")));
+    assert!(matches!(
+        result,
+        Err(Error::Internal(InternalError::Unexpected))
+    ));
+    let result = block_on(wallet.get_address());
+    assert!(matches!(
+        result,
+        Err(Error::Internal(InternalError::Unexpected))
+    ));
+}
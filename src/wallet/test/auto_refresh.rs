@@ -0,0 +1,187 @@
+use futures::executor::block_on;
+use std::sync::Mutex;
+
+use super::*;
+
+const INTERVAL_SECONDS: u32 = 1;
+
+/// Wait until the provided condition is true, failing after a timeout
+fn wait_for(condition: impl Fn() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(
+            start.elapsed() < Duration::from_secs(30),
+            "timeout waiting for auto-refresh"
+        );
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue and send
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id,
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+
+    // transfers progress without calling refresh
+    let wallet = Arc::new(Mutex::new(wallet));
+    let rcv_wallet = Arc::new(Mutex::new(rcv_wallet));
+    let mut auto_refresh = AutoRefresh::start(wallet.clone(), online, INTERVAL_SECONDS).unwrap();
+    let mut rcv_auto_refresh =
+        AutoRefresh::start(rcv_wallet.clone(), rcv_online, INTERVAL_SECONDS).unwrap();
+    assert!(auto_refresh.is_running());
+    wait_for(|| {
+        check_test_transfer_status_recipient(
+            &rcv_wallet.lock().unwrap(),
+            &blind_data.blinded_utxo,
+            TransferStatus::WaitingConfirmations,
+        ) && check_test_transfer_status_sender(
+            &wallet.lock().unwrap(),
+            &txid,
+            TransferStatus::WaitingConfirmations,
+        )
+    });
+    electrum.mine(1);
+    wait_for(|| {
+        check_test_transfer_status_recipient(
+            &rcv_wallet.lock().unwrap(),
+            &blind_data.blinded_utxo,
+            TransferStatus::Settled,
+        ) && check_test_transfer_status_sender(
+            &wallet.lock().unwrap(),
+            &txid,
+            TransferStatus::Settled,
+        )
+    });
+
+    // stop
+    auto_refresh.stop();
+    rcv_auto_refresh.stop();
+    assert!(!auto_refresh.is_running());
+    assert!(!rcv_auto_refresh.is_running());
+}
+
+#[test]
+fn async_wallet_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets, the receiver one operated from async code
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let rcv_wallet = AsyncWallet::from(rcv_wallet);
+
+    // issue and send
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let blind_data = block_on(rcv_wallet.blind(None, None, None, vec![], None)).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap();
+
+    // the receiver gets the consignment without calling refresh
+    block_on(rcv_wallet.start_auto_refresh(rcv_online, INTERVAL_SECONDS)).unwrap();
+    let blinded_utxo = blind_data.blinded_utxo.clone();
+    wait_for(|| {
+        block_on(rcv_wallet.run({
+            let blinded_utxo = blinded_utxo.clone();
+            move |w| {
+                check_test_transfer_status_recipient(
+                    w,
+                    &blinded_utxo,
+                    TransferStatus::WaitingConfirmations,
+                )
+            }
+        }))
+        .unwrap()
+    });
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), Some(true));
+    block_on(rcv_wallet.stop_auto_refresh()).unwrap();
+}
+
+#[test]
+fn fail() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    let (wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (_other_wallet, other_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let wallet = Arc::new(Mutex::new(wallet));
+
+    // invalid interval
+    let result = AutoRefresh::start(wallet.clone(), online.clone(), 0);
+    assert!(matches!(result, Err(Error::InvalidRefreshInterval(_))));
+
+    // bad online object
+    let result = AutoRefresh::start(wallet.clone(), other_online, INTERVAL_SECONDS);
+    assert!(matches!(result, Err(Error::InvalidOnline())));
+
+    // dropping the worker while holding the wallet lock doesn't wait for it
+    let auto_refresh =
+        AutoRefresh::start(wallet.clone(), online.clone(), INTERVAL_SECONDS).unwrap();
+    let guard = wallet.lock().unwrap();
+    drop(auto_refresh);
+    drop(guard);
+
+    // the worker stops if the wallet lock gets poisoned
+    let auto_refresh =
+        AutoRefresh::start(wallet.clone(), online.clone(), INTERVAL_SECONDS).unwrap();
+    let poisoning_wallet = wallet.clone();
+    let _ = std::thread::spawn(move || {
+        let _guard = poisoning_wallet.lock().unwrap();
+        panic!("poisoning the wallet lock");
+    })
+    .join();
+    assert!(wallet.is_poisoned());
+    wait_for(|| !auto_refresh.is_running());
+
+    // a poisoned wallet lock cannot be auto-refreshed
+    let result = AutoRefresh::start(wallet, online, INTERVAL_SECONDS);
+    assert!(matches!(result, Err(Error::Internal(_))));
+}
@@ -10,20 +10,22 @@ fn success() {
 
     // default expiration
     let now_timestamp = now().unix_timestamp();
-    let blind_data = wallet.blind(None, None, None).unwrap();
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
     assert!(blind_data.expiration_timestamp.is_some());
     let timestamp = now_timestamp + DURATION_RCV_TRANSFER as i64;
     assert!(blind_data.expiration_timestamp.unwrap() - timestamp <= 1);
 
     // positive expiration
     let now_timestamp = now().unix_timestamp();
-    let blind_data = wallet.blind(None, None, Some(expiration)).unwrap();
+    let blind_data = wallet
+        .blind(None, None, Some(expiration), vec![], None)
+        .unwrap();
     assert!(blind_data.expiration_timestamp.is_some());
     let timestamp = now_timestamp + expiration as i64;
     assert!(blind_data.expiration_timestamp.unwrap() - timestamp <= 1);
 
     // 0 expiration
-    let blind_data = wallet.blind(None, None, Some(0)).unwrap();
+    let blind_data = wallet.blind(None, None, Some(0), vec![], None).unwrap();
     assert!(blind_data.expiration_timestamp.is_none());
 
     // asset id is set
@@ -37,12 +39,18 @@ fn success() {
         )
         .unwrap();
     let asset_id = asset.asset_id;
-    let result = wallet.blind(Some(asset_id.clone()), None, None);
+    let result = wallet.blind(Some(asset_id.clone()), None, None, vec![], None);
     assert!(result.is_ok());
 
     // all set
     let now_timestamp = now().unix_timestamp();
-    let result = wallet.blind(Some(asset_id.clone()), Some(amount), Some(expiration));
+    let result = wallet.blind(
+        Some(asset_id.clone()),
+        Some(amount),
+        Some(expiration),
+        vec![],
+        None,
+    );
     assert!(result.is_ok());
     let blind_data = result.unwrap();
 
@@ -56,10 +64,44 @@ fn success() {
     assert_eq!(invoice_data.asset_id, Some(asset_id));
     assert_eq!(invoice_data.amount, Some(amount));
     assert!(invoice_data.expiration_timestamp.unwrap() - approx_expiry <= 1);
+    assert_eq!(
+        invoice_data.consignment_key,
+        Some(blind_data.consignment_key)
+    );
+    let transfer = get_test_transfer_recipient(&wallet, &blind_data.blinded_utxo);
+    assert_eq!(transfer.consignment_key, invoice_data.consignment_key);
+
+    // invoices without rgb-lib extensions are supported
+    let invoice_data = InvoiceData {
+        consignment_key: None,
+        transport_endpoints: vec![],
+        ..invoice_data
+    };
+    let invoice = Invoice::from_invoice_data(invoice_data.clone()).unwrap();
+    let invoice_from_string = Invoice::new(invoice.bech32_invoice()).unwrap();
+    assert_eq!(invoice_from_string.invoice_data(), invoice_data);
 
     // check BlindedUTXO
     let result = BlindedUTXO::new(blind_data.blinded_utxo);
     assert!(result.is_ok());
+
+    // transport endpoints default to the wallet's proxy URLs
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
+    let invoice_data = Invoice::new(blind_data.invoice).unwrap().invoice_data();
+    assert_eq!(
+        invoice_data.transport_endpoints,
+        vec![PROXY_URL.to_string()]
+    );
+
+    // custom transport endpoints
+    let transport_endpoints = vec![s!("https://proxy.example.com/json-rpc"), s!("http://other")];
+    let blind_data = wallet
+        .blind(None, None, None, transport_endpoints.clone(), None)
+        .unwrap();
+    let invoice_data = Invoice::new(blind_data.invoice).unwrap().invoice_data();
+    assert_eq!(invoice_data.transport_endpoints, transport_endpoints);
+    let invoice_from_data = Invoice::from_invoice_data(invoice_data.clone()).unwrap();
+    assert_eq!(invoice_from_data.invoice_data(), invoice_data);
 }
 
 #[test]
@@ -71,7 +113,7 @@ fn respect_max_allocations() {
     // generate MAX_ALLOCATIONS_PER_UTXO + 1 blinded UTXOs and save selected TXOs
     let mut txo_list: HashSet<DbTxo> = HashSet::new();
     for _ in 0..=MAX_ALLOCATIONS_PER_UTXO {
-        let blind_data = wallet.blind(None, None, None).unwrap();
+        let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
         let transfer = get_test_transfer_recipient(&wallet, &blind_data.blinded_utxo);
         let coloring = get_test_coloring(&wallet, transfer.asset_transfer_idx);
         let txo = get_test_txo(&wallet, coloring.txo_idx);
@@ -91,7 +133,9 @@ fn expire() {
 
     // check expiration
     let now_timestamp = now().unix_timestamp();
-    let blind_data_1 = wallet.blind(None, None, Some(expiration)).unwrap();
+    let blind_data_1 = wallet
+        .blind(None, None, Some(expiration), vec![], None)
+        .unwrap();
     let timestamp = now_timestamp + expiration as i64;
     assert!(blind_data_1.expiration_timestamp.unwrap() - timestamp <= 1);
 
@@ -124,11 +168,11 @@ fn fail() {
     let (mut wallet, _online) = get_empty_wallet!();
 
     // bad asset id
-    let result = wallet.blind(Some(s!("rgb1inexistent")), None, None);
+    let result = wallet.blind(Some(s!("rgb1inexistent")), None, None, vec![], None);
     assert!(matches!(result, Err(Error::AssetNotFound(_))));
 
     // insufficient funds
-    let result = wallet.blind(None, None, None);
+    let result = wallet.blind(None, None, None, vec![], None);
     assert!(matches!(result, Err(Error::InsufficientBitcoins)));
 
     // invalid BlindedUTXO
@@ -141,14 +185,43 @@ fn fail() {
 
     // unsupported invoice
     let (mut wallet, _online) = get_funded_wallet!();
-    let blind_data = wallet.blind(None, None, None).unwrap();
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
     let concealed_seal = ConcealedSeal::from_str(&blind_data.blinded_utxo).unwrap();
     let beneficiary = Beneficiary::BlindUtxo(concealed_seal);
     let amount = AmountExt::Milli(1, 1);
-    let mut invoice = UniversalInvoice::new(beneficiary, None, None);
+    let mut invoice = UniversalInvoice::new(beneficiary.clone(), None, None);
     invoice.set_amount(amount);
     let result = Invoice::new(invoice.to_string());
     assert!(matches!(result, Err(Error::UnsupportedInvoice)));
+
+    // unsupported invoice extensions
+    let mut invoice = UniversalInvoice::new(beneficiary, None, None);
+    let source = format!("{}invalid", INVOICE_EXTENSIONS_PREFIX);
+    invoice.set_details(Details {
+        commitment: sha256d::Hash::hash(source.as_bytes()),
+        source,
+    });
+    let result = Invoice::new(invoice.to_string());
+    assert!(matches!(result, Err(Error::UnsupportedInvoice)));
+
+    // invalid consignment key
+    let invoice_data = Invoice::new(blind_data.invoice).unwrap().invoice_data();
+    let result = Invoice::from_invoice_data(InvoiceData {
+        consignment_key: Some(s!("invalid")),
+        ..invoice_data.clone()
+    });
+    assert!(matches!(result, Err(Error::InvalidConsignmentKey(_))));
+
+    // invalid transport endpoints
+    let result = Invoice::from_invoice_data(InvoiceData {
+        transport_endpoints: vec![s!("invalid")],
+        ..invoice_data
+    });
+    assert!(matches!(result, Err(Error::InvalidTransportEndpoint(_))));
+    let result = wallet.blind(None, None, None, vec![s!("invalid")], None);
+    assert!(matches!(result, Err(Error::InvalidTransportEndpoint(_))));
+    let result = wallet.blind(None, None, None, vec![s!("ftp://127.0.0.1:3033")], None);
+    assert!(matches!(result, Err(Error::InvalidTransportEndpoint(_))));
 }
 
 #[test]
@@ -180,18 +253,23 @@ fn wrong_asset_fail() {
         )
         .unwrap();
 
-    let blind_data_a = wallet_1.blind(Some(asset_a.asset_id), None, None).unwrap();
+    let blind_data_a = wallet_1
+        .blind(Some(asset_a.asset_id), None, None, vec![], None)
+        .unwrap();
 
     let recipient_map = HashMap::from([(
         asset_b.asset_id.clone(),
         vec![Recipient {
             amount,
             blinded_utxo: blind_data_a.blinded_utxo.clone(),
+            consignment_key: Some(blind_data_a.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid = wallet_2
-        .send(online_2.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_2.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // transfer is pending
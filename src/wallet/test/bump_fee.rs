@@ -0,0 +1,368 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send and take the transfer to WaitingConfirmations
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let send_result = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap();
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    let txid = Txid::from_str(&send_result.txid).unwrap();
+    assert!(electrum.in_mempool(&txid));
+    let consignment = proxy.consignment(&blind_data.blinded_utxo).unwrap();
+
+    // bump the fee
+    let bump_result = wallet
+        .bump_fee(
+            online.clone(),
+            send_result.txid.clone(),
+            Some(FEE_RATE.unwrap() + 2.0),
+        )
+        .unwrap();
+    assert_ne!(bump_result.txid, send_result.txid);
+    assert!(bump_result.fee > send_result.fee);
+    let new_txid = Txid::from_str(&bump_result.txid).unwrap();
+    assert!(electrum.in_mempool(&new_txid));
+    assert!(!electrum.in_mempool(&txid));
+    assert!(get_test_batch_transfers(&wallet, &send_result.txid).is_empty());
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &bump_result.txid,
+        TransferStatus::WaitingConfirmations
+    ));
+    let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR);
+    assert!(!transfer_dir.join(&send_result.txid).exists());
+    assert!(transfer_dir.join(&bump_result.txid).is_dir());
+
+    // the proxy keeps the original consignment, the new one is posted under a new ID
+    let replacement_id = format!("{}_1", blind_data.blinded_utxo);
+    assert_eq!(
+        proxy.consignment(&blind_data.blinded_utxo).unwrap(),
+        consignment
+    );
+    assert!(proxy.consignment(&replacement_id).is_some());
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &bump_result.txid);
+    assert_eq!(transfer.recipient_id, Some(replacement_id.clone()));
+
+    // the receiver switches to the replacement once the original transaction is gone
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert_eq!(
+        get_test_batch_transfers(&rcv_wallet, &bump_result.txid).len(),
+        1
+    );
+    let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo);
+    assert_eq!(rcv_transfer.recipient_id, Some(replacement_id));
+
+    // both sides settle once the replacement confirms
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &bump_result.txid,
+        TransferStatus::Settled
+    ));
+    let rcv_balance = rcv_wallet
+        .get_asset_balance(asset.asset_id.clone())
+        .unwrap();
+    assert_eq!(rcv_balance.settled, amount);
+    let balance = wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(balance.settled, AMOUNT - amount);
+}
+
+#[test]
+fn multiple_bumps_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send and take the transfer to WaitingConfirmations
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let send_result = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap();
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+
+    // bump the fee twice before the receiver refreshes
+    let first_bump = wallet
+        .bump_fee(
+            online.clone(),
+            send_result.txid,
+            Some(FEE_RATE.unwrap() + 2.0),
+        )
+        .unwrap();
+    let second_bump = wallet
+        .bump_fee(
+            online.clone(),
+            first_bump.txid.clone(),
+            Some(FEE_RATE.unwrap() + 4.0),
+        )
+        .unwrap();
+    assert!(proxy
+        .consignment(&format!("{}_1", blind_data.blinded_utxo))
+        .is_some());
+    let second_id = format!("{}_2", blind_data.blinded_utxo);
+    assert!(proxy.consignment(&second_id).is_some());
+
+    // the receiver switches straight to the latest replacement
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert!(get_test_batch_transfers(&rcv_wallet, &first_bump.txid).is_empty());
+    assert_eq!(
+        get_test_batch_transfers(&rcv_wallet, &second_bump.txid).len(),
+        1
+    );
+    let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo);
+    assert_eq!(rcv_transfer.recipient_id, Some(second_id));
+
+    // both sides settle once the latest replacement confirms
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet.refresh(online, Some(asset.asset_id)).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &second_bump.txid,
+        TransferStatus::Settled
+    ));
+}
+
+#[test]
+fn btc_inputs_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send and take the transfer to WaitingConfirmations
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let send_result = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap();
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id))
+        .unwrap();
+
+    // a fee higher than the value of the replaced inputs is covered by adding UTXOs that cannot
+    // hold RGB allocations
+    let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR);
+    let replaced_tx = wallet
+        ._get_signed_psbt(transfer_dir.join(&send_result.txid))
+        .unwrap()
+        .extract_tx();
+    let colorable_utxos: Vec<Outpoint> = wallet
+        .list_unspents(false)
+        .unwrap()
+        .into_iter()
+        .filter(|u| u.utxo.colorable)
+        .map(|u| u.utxo.outpoint)
+        .collect();
+    let bump_result = wallet
+        .bump_fee(online, send_result.txid, Some(100.0))
+        .unwrap();
+    let tx = wallet
+        ._get_signed_psbt(transfer_dir.join(&bump_result.txid))
+        .unwrap()
+        .extract_tx();
+    assert!(tx.input.len() > replaced_tx.input.len());
+    for input in &replaced_tx.input {
+        assert!(tx
+            .input
+            .iter()
+            .any(|i| i.previous_output == input.previous_output));
+    }
+    let added_inputs = tx.input.iter().filter(|i| {
+        !replaced_tx
+            .input
+            .iter()
+            .any(|r| r.previous_output == i.previous_output)
+    });
+    for input in added_inputs {
+        assert!(!colorable_utxos.contains(&Outpoint::from(input.previous_output)));
+    }
+}
+
+#[test]
+fn max_replacements_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+    let (wallet, _online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let blinded_utxo = "blindedutxo";
+    let proxy_urls = vec![proxy.url()];
+
+    // fill all the replacement IDs
+    let posted_path = wallet.get_wallet_dir().join("posted");
+    std::fs::write(&posted_path, b"posted").unwrap();
+    for replacement in 1..=MAX_CONSIGNMENT_REPLACEMENTS {
+        let res = wallet
+            .rest_client
+            .clone()
+            .post_consignment(
+                &proxy.url(),
+                consignment_recipient_id(blinded_utxo, replacement),
+                posted_path.clone(),
+            )
+            .unwrap();
+        assert!(res.success);
+    }
+
+    // a new replacement cannot be posted
+    let new_path = wallet.get_wallet_dir().join("new");
+    std::fs::write(&new_path, b"new").unwrap();
+    let result = wallet._get_replacement_recipient_id(&proxy_urls, blinded_utxo, &new_path);
+    assert!(matches!(result, Err(Error::MaxConsignmentReplacements(_))));
+}
+
+#[test]
+fn fail_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // unknown transfer
+    let result = wallet.bump_fee(online.clone(), s!("unknown"), FEE_RATE);
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+
+    // transfer not yet broadcast
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    let result = wallet.bump_fee(online.clone(), txid.clone(), Some(10.0));
+    assert!(matches!(result, Err(Error::CannotBumpFee)));
+
+    // fee rate not high enough to replace the transaction
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    let result = wallet.bump_fee(online.clone(), txid.clone(), FEE_RATE);
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+
+    // incoming transfer
+    let result = rcv_wallet.bump_fee(rcv_online, txid.clone(), Some(10.0));
+    assert!(matches!(result, Err(Error::CannotBumpFee)));
+
+    // confirmed transaction
+    electrum.mine(1);
+    let result = wallet.bump_fee(online, txid, Some(10.0));
+    assert!(matches!(result, Err(Error::CannotBumpFee)));
+}
@@ -0,0 +1,175 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let btc_amount: u64 = 5000;
+    let fee_rate: f32 = 10.0;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send asset and bitcoins to the receiver and take the transfer to WaitingConfirmations
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let btc_address = rcv_wallet.get_address();
+    let send_result = wallet
+        .send(
+            online.clone(),
+            recipient_map,
+            false,
+            SendOptions {
+                btc_recipients: vec![BtcRecipient {
+                    address: btc_address.clone(),
+                    amount: btc_amount,
+                }],
+                ..send_options()
+            },
+        )
+        .unwrap();
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // accelerate the transfer
+    let cpfp_result = rcv_wallet
+        .cpfp(rcv_online.clone(), send_result.txid.clone(), Some(fee_rate))
+        .unwrap();
+    let parent_txid = Txid::from_str(&send_result.txid).unwrap();
+    let child_txid = Txid::from_str(&cpfp_result.txid).unwrap();
+    assert!(electrum.in_mempool(&parent_txid));
+    assert!(electrum.in_mempool(&child_txid));
+
+    // the child spends the BTC output of the parent and the package pays the target fee rate
+    let transfer_dir = wallet
+        .get_wallet_dir()
+        .join(TRANSFER_DIR)
+        .join(&send_result.txid);
+    let parent_tx = wallet._get_signed_psbt(transfer_dir).unwrap().extract_tx();
+    let child_tx = rcv_wallet
+        .bdk_wallet
+        .get_tx(&child_txid, true)
+        .unwrap()
+        .unwrap()
+        .transaction
+        .unwrap();
+    let btc_script = Address::from_str(&btc_address).unwrap().script_pubkey();
+    let btc_vout = parent_tx
+        .output
+        .iter()
+        .position(|o| o.script_pubkey == btc_script)
+        .unwrap();
+    assert!(child_tx.input.iter().any(|i| i.previous_output
+        == OutPoint {
+            txid: parent_txid,
+            vout: btc_vout as u32,
+        }));
+    let package_vsize = ((parent_tx.weight() + child_tx.weight()) as f32 / 4.0).ceil();
+    let package_fee = send_result.fee + cpfp_result.fee;
+    assert!(package_fee as f32 / package_vsize >= fee_rate);
+
+    // the blinded UTXO is not spent and the transfer settles once the package confirms
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    let rcv_balance = rcv_wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(rcv_balance.settled, amount);
+}
+
+#[test]
+fn fail_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // unknown transfer
+    let result = rcv_wallet.cpfp(rcv_online.clone(), s!("unknown"), Some(10.0));
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+
+    // send with no BTC output for the receiver
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+
+    // outgoing transfer
+    let result = wallet.cpfp(online, txid.clone(), Some(10.0));
+    assert!(matches!(result, Err(Error::CannotCpfp)));
+
+    // fee rate not higher than the one of the transaction
+    let result = rcv_wallet.cpfp(rcv_online.clone(), txid.clone(), FEE_RATE);
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+
+    // no output owned by the receiver to spend
+    let result = rcv_wallet.cpfp(rcv_online.clone(), txid.clone(), Some(10.0));
+    assert!(matches!(result, Err(Error::CannotCpfp)));
+
+    // confirmed transaction
+    electrum.mine(1);
+    let result = rcv_wallet.cpfp(rcv_online, txid, Some(10.0));
+    assert!(matches!(result, Err(Error::CannotCpfp)));
+}
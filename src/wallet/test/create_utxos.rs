@@ -8,8 +8,9 @@ fn success() {
     println!("\n=== up_to true, 0 allocatable");
     let (mut wallet, online) = get_funded_noutxo_wallet!();
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), true, None, None)
-        .unwrap();
+        .create_utxos(online.clone(), true, None, None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, UTXO_NUM);
     let unspents = wallet.list_unspents(false).unwrap();
     assert_eq!(unspents.len(), (UTXO_NUM + 1) as usize);
@@ -17,15 +18,19 @@ fn success() {
     // up_to version with allocatable UTXOs partially available (1 missing)
     println!("\n=== up_to true, need to create 1 more");
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), true, Some(UTXO_NUM + 1), None)
-        .unwrap();
+        .create_utxos(online.clone(), true, Some(UTXO_NUM + 1), None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
     let unspents = wallet.list_unspents(false).unwrap();
     assert_eq!(unspents.len(), (UTXO_NUM + 2) as usize);
 
     // forced version always creates UTXOs
     println!("\n=== up_to false");
-    let num_utxos_created = wallet.create_utxos(online, false, None, None).unwrap();
+    let num_utxos_created = wallet
+        .create_utxos(online, false, None, None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, UTXO_NUM);
     let unspents = wallet.list_unspents(false).unwrap();
     assert_eq!(unspents.len(), (UTXO_NUM * 2 + 2) as usize);
@@ -37,11 +42,84 @@ fn fail() {
 
     // cannot create UTXOs for an empty wallet
     let (mut wallet, online) = get_empty_wallet!();
-    let result = wallet.create_utxos(online, true, None, None);
+    let result = wallet.create_utxos(online, true, None, None, FEE_RATE);
     assert!(matches!(result, Err(Error::InsufficientBitcoins)));
 
     // don't create UTXOs if enough allocations are already available
     let (mut wallet, online) = get_funded_wallet!();
-    let result = wallet.create_utxos(online, true, None, None);
+    let result = wallet.create_utxos(online, true, None, None, FEE_RATE);
     assert!(matches!(result, Err(Error::AllocationsAlreadyAvailable)));
+
+    // invalid fee rate
+    let (mut wallet, online) = get_funded_noutxo_wallet!();
+    let result = wallet.create_utxos(online.clone(), false, None, None, Some(0.0));
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+    let result = wallet.create_utxos(online, false, None, None, Some(MAX_FEE_RATE + 1.0));
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+}
+
+#[test]
+fn fee_rate_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // the same UTXOs created from identical wallets cost more at a higher fee rate
+    let (mut wallet_low, online_low) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut wallet_high, online_high) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let result_low = wallet_low
+        .create_utxos(online_low, false, Some(2), None, Some(MIN_FEE_RATE))
+        .unwrap();
+    let result_high = wallet_high
+        .create_utxos(online_high, false, Some(2), None, Some(MIN_FEE_RATE * 10.0))
+        .unwrap();
+    assert_eq!(result_low.num_utxos, 2);
+    assert_eq!(result_high.num_utxos, 2);
+    assert!(result_low.fee > 0);
+    assert!(result_high.fee > result_low.fee * 5);
+}
+
+#[test]
+fn stripped_psbt_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let unsigned_psbt = wallet
+        .create_utxos_begin(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap();
+    let mut psbt = PartiallySignedTransaction::from_str(&unsigned_psbt).unwrap();
+    wallet
+        .bdk_wallet
+        .sign(&mut psbt, SignOptions::default())
+        .unwrap();
+    let fee = wallet._psbt_fee(&psbt).unwrap();
+    assert!(fee > 0);
+
+    // the fee of a PSBT without the spent outputs is computed from the wallet's transactions
+    let mut stripped_psbt = psbt.clone();
+    for input in stripped_psbt.inputs.iter_mut() {
+        input.witness_utxo = None;
+        input.non_witness_utxo = None;
+    }
+    assert_eq!(wallet._psbt_fee(&stripped_psbt).unwrap(), fee);
+
+    // spent outputs unknown to the wallet are reported
+    let mut foreign_psbt = stripped_psbt.clone();
+    let foreign_outpoint = OutPoint {
+        txid: Txid::from_str("e5a3e577309df31bd606f48049049d2e1e02b048206ba232944fcc053a176ccb")
+            .unwrap(),
+        vout: 0,
+    };
+    foreign_psbt.unsigned_tx.input[0].previous_output = foreign_outpoint;
+    let result = wallet._psbt_fee(&foreign_psbt);
+    assert!(
+        matches!(result, Err(Error::UnknownPsbtInput(ref o)) if o == &foreign_outpoint.to_string())
+    );
+
+    // the stripped PSBT can be completed
+    let result = wallet
+        .create_utxos_end(online, stripped_psbt.to_string())
+        .unwrap();
+    assert_eq!(result.num_utxos, 1);
+    assert_eq!(result.fee, fee);
 }
@@ -7,7 +7,7 @@ fn success() {
     let (mut wallet, online) = get_funded_wallet!();
 
     // delete single transfer
-    let blind_data = wallet.blind(None, None, None).unwrap();
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
     wallet
         .fail_transfers(online.clone(), Some(blind_data.blinded_utxo.clone()), None)
         .unwrap();
@@ -21,9 +21,9 @@ fn success() {
         .unwrap();
 
     // delete all Failed transfers
-    let blind_data_1 = wallet.blind(None, None, None).unwrap();
-    let blind_data_2 = wallet.blind(None, None, None).unwrap();
-    let blind_data_3 = wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_3 = wallet.blind(None, None, None, vec![], None).unwrap();
     wallet
         .fail_transfers(
             online.clone(),
@@ -77,22 +77,29 @@ fn batch_success() {
     let asset_id = asset.asset_id;
 
     // failed transfer can be deleted, using both blinded_utxo + txid
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online.clone(), None, Some(txid.clone()))
@@ -102,22 +109,29 @@ fn batch_success() {
         .unwrap();
 
     // ...and can be deleted using txid only
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id,
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online, None, Some(txid.clone()))
@@ -131,7 +145,7 @@ fn fail() {
 
     let (mut wallet, _online) = get_funded_wallet!();
 
-    let blind_data = wallet.blind(None, None, None).unwrap();
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
 
     // don't delete transfer not in Failed status
     assert!(!check_test_transfer_status_recipient(
@@ -170,22 +184,29 @@ fn batch_fail() {
     let asset_id = asset.asset_id;
 
     // only blinded UTXO given but multiple transfers in batch
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     wallet
         .fail_transfers(online.clone(), None, Some(txid.clone()))
         .unwrap();
@@ -198,22 +219,29 @@ fn batch_fail() {
     assert!(matches!(result, Err(Error::CannotDeleteTransfer)));
 
     // blinded UTXO + txid given but blinded UTXO transfer not part of batch transfer
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map_1 = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid_1 = wallet.send(online.clone(), recipient_map_1, false).unwrap();
+    let txid_1 = wallet
+        .send(online.clone(), recipient_map_1, false, send_options())
+        .unwrap()
+        .txid;
     wallet
         .fail_transfers(online.clone(), None, Some(txid_1.clone()))
         .unwrap();
@@ -222,15 +250,20 @@ fn batch_fail() {
         &txid_1,
         TransferStatus::Failed
     ));
-    let blind_data_3 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_3 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map_2 = HashMap::from([(
         asset_id,
         vec![Recipient {
             blinded_utxo: blind_data_3.blinded_utxo.clone(),
             amount,
+            consignment_key: Some(blind_data_3.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_2 = wallet.send(online.clone(), recipient_map_2, false).unwrap();
+    let txid_2 = wallet
+        .send(online.clone(), recipient_map_2, false, send_options())
+        .unwrap()
+        .txid;
     wallet
         .fail_transfers(online, None, Some(txid_2.clone()))
         .unwrap();
@@ -11,7 +11,7 @@ fn success() {
     let (wallet, online) = get_funded_noutxo_wallet!();
     wallet._sync_db_txos().unwrap();
     wallet
-        .drain_to(online, rcv_wallet.get_address(), false)
+        .drain_to(online, rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
     mine();
     wallet._sync_db_txos().unwrap();
@@ -33,14 +33,14 @@ fn success() {
 
     // drain funded wallet with RGB allocations
     wallet
-        .drain_to(online.clone(), rcv_wallet.get_address(), false)
+        .drain_to(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
     mine();
     wallet._sync_db_txos().unwrap();
     let unspents = list_test_unspents(&wallet, "funded with allocations after draining (false)");
     assert_eq!(unspents.len() as u8, UTXO_NUM);
     wallet
-        .drain_to(online, rcv_wallet.get_address(), true)
+        .drain_to(online, rcv_wallet.get_address(), true, FEE_RATE)
         .unwrap();
     mine();
     wallet._sync_db_txos().unwrap();
@@ -57,24 +57,30 @@ fn fail() {
 
     // drain empty wallet
     let (wallet, online) = get_empty_wallet!();
-    let result = wallet.drain_to(online, rcv_wallet.get_address(), true);
+    let result = wallet.drain_to(online, rcv_wallet.get_address(), true, FEE_RATE);
     assert!(matches!(result, Err(Error::InsufficientBitcoins)));
 
     // bad online object
     let (wallet, _online) = get_funded_noutxo_wallet!();
     wallet._sync_db_txos().unwrap();
-    let result = wallet.drain_to(rcv_online, rcv_wallet.get_address(), false);
+    let result = wallet.drain_to(rcv_online, rcv_wallet.get_address(), false, FEE_RATE);
     assert!(matches!(result, Err(Error::InvalidOnline())));
 
     // bad address
     let (wallet, online) = get_funded_noutxo_wallet!();
     wallet._sync_db_txos().unwrap();
-    let result = wallet.drain_to(online, s!("invalid address"), false);
+    let result = wallet.drain_to(online, s!("invalid address"), false, FEE_RATE);
     assert!(matches!(result, Err(Error::InvalidAddress(_))));
 
+    // invalid fee rate
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    wallet._sync_db_txos().unwrap();
+    let result = wallet.drain_to(online, rcv_wallet.get_address(), false, Some(0.9));
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+
     // no private keys
     let (wallet, online) = get_funded_noutxo_wallet!(false, false);
     wallet._sync_db_txos().unwrap();
-    let result = wallet.drain_to(online, rcv_wallet.get_address(), false);
+    let result = wallet.drain_to(online, rcv_wallet.get_address(), false, FEE_RATE);
     assert!(matches!(result, Err(Error::WatchOnly())));
 }
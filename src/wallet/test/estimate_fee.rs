@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    let (wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // estimates are converted from BTC/kvB to sat/vB
+    electrum.set_fee_estimate(0.0002);
+    let fee_rate = wallet.estimate_fee(online.clone(), 6).unwrap();
+    assert_eq!(fee_rate, 20.0);
+
+    // estimates are capped to the accepted fee rate range
+    electrum.set_fee_estimate(0.000001);
+    let fee_rate = wallet.estimate_fee(online.clone(), 1).unwrap();
+    assert_eq!(fee_rate, MIN_FEE_RATE);
+    electrum.set_fee_estimate(1.0);
+    let fee_rate = wallet.estimate_fee(online.clone(), 1).unwrap();
+    assert_eq!(fee_rate, MAX_FEE_RATE);
+
+    // on regtest a missing estimate falls back to the minimum fee rate
+    electrum.set_fee_estimate(-1.0);
+    let fee_rate = wallet.estimate_fee(online, 1008).unwrap();
+    assert_eq!(fee_rate, MIN_FEE_RATE);
+}
+
+#[test]
+fn auto_fee_rate_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // identical transactions pay the same fee with the estimated rate and the explicit one
+    let (mut wallet_auto, online_auto) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut wallet_explicit, online_explicit) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    electrum.set_fee_estimate(0.0001);
+    let result_auto = wallet_auto
+        .create_utxos(online_auto.clone(), false, Some(2), None, None)
+        .unwrap();
+    let result_explicit = wallet_explicit
+        .create_utxos(online_explicit, false, Some(2), None, Some(10.0))
+        .unwrap();
+    assert_eq!(result_auto.fee, result_explicit.fee);
+
+    // draining with the estimated rate
+    wallet_auto._sync_db_txos().unwrap();
+    let result = wallet_auto
+        .drain_to(online_auto, wallet_explicit.get_address(), false, None)
+        .unwrap();
+    assert!(result.fee > 0);
+}
+
+#[test]
+fn fail() {
+    initialize();
+
+    let (wallet, online) = get_empty_wallet!();
+
+    // invalid targets
+    let result = wallet.estimate_fee(online.clone(), 0);
+    assert!(matches!(result, Err(Error::InvalidFeeTarget(_))));
+    let result = wallet.estimate_fee(online, 1009);
+    assert!(matches!(result, Err(Error::InvalidFeeTarget(_))));
+
+    // bad online object
+    let (_other_wallet, other_online) = get_empty_wallet!();
+    let result = wallet.estimate_fee(other_online, 6);
+    assert!(matches!(result, Err(Error::InvalidOnline())));
+}
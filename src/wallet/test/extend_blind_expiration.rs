@@ -0,0 +1,83 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    let blind_data = wallet
+        .blind(None, Some(AMOUNT), Some(1), vec![], None)
+        .unwrap();
+
+    // extend expiration
+    let now_timestamp = now().unix_timestamp();
+    let extended = wallet
+        .extend_blind_expiration(blind_data.invoice.clone(), Some(3600))
+        .unwrap();
+    assert_eq!(extended.blinded_utxo, blind_data.blinded_utxo);
+    assert_eq!(extended.blinding_secret, blind_data.blinding_secret);
+    assert_eq!(extended.consignment_key, blind_data.consignment_key);
+    assert!(extended.expiration_timestamp.unwrap() - (now_timestamp + 3600) <= 1);
+    let transfer = get_test_transfer_recipient(&wallet, &blind_data.blinded_utxo);
+    let transfer_data = wallet.database.get_transfer_data(&transfer).unwrap();
+    assert_eq!(transfer_data.expiration, extended.expiration_timestamp);
+
+    // the invoice is re-issued with the new expiry and the same other data
+    assert_ne!(extended.invoice, blind_data.invoice);
+    let invoice_data = Invoice::new(extended.invoice).unwrap().invoice_data();
+    assert_eq!(invoice_data.blinded_utxo, blind_data.blinded_utxo);
+    assert_eq!(invoice_data.amount, Some(AMOUNT));
+    assert_eq!(
+        invoice_data.expiration_timestamp,
+        extended.expiration_timestamp
+    );
+    assert_eq!(
+        invoice_data.consignment_key,
+        Some(blind_data.consignment_key.clone())
+    );
+
+    // clear expiration
+    let cleared = wallet
+        .extend_blind_expiration(blind_data.invoice.clone(), Some(0))
+        .unwrap();
+    assert!(cleared.expiration_timestamp.is_none());
+    let invoice_data = Invoice::new(cleared.invoice).unwrap().invoice_data();
+    assert!(invoice_data.expiration_timestamp.is_none());
+
+    // the transfer doesn't expire anymore
+    std::thread::sleep(std::time::Duration::from_millis(2000));
+    wallet.refresh(online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingCounterparty
+    ));
+}
+
+#[test]
+fn fail_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut other_wallet, _other_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // invalid invoice
+    let result = wallet.extend_blind_expiration(s!("invalid"), None);
+    assert!(matches!(result, Err(Error::InvalidInvoice(_))));
+
+    // invoice from another wallet
+    let other_blind_data = other_wallet.blind(None, None, None, vec![], None).unwrap();
+    let result = wallet.extend_blind_expiration(other_blind_data.invoice, None);
+    assert!(matches!(result, Err(Error::TransferNotFound(_))));
+
+    // failed transfer
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
+    wallet
+        .fail_transfers(online, Some(blind_data.blinded_utxo), None)
+        .unwrap();
+    let result = wallet.extend_blind_expiration(blind_data.invoice, None);
+    assert!(matches!(result, Err(Error::CannotChangeExpiration)));
+}
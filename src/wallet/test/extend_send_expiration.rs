@@ -0,0 +1,135 @@
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send with a custom expiration
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(
+            online.clone(),
+            recipient_map,
+            false,
+            SendOptions {
+                duration_seconds: Some(1),
+                ..send_options()
+            },
+        )
+        .unwrap()
+        .txid;
+    let transfer = get_test_transfer_sender(&wallet, &txid).0;
+    let transfer_data = wallet.database.get_transfer_data(&transfer).unwrap();
+    assert_eq!(transfer_data.expiration, Some(transfer_data.created_at + 1));
+
+    // extend expiration
+    let now_timestamp = now().unix_timestamp();
+    let expiration = wallet
+        .extend_send_expiration(txid.clone(), Some(7200))
+        .unwrap();
+    assert!(expiration.unwrap() - (now_timestamp + 7200) <= 1);
+    let transfer_data = wallet.database.get_transfer_data(&transfer).unwrap();
+    assert_eq!(transfer_data.expiration, expiration);
+
+    // the transfer doesn't expire with its original expiration
+    std::thread::sleep(std::time::Duration::from_millis(2000));
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingCounterparty
+    ));
+
+    // default and cleared expiration
+    let now_timestamp = now().unix_timestamp();
+    let expiration = wallet.extend_send_expiration(txid.clone(), None).unwrap();
+    assert!(expiration.unwrap() - (now_timestamp + DURATION_SEND_TRANSFER as i64) <= 1);
+    let expiration = wallet
+        .extend_send_expiration(txid.clone(), Some(0))
+        .unwrap();
+    assert!(expiration.is_none());
+    let transfer_data = wallet.database.get_transfer_data(&transfer).unwrap();
+    assert!(transfer_data.expiration.is_none());
+}
+
+#[test]
+fn fail_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // unknown transfer
+    let result = wallet.extend_send_expiration(s!("unknown"), None);
+    assert!(matches!(result, Err(Error::BatchTransferNotFound(_))));
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+
+    // transfer no longer waiting for the counterparty
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()))
+        .unwrap();
+    let result = wallet.extend_send_expiration(txid.clone(), None);
+    assert!(matches!(result, Err(Error::CannotChangeExpiration)));
+
+    // incoming transfer
+    let result = rcv_wallet.extend_send_expiration(txid, None);
+    assert!(matches!(result, Err(Error::CannotChangeExpiration)));
+}
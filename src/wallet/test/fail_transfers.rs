@@ -21,7 +21,7 @@ fn success() {
         .unwrap();
 
     // fail single transfer
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     assert!(check_test_transfer_status_recipient(
         &rcv_wallet,
         &blind_data.blinded_utxo,
@@ -32,17 +32,22 @@ fn success() {
         .unwrap();
 
     // fail all WaitingCounterparty transfers
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
-    let blind_data_3 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_3 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             blinded_utxo: blind_data_3.blinded_utxo.clone(),
             amount,
+            consignment_key: Some(blind_data_3.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online, recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
     assert!(check_test_transfer_status_recipient(
@@ -101,22 +106,29 @@ fn batch_success() {
     let asset_id = asset.asset_id;
 
     // transfer is in WaitingCounterparty status and can be failed, using both blinded_utxo + txid
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     assert!(check_test_transfer_status_recipient(
         &rcv_wallet_1,
@@ -138,44 +150,58 @@ fn batch_success() {
         .unwrap();
 
     // ...and can be failed using txid only
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online.clone(), None, Some(txid))
         .unwrap();
 
     // transfer is still in WaitingCounterparty status after some recipients (but not all) replied with an ACK
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id,
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     rcv_wallet_1.refresh(rcv_online_1, None).unwrap();
     assert!(check_test_transfer_status_recipient(
@@ -218,7 +244,7 @@ fn fail() {
         .unwrap();
     let asset_id = asset.asset_id;
     // blind
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let blinded_utxo = blind_data.blinded_utxo;
     // send
     let recipient_map = HashMap::from([(
@@ -226,9 +252,13 @@ fn fail() {
         vec![Recipient {
             blinded_utxo: blinded_utxo.clone(),
             amount: 66,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    wallet.send(online.clone(), recipient_map, false).unwrap();
+    wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap();
 
     // check starting transfer status
     assert!(check_test_transfer_status_recipient(
@@ -330,22 +360,29 @@ fn batch_fail() {
     let asset_id = asset.asset_id;
 
     // only blinded utxo given but multiple transfers in batch
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     let result = wallet.fail_transfers(online.clone(), Some(blind_data_1.blinded_utxo), None);
     assert!(matches!(result, Err(Error::CannotFailTransfer)));
     // successfully fail transfer so asset can be spent again
@@ -353,31 +390,43 @@ fn batch_fail() {
     assert!(result.is_ok());
 
     // blinded utxo + txid given but blinded utxo transfer not part of batch transfer
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map_1 = HashMap::from([(
         asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid_1 = wallet.send(online.clone(), recipient_map_1, false).unwrap();
-    let blind_data_3 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let txid_1 = wallet
+        .send(online.clone(), recipient_map_1, false, send_options())
+        .unwrap()
+        .txid;
+    let blind_data_3 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map_2 = HashMap::from([(
         asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_3.blinded_utxo.clone(),
             amount,
+            consignment_key: Some(blind_data_3.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_2 = wallet.send(online.clone(), recipient_map_2, false).unwrap();
+    let txid_2 = wallet
+        .send(online.clone(), recipient_map_2, false, send_options())
+        .unwrap()
+        .txid;
     let result = wallet.fail_transfers(
         online.clone(),
         Some(blind_data_3.blinded_utxo),
@@ -393,22 +442,28 @@ fn batch_fail() {
         .unwrap();
 
     // batch send as donation (doesn't wait for recipient confirmations)
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_id,
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo,
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    wallet.send(online.clone(), recipient_map, true).unwrap();
+    wallet
+        .send(online.clone(), recipient_map, true, send_options())
+        .unwrap();
 
     // transfer is in WaitingConfirmations status and cannot be failed
     assert!(check_test_transfer_status_recipient(
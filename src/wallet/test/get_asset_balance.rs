@@ -105,16 +105,18 @@ fn transfer_balances() {
     //
 
     // send some assets
-    let blind_data_1 = wallet_recv.blind(None, None, None).unwrap();
+    let blind_data_1 = wallet_recv.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_1.blinded_utxo,
             amount: amount_1,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     wallet_send
-        .send(online_send.clone(), recipient_map, false)
+        .send(online_send.clone(), recipient_map, false, send_options())
         .unwrap();
 
     show_unspent_colorings(&wallet_send, "send after 1st send");
@@ -217,16 +219,18 @@ fn transfer_balances() {
     //
 
     // send some assets
-    let blind_data_2 = wallet_recv.blind(None, None, None).unwrap();
+    let blind_data_2 = wallet_recv.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_2.blinded_utxo,
             amount: amount_2,
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     wallet_send
-        .send(online_send.clone(), recipient_map, false)
+        .send(online_send.clone(), recipient_map, false, send_options())
         .unwrap();
 
     show_unspent_colorings(&wallet_send, "send after 2nd send");
@@ -20,15 +20,19 @@ fn success() {
     assert_eq!(transfers.len(), 1);
     let issuance = transfers.first().unwrap();
     let timestamp = issuance.created_at;
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20.asset_id.clone(),
         vec![Recipient {
             amount: 10,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    wallet.send(online.clone(), recipient_map, false).unwrap();
+    wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap();
     rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
     let rgb20_metadata = rcv_wallet
         .get_asset_metadata(rcv_online, asset_rgb20.asset_id.clone())
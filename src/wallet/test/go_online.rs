@@ -9,15 +9,85 @@ fn success() {
     let mut wallet = get_test_wallet(true);
 
     // go online
-    let result_1 = wallet.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result_1 = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(result_1.is_ok());
 
     // can go online twice with the same electrum URL
-    let result_2 = wallet.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result_2 = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(result_2.is_ok());
     assert_eq!(result_1.unwrap(), result_2.unwrap());
 }
 
+#[test]
+fn unavailable_proxy_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+    let unavailable_url = s!("http://127.0.0.1:1");
+
+    // can go online if at least one proxy is available
+    let mut wallet = get_test_wallet_on_mocks(true);
+    let online = wallet
+        .go_online(
+            true,
+            electrum.url(),
+            vec![unavailable_url.clone(), proxy.url()],
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        online.proxy_urls,
+        vec![unavailable_url.clone(), proxy.url()]
+    );
+
+    // proxies with an unsupported protocol version are skipped like unavailable ones
+    let unsupported_proxy = MockProxy::start();
+    unsupported_proxy.set_version("0.3.0");
+    let mut wallet = get_test_wallet_on_mocks(true);
+    wallet
+        .go_online(
+            true,
+            electrum.url(),
+            vec![unsupported_proxy.url(), proxy.url()],
+            None,
+            None,
+        )
+        .unwrap();
+    assert!(!wallet
+        .proxy_versions
+        .borrow()
+        .contains_key(&unsupported_proxy.url()));
+    assert!(wallet.proxy_versions.borrow().contains_key(&proxy.url()));
+
+    // cannot go online if no proxy is available
+    let mut wallet = get_test_wallet_on_mocks(true);
+    let result = wallet.go_online(true, electrum.url(), vec![unavailable_url], None, None);
+    assert!(matches!(result, Err(Error::Proxy(_))));
+
+    // cannot go online if no proxy supports this library's protocol versions
+    let mut wallet = get_test_wallet_on_mocks(true);
+    let result = wallet.go_online(
+        true,
+        electrum.url(),
+        vec![unsupported_proxy.url()],
+        None,
+        None,
+    );
+    assert!(matches!(result, Err(Error::UnsupportedProxyVersion(_))));
+}
+
 #[test]
 fn fail() {
     initialize();
@@ -25,25 +95,125 @@ fn fail() {
     let mut wallet = get_test_wallet(true);
 
     // cannot go online with a broken electrum URL
-    let result = wallet.go_online(false, s!("other:50001"), PROXY_URL.to_string());
+    let result = wallet.go_online(
+        false,
+        s!("other:50001"),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::InvalidElectrum(_))));
 
+    // cannot go online without proxy URLs
+    let result = wallet.go_online(false, ELECTRUM_URL.to_string(), vec![], None, None);
+    assert!(matches!(result, Err(Error::InvalidProxyUrls(_))));
+
+    // cannot go online with duplicate proxy URLs
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string(), PROXY_URL.to_string()],
+        None,
+        None,
+    );
+    assert!(matches!(result, Err(Error::InvalidProxyUrls(_))));
+
     // cannot go online with a broken proxy URL
-    let result = wallet.go_online(false, ELECTRUM_URL.to_string(), s!("http://other"));
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![s!("http://other")],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::Proxy(_))));
 
     wallet
-        .go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string())
+        .go_online(
+            false,
+            ELECTRUM_URL.to_string(),
+            vec![PROXY_URL.to_string()],
+            None,
+            None,
+        )
         .unwrap();
 
     // cannot go online twice with different electrum URLs
-    let result = wallet.go_online(false, s!("other:50001"), PROXY_URL.to_string());
+    let result = wallet.go_online(
+        false,
+        s!("other:50001"),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::CannotChangeOnline())));
 
     // cannot go online twice with differente proxy URLs
-    let result = wallet.go_online(false, ELECTRUM_URL.to_string(), s!("http://other"));
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![s!("http://other")],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::CannotChangeOnline())));
 
+    // cannot go online twice with different proxy options
+    let proxy_options = ProxyOptions {
+        auth: Some(ProxyAuth::Bearer { token: s!("token") }),
+        ..Default::default()
+    };
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        Some(proxy_options),
+        None,
+    );
+    assert!(matches!(result, Err(Error::CannotChangeOnline())));
+
+    // cannot go online twice with different network policies
+    let network_policy = NetworkPolicy {
+        max_retries: 0,
+        ..Default::default()
+    };
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        Some(network_policy),
+    );
+    assert!(matches!(result, Err(Error::CannotChangeOnline())));
+
+    // invalid network policies
+    let mut wallet = get_test_wallet(true);
+    let network_policy = NetworkPolicy {
+        proxy_timeout: 0,
+        ..Default::default()
+    };
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        Some(network_policy),
+    );
+    assert!(matches!(result, Err(Error::InvalidNetworkPolicy(_))));
+    let network_policy = NetworkPolicy {
+        backoff_initial_ms: 2000,
+        backoff_max_ms: 1000,
+        ..Default::default()
+    };
+    let result = wallet.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        Some(network_policy),
+    );
+    assert!(matches!(result, Err(Error::InvalidNetworkPolicy(_))));
+
     // bad online object
     let (_wrong_wallet, wrong_online) = get_empty_wallet!();
     let result = wallet._check_online(wrong_online);
@@ -99,6 +269,8 @@ fn consistency_check_fail_utxos() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     let wallet_data_prefill = WalletData {
         data_dir: data_dir_prefill.into_os_string().into_string().unwrap(),
@@ -106,6 +278,8 @@ fn consistency_check_fail_utxos() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     let wallet_data_prefill_2 = WalletData {
         data_dir: data_dir_prefill_2.into_os_string().into_string().unwrap(),
@@ -113,6 +287,8 @@ fn consistency_check_fail_utxos() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic,
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     // copy original wallet's db data to prefilled wallet data dir
     let wallet_dir_entries = fs::read_dir(&wallet_dir_orig).unwrap();
@@ -139,16 +315,28 @@ fn consistency_check_fail_utxos() {
     // loss)
     let mut wallet_empty = Wallet::new(wallet_data_empty).unwrap();
     let online_empty = wallet_empty
-        .go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string())
+        .go_online(
+            false,
+            ELECTRUM_URL.to_string(),
+            vec![PROXY_URL.to_string()],
+            None,
+            None,
+        )
         .unwrap();
     let (rcv_wallet, _rcv_online) = get_funded_wallet!();
     wallet_empty
-        .drain_to(online_empty, rcv_wallet.get_address(), false)
+        .drain_to(online_empty, rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
 
     // detect asset inconsistency
     let mut wallet_prefill = Wallet::new(wallet_data_prefill).unwrap();
-    let result = wallet_prefill.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result = wallet_prefill.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::Inconsistency(_))));
 
     // make sure detection works multiple times (doesn't get reset on first failed check)
@@ -158,7 +346,13 @@ fn consistency_check_fail_utxos() {
         let dst = PathBuf::from(&wallet_dir_prefill_2).join(file);
         fs::copy(&src, &dst).unwrap();
     }
-    let result = wallet_prefill_2.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result = wallet_prefill_2.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::Inconsistency(_))));
 }
 
@@ -211,6 +405,8 @@ fn consistency_check_fail_asset_ids() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     let wallet_data_prefill_2 = WalletData {
         data_dir: data_dir_prefill_2.to_str().unwrap().to_string(),
@@ -218,6 +414,8 @@ fn consistency_check_fail_asset_ids() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     let wallet_data_prefill_3 = WalletData {
         data_dir: data_dir_prefill_3.to_str().unwrap().to_string(),
@@ -225,6 +423,8 @@ fn consistency_check_fail_asset_ids() {
         database_type: DatabaseType::Sqlite,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic,
+        min_confirmations: wallet_data_orig.min_confirmations,
+        begun_send_duration: wallet_data_orig.begun_send_duration,
     };
     // copy original wallet's data to prefilled wallets 1 + 2 data dir
     for destination in [&wallet_dir_prefill_1, &wallet_dir_prefill_2] {
@@ -241,7 +441,13 @@ fn consistency_check_fail_asset_ids() {
 
     // check the first wallet copy works ok
     let mut wallet_prefill_1 = Wallet::new(wallet_data_prefill_1).unwrap();
-    let result = wallet_prefill_1.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result = wallet_prefill_1.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(result.is_ok());
 
     // introduce asset id inconsistency by removing RGB data from wallet dir
@@ -249,7 +455,13 @@ fn consistency_check_fail_asset_ids() {
 
     // detect inconsistency
     let mut wallet_prefill_2 = Wallet::new(wallet_data_prefill_2).unwrap();
-    let result = wallet_prefill_2.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result = wallet_prefill_2.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::Inconsistency(_))));
 
     // make sure detection works multiple times
@@ -263,6 +475,208 @@ fn consistency_check_fail_asset_ids() {
         .status();
     assert!(result.is_ok());
     let mut wallet_prefill_3 = Wallet::new(wallet_data_prefill_3).unwrap();
-    let result = wallet_prefill_3.go_online(false, ELECTRUM_URL.to_string(), PROXY_URL.to_string());
+    let result = wallet_prefill_3.go_online(
+        false,
+        ELECTRUM_URL.to_string(),
+        vec![PROXY_URL.to_string()],
+        None,
+        None,
+    );
     assert!(matches!(result, Err(Error::Inconsistency(_))));
 }
+
+#[test]
+fn resume_interrupted_sends_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // prepare and sign a donation, returning its TXID, transfer directory and signed PSBT
+    let begin_signed_send = |wallet: &mut Wallet, rcv_wallet: &mut Wallet, asset_id: &str| {
+        let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+        let recipient_map = HashMap::from([(
+            asset_id.to_string(),
+            vec![Recipient {
+                blinded_utxo: blind_data.blinded_utxo,
+                amount,
+                consignment_key: Some(blind_data.consignment_key),
+                transport_endpoints: vec![],
+            }],
+        )]);
+        let unsigned_psbt = wallet
+            .send_begin(online.clone(), recipient_map, true, send_options())
+            .unwrap();
+        let mut psbt = PartiallySignedTransaction::from_str(&unsigned_psbt).unwrap();
+        wallet
+            .bdk_wallet
+            .sign(&mut psbt, SignOptions::default())
+            .unwrap();
+        let txid = psbt.clone().extract_tx().txid().to_string();
+        let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR).join(&txid);
+        (txid, transfer_dir, psbt)
+    };
+    let is_saved = |wallet: &Wallet, txid: &str| {
+        wallet
+            .database
+            .iter_batch_transfers()
+            .unwrap()
+            .iter()
+            .any(|t| t.txid.as_deref() == Some(txid))
+    };
+
+    // send interrupted while posting transfer data goes back to its unsigned state
+    let (txid, transfer_dir, psbt) =
+        begin_signed_send(&mut wallet, &mut rcv_wallet, &asset.asset_id);
+    let journal = InfoSendJournal {
+        step: SendJournalStep::Posting,
+        posted_consignments: HashMap::new(),
+    };
+    wallet._write_send_journal(&transfer_dir, &journal).unwrap();
+    fs::write(transfer_dir.join(SIGNED_PSBT_FILE), psbt.to_string()).unwrap();
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    assert!(!transfer_dir.join(SIGNED_PSBT_FILE).exists());
+    assert!(!is_saved(&wallet, &txid));
+    wallet.abort_send(txid).unwrap();
+
+    // donation interrupted before broadcasting goes back to its unsigned state
+    let (txid, transfer_dir, psbt) =
+        begin_signed_send(&mut wallet, &mut rcv_wallet, &asset.asset_id);
+    let transfer_info_map = wallet._get_transfer_info_map(&transfer_dir).unwrap();
+    let journal = InfoSendJournal {
+        step: SendJournalStep::Saving,
+        posted_consignments: wallet
+            ._post_send_data(&transfer_dir, &transfer_info_map, false)
+            .unwrap(),
+    };
+    wallet._write_send_journal(&transfer_dir, &journal).unwrap();
+    fs::write(transfer_dir.join(SIGNED_PSBT_FILE), psbt.to_string()).unwrap();
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    assert!(!transfer_dir.join(SIGNED_PSBT_FILE).exists());
+    assert!(!is_saved(&wallet, &txid));
+
+    // the send can then be ended again
+    wallet.send_end(online.clone(), psbt.to_string()).unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    electrum.mine(1);
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::Settled
+    ));
+
+    // donation that cannot be checked is skipped until the electrum server is reachable
+    let (txid, transfer_dir, psbt) =
+        begin_signed_send(&mut wallet, &mut rcv_wallet, &asset.asset_id);
+    let transfer_info_map = wallet._get_transfer_info_map(&transfer_dir).unwrap();
+    let journal = InfoSendJournal {
+        step: SendJournalStep::Saving,
+        posted_consignments: wallet
+            ._post_send_data(&transfer_dir, &transfer_info_map, false)
+            .unwrap(),
+    };
+    wallet._write_send_journal(&transfer_dir, &journal).unwrap();
+    fs::write(transfer_dir.join(SIGNED_PSBT_FILE), psbt.to_string()).unwrap();
+    wallet.electrum_client = RefCell::new(None);
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    assert!(transfer_dir.join(SIGNED_PSBT_FILE).exists());
+    wallet._connect_electrum().unwrap();
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    assert!(!is_saved(&wallet, &txid));
+    wallet.abort_send(txid).unwrap();
+
+    // donation interrupted after broadcasting gets saved
+    let (txid, transfer_dir, psbt) =
+        begin_signed_send(&mut wallet, &mut rcv_wallet, &asset.asset_id);
+    let transfer_info_map = wallet._get_transfer_info_map(&transfer_dir).unwrap();
+    let journal = InfoSendJournal {
+        step: SendJournalStep::Saving,
+        posted_consignments: wallet
+            ._post_send_data(&transfer_dir, &transfer_info_map, false)
+            .unwrap(),
+    };
+    wallet._write_send_journal(&transfer_dir, &journal).unwrap();
+    fs::write(transfer_dir.join(SIGNED_PSBT_FILE), psbt.to_string()).unwrap();
+    wallet
+        ._bdk_blockchain()
+        .unwrap()
+        .broadcast(&psbt.extract_tx())
+        .unwrap();
+    let result = wallet._check_consistency();
+    assert!(matches!(result, Err(Error::Inconsistency(_))));
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    assert!(transfer_dir.join(SIGNED_PSBT_FILE).exists());
+    let batch_transfer = wallet.database.get_batch_transfer_or_fail(txid).unwrap();
+    assert_eq!(batch_transfer.status, TransferStatus::WaitingConfirmations);
+    wallet._check_consistency().unwrap();
+
+    // save failing halfway leaves no partial transfer behind and gets resumed
+    let (txid, transfer_dir, psbt) =
+        begin_signed_send(&mut wallet, &mut rcv_wallet, &asset.asset_id);
+    let transfer_info_map = wallet._get_transfer_info_map(&transfer_dir).unwrap();
+    let posted_consignments = wallet
+        ._post_send_data(&transfer_dir, &transfer_info_map, false)
+        .unwrap();
+    let journal = InfoSendJournal {
+        step: SendJournalStep::Saving,
+        posted_consignments: posted_consignments.clone(),
+    };
+    wallet._write_send_journal(&transfer_dir, &journal).unwrap();
+    fs::write(transfer_dir.join(SIGNED_PSBT_FILE), psbt.to_string()).unwrap();
+    let db_counts = |wallet: &Wallet| {
+        (
+            wallet.database.iter_batch_transfers().unwrap().len(),
+            wallet.database.iter_asset_transfers().unwrap().len(),
+            wallet.database.iter_colorings().unwrap().len(),
+            wallet.database.iter_transfers().unwrap().len(),
+        )
+    };
+    let counts_before = db_counts(&wallet);
+    // a non-existent change UTXO makes the change coloring insert fail after the other rows
+    let result = wallet._save_transfers(
+        txid.clone(),
+        transfer_info_map,
+        HashMap::new(),
+        -1,
+        posted_consignments,
+        TransferStatus::WaitingConfirmations,
+        1,
+        None,
+    );
+    assert!(result.is_err());
+    assert_eq!(db_counts(&wallet), counts_before);
+    assert!(!is_saved(&wallet, &txid));
+    wallet
+        ._bdk_blockchain()
+        .unwrap()
+        .broadcast(&psbt.extract_tx())
+        .unwrap();
+    wallet._resume_interrupted_sends().unwrap();
+    assert!(!transfer_dir.join(SEND_JOURNAL_FILE).exists());
+    let batch_transfer = wallet.database.get_batch_transfer_or_fail(txid).unwrap();
+    assert_eq!(batch_transfer.status, TransferStatus::WaitingConfirmations);
+    assert!(db_counts(&wallet).3 > counts_before.3);
+    wallet._check_consistency().unwrap();
+}
@@ -9,7 +9,7 @@ fn success() {
     let (mut wallet, online) = get_funded_wallet!();
 
     // add a pending operation to an UTXO so spendable balance will be != settled / future
-    let _blind_data = wallet.blind(None, None, None);
+    let _blind_data = wallet.blind(None, None, None, vec![], None);
 
     // required fields only
     println!("asset 1");
@@ -62,15 +62,20 @@ fn success() {
 
     // an unspent with a pending allocation
     let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             amount,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online, recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
     let unspent_list = rcv_wallet.list_unspents(true).unwrap();
     assert_eq!(unspent_list.len(), UTXO_NUM as usize + 1);
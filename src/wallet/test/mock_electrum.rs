@@ -0,0 +1,489 @@
+//! In-process stand-in for an Electrum server, backed by a fake chain
+//!
+//! [`MockElectrum`] speaks the subset of the Electrum protocol used by BDK, by the wallet and by
+//! the RGB validator. Tests drive the chain explicitly (funding, mining, reorgs), which makes the
+//! transfer state machine testable without the docker services.
+
+use amplify::s;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::consensus::encode::{deserialize, serialize_hex};
+use bitcoin::hashes::hex::{FromHex, ToHex};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::util::hash::bitcoin_merkle_root;
+use bitcoin::{
+    Address, BlockHeader, OutPoint, Script, Transaction, TxIn, TxMerkleNode, TxOut, Txid,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::utils::now;
+
+const NO_SUCH_TX: &str = "No such mempool or blockchain transaction";
+const DEFAULT_FEE_ESTIMATE: f64 = 0.00001;
+const BLOCK_INTERVAL: u32 = 600;
+
+struct MockBlock {
+    header: BlockHeader,
+    txids: Vec<Txid>,
+}
+
+struct ChainState {
+    blocks: Vec<MockBlock>,
+    mempool: Vec<Txid>,
+    txs: HashMap<Txid, Transaction>,
+    fee_estimate: f64,
+    funding_count: u32,
+}
+
+impl ChainState {
+    fn tip_height(&self) -> usize {
+        self.blocks.len() - 1
+    }
+
+    fn tx_height(&self, txid: &Txid) -> Option<usize> {
+        self.blocks.iter().position(|b| b.txids.contains(txid))
+    }
+
+    /// Transactions in the chain and in the mempool, in order
+    fn live_txids(&self) -> impl Iterator<Item = &Txid> {
+        self.blocks
+            .iter()
+            .flat_map(|b| b.txids.iter())
+            .chain(self.mempool.iter())
+    }
+
+    fn spender(&self, outpoint: &OutPoint) -> Option<Txid> {
+        self.live_txids()
+            .find(|txid| {
+                self.txs[txid]
+                    .input
+                    .iter()
+                    .any(|i| i.previous_output == *outpoint)
+            })
+            .copied()
+    }
+
+    fn add_block(&mut self, txids: Vec<Txid>) {
+        let prev = self.blocks.last().map(|b| b.header);
+        let merkle_root = bitcoin_merkle_root(txids.iter().map(|t| t.as_hash()))
+            .map(TxMerkleNode::from_hash)
+            .unwrap_or_default();
+        let header = BlockHeader {
+            version: 0x20000000,
+            prev_blockhash: prev.map(|h| h.block_hash()).unwrap_or_default(),
+            merkle_root,
+            time: prev
+                .map(|h| h.time + BLOCK_INTERVAL)
+                .unwrap_or(now().unix_timestamp() as u32),
+            bits: 0x207fffff,
+            nonce: self.blocks.len() as u32,
+        };
+        self.blocks.push(MockBlock { header, txids });
+    }
+
+    fn accept(&mut self, tx: Transaction) -> Result<Txid, String> {
+        let txid = tx.txid();
+        if self.live_txids().any(|t| *t == txid) {
+            return Ok(txid);
+        }
+        let mut seen = HashSet::new();
+        let mut replaced = HashSet::new();
+        for input in &tx.input {
+            let prevout = input.previous_output;
+            if !seen.insert(prevout) {
+                return Err(s!("bad-txns-inputs-duplicate"));
+            }
+            let prev_known = self.live_txids().any(|t| *t == prevout.txid)
+                && (prevout.vout as usize) < self.txs[&prevout.txid].output.len();
+            if !prev_known {
+                return Err(s!("bad-txns-inputs-missingorspent"));
+            }
+            if let Some(spender) = self.spender(&prevout) {
+                // BIP125: only unconfirmed transactions signaling replaceability can be replaced
+                let replaceable = self.mempool.contains(&spender)
+                    && self.txs[&spender]
+                        .input
+                        .iter()
+                        .any(|i| i.sequence <= 0xfffffffd);
+                if !replaceable {
+                    return Err(s!("txn-mempool-conflict"));
+                }
+                replaced.insert(spender);
+            }
+        }
+        if !replaced.is_empty() {
+            let replaced_fee: u64 = replaced.iter().map(|t| self.fee(&self.txs[t])).sum();
+            if self.fee(&tx) <= replaced_fee {
+                return Err(s!("insufficient fee"));
+            }
+            // evict the replaced transactions along with their descendants
+            let mut evicted = replaced;
+            loop {
+                let descendants: Vec<Txid> = self
+                    .mempool
+                    .iter()
+                    .filter(|t| !evicted.contains(*t))
+                    .filter(|t| {
+                        self.txs[t]
+                            .input
+                            .iter()
+                            .any(|i| evicted.contains(&i.previous_output.txid))
+                    })
+                    .copied()
+                    .collect();
+                if descendants.is_empty() {
+                    break;
+                }
+                evicted.extend(descendants);
+            }
+            self.mempool.retain(|t| !evicted.contains(t));
+        }
+        self.txs.insert(txid, tx);
+        self.mempool.push(txid);
+        Ok(txid)
+    }
+
+    fn fee(&self, tx: &Transaction) -> u64 {
+        let input_sum: u64 = tx
+            .input
+            .iter()
+            .map(|i| {
+                self.txs[&i.previous_output.txid].output[i.previous_output.vout as usize].value
+            })
+            .sum();
+        let output_sum: u64 = tx.output.iter().map(|o| o.value).sum();
+        input_sum - output_sum
+    }
+
+    fn tx_details(&self, txid: &Txid) -> Option<Value> {
+        if !self.live_txids().any(|t| t == txid) {
+            return None;
+        }
+        let mut details = json!({
+            "txid": txid.to_string(),
+            "hash": txid.to_string(),
+            "hex": serialize_hex(&self.txs[txid]),
+        });
+        if let Some(height) = self.tx_height(txid) {
+            let header = self.blocks[height].header;
+            details["blockhash"] = json!(header.block_hash().to_string());
+            details["confirmations"] = json!(self.tip_height() - height + 1);
+            details["time"] = json!(header.time);
+            details["blocktime"] = json!(header.time);
+        }
+        Some(details)
+    }
+
+    /// Outputs paying to the given script hash, with the height of their transaction (0 if
+    /// unconfirmed) and whether they have been spent
+    fn outputs(&self, script_hash: &str) -> Vec<(OutPoint, &TxOut, usize, bool)> {
+        let mut outputs = vec![];
+        for txid in self.live_txids() {
+            let height = self.tx_height(txid).unwrap_or(0);
+            for (vout, output) in self.txs[txid].output.iter().enumerate() {
+                if _script_hash(&output.script_pubkey) != script_hash {
+                    continue;
+                }
+                let outpoint = OutPoint::new(*txid, vout as u32);
+                let spent = self.spender(&outpoint).is_some();
+                outputs.push((outpoint, output, height, spent));
+            }
+        }
+        outputs
+    }
+
+    fn history(&self, script_hash: &str) -> Vec<Value> {
+        let mut txids: Vec<Txid> = self
+            .outputs(script_hash)
+            .iter()
+            .map(|(o, _, _, _)| o.txid)
+            .collect();
+        for txid in self.live_txids() {
+            let spends = self.txs[txid].input.iter().any(|i| {
+                self.txs.get(&i.previous_output.txid).map_or(false, |prev| {
+                    prev.output
+                        .get(i.previous_output.vout as usize)
+                        .map_or(false, |o| _script_hash(&o.script_pubkey) == script_hash)
+                })
+            });
+            if spends {
+                txids.push(*txid);
+            }
+        }
+        let mut seen = HashSet::new();
+        txids.retain(|t| seen.insert(*t));
+        txids.sort_by_key(|t| self.tx_height(t).unwrap_or(usize::MAX));
+        txids
+            .iter()
+            .map(|t| json!({"tx_hash": t.to_string(), "height": self.tx_height(t).unwrap_or(0)}))
+            .collect()
+    }
+}
+
+fn _script_hash(script: &Script) -> String {
+    let mut hash = sha256::Hash::hash(script.as_bytes()).into_inner();
+    hash.reverse();
+    hash.to_hex()
+}
+
+/// An Electrum server stand-in running in-process
+#[derive(Clone)]
+pub(crate) struct MockElectrum {
+    url: String,
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl MockElectrum {
+    /// Start a server with a chain containing only the genesis block
+    pub(crate) fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = listener.local_addr().unwrap().to_string();
+        let mut state = ChainState {
+            blocks: vec![],
+            mempool: vec![],
+            txs: HashMap::new(),
+            fee_estimate: DEFAULT_FEE_ESTIMATE,
+            funding_count: 0,
+        };
+        state.add_block(vec![]);
+        let mock = MockElectrum {
+            url,
+            state: Arc::new(Mutex::new(state)),
+        };
+        let server = mock.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let server = server.clone();
+                thread::spawn(move || server._serve(stream));
+            }
+        });
+        mock
+    }
+
+    pub(crate) fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self._state().tip_height()
+    }
+
+    /// Add to the mempool a transaction sending `amount` sats to `address`
+    pub(crate) fn fund(&self, address: &str, amount: u64) -> Txid {
+        let address = Address::from_str(address).expect("valid address");
+        let mut state = self._state();
+        state.funding_count += 1;
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_int(state.funding_count as i64)
+                    .into_script(),
+                sequence: 0xFFFFFFFF,
+                witness: Default::default(),
+            }],
+            output: vec![TxOut {
+                value: amount,
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let txid = tx.txid();
+        state.txs.insert(txid, tx);
+        state.mempool.push(txid);
+        txid
+    }
+
+    /// Mine `num` blocks, the first one including all the mempool transactions
+    pub(crate) fn mine(&self, num: usize) {
+        let mut state = self._state();
+        for _ in 0..num {
+            let txids = std::mem::take(&mut state.mempool);
+            state.add_block(txids);
+        }
+    }
+
+    /// Disconnect the last `depth` blocks, moving their transactions back to the mempool
+    pub(crate) fn reorg(&self, depth: usize) {
+        let mut state = self._state();
+        assert!(
+            depth <= state.tip_height(),
+            "cannot disconnect the genesis block"
+        );
+        let keep = state.blocks.len() - depth;
+        let mut txids: Vec<Txid> = state.blocks.drain(keep..).flat_map(|b| b.txids).collect();
+        txids.append(&mut state.mempool);
+        state.mempool = txids;
+    }
+
+    /// Return the number of confirmations of a transaction, None if it's unknown
+    pub(crate) fn confirmations(&self, txid: &Txid) -> Option<usize> {
+        let state = self._state();
+        if !state.live_txids().any(|t| t == txid) {
+            return None;
+        }
+        Some(
+            state
+                .tx_height(txid)
+                .map_or(0, |h| state.tip_height() - h + 1),
+        )
+    }
+
+    /// Return whether the transaction is in the mempool
+    pub(crate) fn in_mempool(&self, txid: &Txid) -> bool {
+        self._state().mempool.contains(txid)
+    }
+
+    /// Set the answer to fee estimation requests, in BTC/kvB (negative means no estimate)
+    pub(crate) fn set_fee_estimate(&self, fee_estimate: f64) {
+        self._state().fee_estimate = fee_estimate;
+    }
+
+    fn _state(&self) -> MutexGuard<ChainState> {
+        self.state
+            .lock()
+            .expect("chain state lock should not be poisoned")
+    }
+
+    fn _serve(&self, stream: TcpStream) {
+        let mut writer = match stream.try_clone() {
+            Ok(w) => w,
+            Err(_) => return,
+        };
+        for line in BufReader::new(stream).lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => break,
+            };
+            let request: Value = match serde_json::from_str(&line) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let method = request["method"].as_str().unwrap_or_default();
+            let params = request["params"].as_array().cloned().unwrap_or_default();
+            let response = match self._handle(method, &params) {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+                Err(message) => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": {"code": 2, "message": message},
+                }),
+            };
+            let mut reply = response.to_string();
+            reply.push('\n');
+            if writer.write_all(reply.as_bytes()).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn _handle(&self, method: &str, params: &[Value]) -> Result<Value, String> {
+        let param_str = |i: usize| -> Result<&str, String> {
+            params
+                .get(i)
+                .and_then(|p| p.as_str())
+                .ok_or_else(|| format!("missing parameter {}", i))
+        };
+        let param_u64 = |i: usize| -> Result<u64, String> {
+            params
+                .get(i)
+                .and_then(|p| p.as_u64())
+                .ok_or_else(|| format!("missing parameter {}", i))
+        };
+        let mut state = self._state();
+        let result = match method {
+            "server.version" => json!(["MockElectrum", "1.4"]),
+            "server.ping" => Value::Null,
+            "server.features" => json!({
+                "genesis_hash": state.blocks[0].header.block_hash().to_string(),
+                "hosts": {},
+                "protocol_max": "1.4",
+                "protocol_min": "1.4",
+                "server_version": "MockElectrum",
+                "hash_function": "sha256",
+                "pruning": null,
+            }),
+            "blockchain.headers.subscribe" => json!({
+                "height": state.tip_height(),
+                "hex": serialize_hex(&state.blocks[state.tip_height()].header),
+            }),
+            "blockchain.block.header" => {
+                let height = param_u64(0)? as usize;
+                let block = state
+                    .blocks
+                    .get(height)
+                    .ok_or_else(|| format!("height {} out of range", height))?;
+                json!(serialize_hex(&block.header))
+            }
+            "blockchain.block.headers" => {
+                let start = param_u64(0)? as usize;
+                let count = param_u64(1)? as usize;
+                let headers: Vec<String> = state
+                    .blocks
+                    .iter()
+                    .skip(start)
+                    .take(count)
+                    .map(|b| serialize_hex(&b.header))
+                    .collect();
+                json!({"count": headers.len(), "hex": headers.concat(), "max": 2016})
+            }
+            "blockchain.estimatefee" => json!(state.fee_estimate),
+            "blockchain.relayfee" => json!(DEFAULT_FEE_ESTIMATE),
+            "blockchain.scripthash.subscribe" => Value::Null,
+            "blockchain.scripthash.get_history" => json!(state.history(param_str(0)?)),
+            "blockchain.scripthash.listunspent" => {
+                let unspents: Vec<Value> = state
+                    .outputs(param_str(0)?)
+                    .into_iter()
+                    .filter(|(_, _, _, spent)| !spent)
+                    .map(|(outpoint, output, height, _)| {
+                        json!({
+                            "tx_hash": outpoint.txid.to_string(),
+                            "tx_pos": outpoint.vout,
+                            "height": height,
+                            "value": output.value,
+                        })
+                    })
+                    .collect();
+                json!(unspents)
+            }
+            "blockchain.scripthash.get_balance" => {
+                let (mut confirmed, mut unconfirmed) = (0, 0);
+                for (_, output, height, spent) in state.outputs(param_str(0)?) {
+                    if spent {
+                        continue;
+                    }
+                    if height > 0 {
+                        confirmed += output.value;
+                    } else {
+                        unconfirmed += output.value;
+                    }
+                }
+                json!({"confirmed": confirmed, "unconfirmed": unconfirmed})
+            }
+            "blockchain.transaction.get" => {
+                let txid = Txid::from_str(param_str(0)?).map_err(|e| e.to_string())?;
+                let verbose = params.get(1).and_then(|p| p.as_bool()).unwrap_or(false);
+                let details = state.tx_details(&txid).ok_or_else(|| s!(NO_SUCH_TX))?;
+                if verbose {
+                    details
+                } else {
+                    details["hex"].clone()
+                }
+            }
+            "blockchain.transaction.broadcast" => {
+                let bytes = Vec::<u8>::from_hex(param_str(0)?).map_err(|e| e.to_string())?;
+                let tx: Transaction = deserialize(&bytes).map_err(|e| e.to_string())?;
+                json!(state.accept(tx)?.to_string())
+            }
+            _ => return Err(format!("unsupported method {}", method)),
+        };
+        Ok(result)
+    }
+}
@@ -2,18 +2,25 @@ use amplify::s;
 use std::process::{Command, Stdio};
 use std::sync::Once;
 
+use crate::api::mock::MockProxy;
 use crate::generate_keys;
+use mock_electrum::MockElectrum;
 
 use super::*;
 
-const PROXY_URL: &str = "http://proxy.rgbtools.org";
+const PROXY_ADDR: &str = "127.0.0.1:3033";
+const PROXY_URL: &str = "http://127.0.0.1:3033";
 const ELECTRUM_URL: &str = "127.0.0.1:50001";
 const TEST_DATA_DIR: &str = "./tests/tmp";
+// not removed by start_services.sh, as tests on mocks don't wait for it
+const MOCK_TEST_DATA_DIR: &str = "./tests/tmp_mocks";
 const TICKER: &str = "TICKER";
 const NAME: &str = "name";
 const DESCRIPTION: &str = "DESCRIPTION";
 const PRECISION: u8 = 7;
 const AMOUNT: u64 = 666;
+const FEE_RATE: Option<f32> = Some(1.5);
+const MIN_CONFIRMATIONS: u8 = 1;
 
 static INIT: Once = Once::new();
 
@@ -69,12 +76,22 @@ pub fn initialize() {
             .status()
             .expect("failed to start test services");
         assert!(status.success());
+        // in-process proxy shared by all tests, faults should be injected in dedicated ones
+        MockProxy::start_on(PROXY_ADDR);
     });
 }
 
 // return a regtest wallet for testing.
 fn get_test_wallet(private_keys: bool) -> Wallet {
-    let tests_data = TEST_DATA_DIR;
+    get_test_wallet_in(TEST_DATA_DIR, private_keys)
+}
+
+// return a regtest wallet for tests using the in-process electrum and proxy stand-ins
+fn get_test_wallet_on_mocks(private_keys: bool) -> Wallet {
+    get_test_wallet_in(MOCK_TEST_DATA_DIR, private_keys)
+}
+
+fn get_test_wallet_in(tests_data: &str, private_keys: bool) -> Wallet {
     fs::create_dir_all(tests_data).unwrap();
 
     let bitcoin_network = BitcoinNetwork::Regtest;
@@ -89,6 +106,8 @@ fn get_test_wallet(private_keys: bool) -> Wallet {
         database_type: DatabaseType::Sqlite,
         pubkey: keys.xpub,
         mnemonic,
+        min_confirmations: MIN_CONFIRMATIONS,
+        begun_send_duration: None,
     })
     .unwrap()
 }
@@ -100,7 +119,13 @@ fn get_empty_wallet(print_log: bool, private_keys: bool) -> (Wallet, Online) {
         println!("wallet directory: {:?}", wallet.get_wallet_dir());
     }
     let online = wallet
-        .go_online(true, ELECTRUM_URL.to_string(), PROXY_URL.to_string())
+        .go_online(
+            true,
+            ELECTRUM_URL.to_string(),
+            vec![PROXY_URL.to_string()],
+            None,
+            None,
+        )
         .unwrap();
     (wallet, online)
 }
@@ -131,7 +156,7 @@ macro_rules! get_funded_noutxo_wallet {
 fn get_funded_wallet(print_log: bool, private_keys: bool) -> (Wallet, Online) {
     let (mut wallet, online) = get_funded_noutxo_wallet(print_log, private_keys);
     wallet
-        .create_utxos(online.clone(), false, None, None)
+        .create_utxos(online.clone(), false, None, None, FEE_RATE)
         .unwrap();
     (wallet, online)
 }
@@ -144,6 +169,56 @@ macro_rules! get_funded_wallet {
     };
 }
 
+// return a funded wallet with UTXOs, using the given proxy instead of the shared one
+fn get_funded_wallet_on_proxy(proxy: &MockProxy) -> (Wallet, Online) {
+    let mut wallet = get_test_wallet(true);
+    let online = wallet
+        .go_online(
+            true,
+            ELECTRUM_URL.to_string(),
+            vec![proxy.url()],
+            None,
+            None,
+        )
+        .unwrap();
+    fund_wallet(wallet.get_address());
+    mine();
+    wallet
+        .create_utxos(online.clone(), false, None, None, FEE_RATE)
+        .unwrap();
+    (wallet, online)
+}
+
+// return a funded wallet with UTXOs, using the in-process electrum and proxy stand-ins, so
+// docker services are not needed
+fn get_funded_wallet_on_mocks(electrum: &MockElectrum, proxy: &MockProxy) -> (Wallet, Online) {
+    get_funded_wallet_on_mock_proxies(electrum, vec![proxy.url()])
+}
+
+fn get_funded_wallet_on_mock_proxies(
+    electrum: &MockElectrum,
+    proxy_urls: Vec<String>,
+) -> (Wallet, Online) {
+    let mut wallet = get_test_wallet_on_mocks(true);
+    let online = wallet
+        .go_online(true, electrum.url(), proxy_urls, None, None)
+        .unwrap();
+    electrum.fund(&wallet.get_address(), 100_000_000);
+    electrum.mine(1);
+    wallet
+        .create_utxos(online.clone(), false, None, None, FEE_RATE)
+        .unwrap();
+    (wallet, online)
+}
+
+// return the send options used by tests, paying the test fee rate
+fn send_options() -> SendOptions {
+    SendOptions {
+        fee_rate: FEE_RATE,
+        ..Default::default()
+    }
+}
+
 fn check_test_transfer_status_recipient(
     wallet: &Wallet,
     blinded_utxo: &str,
@@ -349,10 +424,18 @@ fn show_unspent_colorings(wallet: &Wallet, msg: &str) {
     }
 }
 
+mod abort_send;
+mod async_wallet;
+mod auto_refresh;
 mod blind;
+mod bump_fee;
+mod cpfp;
 mod create_utxos;
 mod delete_transfers;
 mod drain_to;
+mod estimate_fee;
+mod extend_blind_expiration;
+mod extend_send_expiration;
 mod fail_transfers;
 mod get_address;
 mod get_asset_balance;
@@ -363,6 +446,9 @@ mod issue_asset_rgb20;
 mod list_assets;
 mod list_transfers;
 mod list_unspents;
+mod mock_electrum;
 mod new;
+mod preview_send;
 mod refresh;
 mod send;
+mod set_transfer_listener;
@@ -46,6 +46,8 @@ fn testnet_success() {
         database_type: DatabaseType::Sqlite,
         pubkey: keys.xpub.clone(),
         mnemonic: Some(keys.mnemonic.clone()),
+        min_confirmations: MIN_CONFIRMATIONS,
+        begun_send_duration: None,
     })
     .unwrap();
     check_wallet(&wallet, DescriptorType::Wpkh, bitcoin_network);
@@ -66,6 +68,8 @@ fn mainnet_success() {
         database_type: DatabaseType::Sqlite,
         pubkey: keys.xpub.clone(),
         mnemonic: Some(keys.mnemonic.clone()),
+        min_confirmations: MIN_CONFIRMATIONS,
+        begun_send_duration: None,
     })
     .unwrap();
     check_wallet(&wallet, DescriptorType::Wpkh, bitcoin_network);
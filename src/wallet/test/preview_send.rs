@@ -0,0 +1,169 @@
+use bdk::database::Database;
+
+use super::*;
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue asset A, then asset B on the same UTXO
+    let asset_a = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let asset_b = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let unspents = wallet.list_unspents(false).unwrap();
+    let asset_utxo = unspents
+        .iter()
+        .find(|u| {
+            u.rgb_allocations
+                .iter()
+                .any(|a| a.asset_id == Some(asset_a.asset_id.clone()))
+        })
+        .unwrap()
+        .utxo
+        .outpoint
+        .clone();
+    let count_transfer_dirs = |wallet: &Wallet| {
+        fs::read_dir(wallet.get_wallet_dir().join(TRANSFER_DIR))
+            .map(|d| d.count())
+            .unwrap_or(0)
+    };
+    let derivation_indexes = |wallet: &Wallet| {
+        let db = wallet.bdk_wallet.database();
+        (
+            db.get_last_index(KeychainKind::External).unwrap(),
+            db.get_last_index(KeychainKind::Internal).unwrap(),
+        )
+    };
+    let num_batch_transfers = wallet.database.iter_batch_transfers().unwrap().len();
+    let num_transfer_dirs = count_transfer_dirs(&wallet);
+    let indexes = derivation_indexes(&wallet);
+
+    // preview sending part of asset A, which moves asset B with a blank transition
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset_a.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let preview = wallet
+        .preview_send(online.clone(), recipient_map.clone(), send_options())
+        .unwrap();
+    assert_eq!(
+        preview.asset_inputs,
+        HashMap::from([(asset_a.asset_id.clone(), vec![asset_utxo.clone()])])
+    );
+    assert!(preview.btc_inputs.is_empty());
+    assert!(preview.fee.unwrap() > 0);
+    assert!(preview.change_utxo.is_some());
+    assert_ne!(preview.change_utxo, Some(asset_utxo));
+    assert_eq!(
+        preview.change_allocations,
+        HashMap::from([(asset_a.asset_id.clone(), AMOUNT - amount)])
+    );
+    assert_eq!(
+        preview.blank_allocations,
+        HashMap::from([(asset_b.asset_id.clone(), AMOUNT)])
+    );
+    assert!(!preview.new_utxos_needed);
+
+    // paying a BTC recipient spends UTXOs without allocations too
+    let btc_recipients = vec![BtcRecipient {
+        address: rcv_wallet.get_address(),
+        amount: 1500,
+    }];
+    let btc_preview = wallet
+        .preview_send(
+            online.clone(),
+            recipient_map.clone(),
+            SendOptions {
+                btc_recipients,
+                ..send_options()
+            },
+        )
+        .unwrap();
+    assert_eq!(btc_preview.btc_inputs.len(), 1);
+    assert!(!btc_preview.new_utxos_needed);
+
+    // new UTXOs are needed when the available ones can't cover the BTC recipients
+    let btc_recipients = vec![BtcRecipient {
+        address: rcv_wallet.get_address(),
+        amount: 10_000,
+    }];
+    let btc_preview = wallet
+        .preview_send(
+            online.clone(),
+            recipient_map.clone(),
+            SendOptions {
+                btc_recipients,
+                ..send_options()
+            },
+        )
+        .unwrap();
+    assert!(btc_preview.fee.is_none());
+    assert!(btc_preview.new_utxos_needed);
+
+    // previews leave no trace
+    assert_eq!(
+        wallet.database.iter_batch_transfers().unwrap().len(),
+        num_batch_transfers
+    );
+    assert_eq!(count_transfer_dirs(&wallet), num_transfer_dirs);
+    // no external or internal addresses have been derived
+    assert_eq!(derivation_indexes(&wallet), indexes);
+
+    // the actual send pays the previewed fee
+    let send_result = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap();
+    assert_eq!(Some(send_result.fee), preview.fee);
+}
+
+#[test]
+fn fail() {
+    initialize();
+
+    let (wallet, online) = get_empty_wallet!();
+
+    // unknown asset
+    let recipient_map = HashMap::from([(
+        s!("rgb1inexistent"),
+        vec![Recipient {
+            blinded_utxo: s!("blinded"),
+            amount: 66,
+            consignment_key: None,
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let result = wallet.preview_send(online.clone(), recipient_map, send_options());
+    assert!(matches!(result, Err(Error::AssetNotFound(_))));
+
+    // bad online object
+    let (_other_wallet, other_online) = get_empty_wallet!();
+    let result = wallet.preview_send(other_online, HashMap::new(), send_options());
+    assert!(matches!(result, Err(Error::InvalidOnline())));
+}
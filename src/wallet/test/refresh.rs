@@ -1,3 +1,5 @@
+use crate::api::mock::Fault;
+
 use super::*;
 
 #[test]
@@ -10,3 +12,648 @@ fn fail() {
     let result = wallet.refresh(online, Some(s!("rgb1inexistent")));
     assert!(matches!(result, Err(Error::AssetNotFound(_))));
 }
+
+#[test]
+fn proxy_faults() {
+    initialize();
+
+    let amount: u64 = 66;
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_proxy(&proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_proxy(&proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send, with a transient proxy failure that gets retried
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    proxy.inject_fault("consignment", Fault::ServerError, 1);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    assert!(!txid.is_empty());
+    assert!(proxy.consignment(&blind_data.blinded_utxo).is_some());
+
+    // refreshing receiver fails if the proxy returns a malformed response
+    proxy.inject_fault("consignment", Fault::MalformedJson, 1);
+    let report = rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert_eq!(report.len(), 1);
+    assert!(report[0].failure.is_some());
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingCounterparty
+    ));
+
+    // receiver ACK gets lost, so the sender keeps waiting for the counterparty
+    proxy.inject_fault("ack", Fault::LostAck, 1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), None);
+    wallet.refresh(online, Some(asset.asset_id)).unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingCounterparty
+    ));
+}
+
+#[test]
+fn confirmations_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    let tx_id = Txid::from_str(&txid).unwrap();
+    // the transaction is broadcast only after the receiver ACKs
+    assert_eq!(electrum.confirmations(&tx_id), None);
+
+    // after the ACK the transaction is broadcast, transfers wait for confirmations
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(electrum.in_mempool(&tx_id));
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // refreshing without new blocks doesn't change the status
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // a confirmation undone by a reorg doesn't settle the transfers
+    electrum.mine(1);
+    electrum.reorg(1);
+    assert_eq!(electrum.confirmations(&tx_id), Some(0));
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // transfers settle once the transaction is confirmed
+    let height = electrum.height();
+    electrum.mine(1);
+    assert_eq!(electrum.height(), height + 1);
+    assert_eq!(electrum.confirmations(&tx_id), Some(1));
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::Settled
+    ));
+    let rcv_assets = rcv_wallet.list_assets(vec![]).unwrap();
+    let rcv_asset = rcv_assets.rgb20.unwrap();
+    assert_eq!(rcv_asset.len(), 1);
+    assert_eq!(rcv_asset[0].balance.settled, amount);
+    let balance = wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(balance.settled, AMOUNT - amount);
+}
+
+#[test]
+fn min_confirmations_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the receiver requires 3 confirmations, the sender none
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], Some(3)).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(
+            online.clone(),
+            recipient_map,
+            false,
+            SendOptions {
+                min_confirmations: Some(0),
+                ..send_options()
+            },
+        )
+        .unwrap()
+        .txid;
+    let rcv_batch_transfer = rcv_wallet
+        .database
+        .iter_batch_transfers()
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(rcv_batch_transfer.min_confirmations, 3);
+    let batch_transfer = get_test_batch_transfers(&wallet, &txid);
+    assert_eq!(batch_transfer[0].min_confirmations, 0);
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    let tx_id = Txid::from_str(&txid).unwrap();
+    assert!(electrum.in_mempool(&tx_id));
+
+    // the sender settles with the transaction still unconfirmed
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::Settled
+    ));
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // the receiver settles only after the third confirmation
+    electrum.mine(2);
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    let rcv_balance = rcv_wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(rcv_balance.settled, amount);
+}
+
+#[test]
+fn zero_confirmations_rbf_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the receiver requires no confirmations
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], Some(0)).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    let tx_id = Txid::from_str(&txid).unwrap();
+    assert!(electrum.in_mempool(&tx_id));
+
+    // the transaction can still be replaced, so the receiver waits for a confirmation
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    let rcv_balance = rcv_wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(rcv_balance.settled, amount);
+}
+
+#[test]
+fn proxy_failover_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy_1 = MockProxy::start();
+    let proxy_2 = MockProxy::start();
+
+    // wallets, using both proxies
+    let proxy_urls = vec![proxy_1.url(), proxy_2.url()];
+    let (mut wallet, online) = get_funded_wallet_on_mock_proxies(&electrum, proxy_urls.clone());
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mock_proxies(&electrum, proxy_urls);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the consignment is posted to the second proxy, as the first one is failing
+    proxy_1.inject_fault(
+        "consignment",
+        Fault::ServerError,
+        NETWORK_RETRIES as usize + 1,
+    );
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    assert!(proxy_1.consignment(&blind_data.blinded_utxo).is_none());
+    assert!(proxy_2.consignment(&blind_data.blinded_utxo).is_some());
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
+    assert_eq!(transfer.proxy_url, Some(proxy_2.url()));
+
+    // the receiver checks both proxies and records the one the consignment was found on
+    assert!(
+        get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo)
+            .proxy_url
+            .is_none()
+    );
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo);
+    assert_eq!(rcv_transfer.proxy_url, Some(proxy_2.url()));
+    assert_eq!(proxy_2.ack(&blind_data.blinded_utxo), Some(true));
+
+    // the sender gets the ACK from the proxy the consignment was posted to
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingConfirmations
+    ));
+    let transfers = wallet.list_transfers(asset.asset_id).unwrap();
+    let transfer = transfers.iter().find(|t| t.txid == Some(txid.clone()));
+    assert_eq!(transfer.unwrap().proxy_url, Some(proxy_2.url()));
+}
+
+#[test]
+fn report_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    wallet
+        .create_utxos(online.clone(), false, Some(UTXO_NUM), None, FEE_RATE)
+        .unwrap();
+    electrum.mine(1);
+
+    // issue, with an allocation for each send
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT, AMOUNT],
+        )
+        .unwrap();
+
+    // send twice
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let mut txids = vec![];
+    for blind_data in [&blind_data_1, &blind_data_2] {
+        let recipient_map = HashMap::from([(
+            asset.asset_id.clone(),
+            vec![Recipient {
+                amount,
+                blinded_utxo: blind_data.blinded_utxo.clone(),
+                consignment_key: Some(blind_data.consignment_key.clone()),
+                transport_endpoints: vec![],
+            }],
+        )]);
+        txids.push(
+            wallet
+                .send(online.clone(), recipient_map, false, send_options())
+                .unwrap()
+                .txid,
+        );
+    }
+
+    // nothing changes for the sender before the receiver ACKs
+    let report = wallet.refresh(online.clone(), None).unwrap();
+    assert_eq!(report.len(), 2);
+    for (refreshed, txid) in report.iter().zip(&txids) {
+        assert_eq!(refreshed.txid, Some(txid.clone()));
+        assert_eq!(refreshed.blinded_utxo, None);
+        assert_eq!(refreshed.old_status, TransferStatus::WaitingCounterparty);
+        assert_eq!(refreshed.new_status, TransferStatus::WaitingCounterparty);
+        assert_eq!(refreshed.failure, None);
+    }
+
+    // an error getting the first consignment doesn't prevent getting the second one
+    proxy.inject_fault(
+        "consignment",
+        Fault::ServerError,
+        NETWORK_RETRIES as usize + 1,
+    );
+    let report = rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(
+        report[0].blinded_utxo,
+        Some(blind_data_1.blinded_utxo.clone())
+    );
+    assert_eq!(report[0].old_status, TransferStatus::WaitingCounterparty);
+    assert_eq!(report[0].new_status, TransferStatus::WaitingCounterparty);
+    assert!(report[0].failure.is_some());
+    assert_eq!(
+        report[1].blinded_utxo,
+        Some(blind_data_2.blinded_utxo.clone())
+    );
+    assert_eq!(report[1].txid, Some(txids[1].clone()));
+    assert_eq!(report[1].old_status, TransferStatus::WaitingCounterparty);
+    assert_eq!(report[1].new_status, TransferStatus::WaitingConfirmations);
+    assert_eq!(report[1].failure, None);
+
+    // the first consignment is got at the next refresh
+    let report = rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].blinded_utxo, Some(blind_data_1.blinded_utxo));
+    assert_eq!(report[0].new_status, TransferStatus::WaitingConfirmations);
+    assert_eq!(report[0].failure, None);
+    assert_eq!(report[1].old_status, TransferStatus::WaitingConfirmations);
+    assert_eq!(report[1].new_status, TransferStatus::WaitingConfirmations);
+    let report = wallet.refresh(online, None).unwrap();
+    assert!(report
+        .iter()
+        .all(|t| t.new_status == TransferStatus::WaitingConfirmations));
+}
+
+#[test]
+fn retry_without_nacks_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet_1, rcv_online_1) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet_2, _rcv_online_2) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send to both receivers, retrying without the ones that NACK
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![
+            Recipient {
+                amount,
+                blinded_utxo: blind_data_1.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
+            },
+            Recipient {
+                amount,
+                blinded_utxo: blind_data_2.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
+            },
+        ],
+    )]);
+    let txid = wallet
+        .send(
+            online.clone(),
+            recipient_map,
+            false,
+            SendOptions {
+                retry_without_nacks: true,
+                ..send_options()
+            },
+        )
+        .unwrap()
+        .txid;
+
+    // the first receiver ACKs, the second one NACKs
+    rcv_wallet_1.refresh(rcv_online_1.clone(), None).unwrap();
+    rcv_wallet_2
+        .rest_client
+        .post_nack(&proxy.url(), blind_data_2.blinded_utxo.clone())
+        .unwrap();
+
+    // the transfer fails and is sent again to the first receiver only
+    let report = wallet.refresh(online.clone(), None).unwrap();
+    let refreshed = report
+        .iter()
+        .find(|t| t.txid == Some(txid.clone()))
+        .unwrap();
+    assert_eq!(refreshed.new_status, TransferStatus::Failed);
+    assert_eq!(refreshed.failure, None);
+    let retried = refreshed.retried.clone().unwrap();
+    assert_ne!(retried.txid, txid);
+    assert_eq!(
+        retried.dropped_blinded_utxos,
+        vec![blind_data_2.blinded_utxo.clone()]
+    );
+    assert_eq!(retried.unsigned_psbt, None);
+    let transfers = wallet
+        .database
+        .iter_batch_transfer_transfers(&get_test_batch_transfers(&wallet, &retried.txid)[0])
+        .unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(
+        transfers[0].blinded_utxo,
+        Some(blind_data_1.blinded_utxo.clone())
+    );
+    assert_eq!(transfers[0].ack, None);
+    let replacement_id = format!("{}_1", blind_data_1.blinded_utxo);
+    assert_eq!(transfers[0].recipient_id, Some(replacement_id.clone()));
+    assert!(proxy.consignment(&replacement_id).is_some());
+
+    // the new transaction is not broadcast until the first receiver ACKs the new consignment
+    wallet.refresh(online.clone(), None).unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &retried.txid,
+        TransferStatus::WaitingCounterparty
+    ));
+    let retried_txid = Txid::from_str(&retried.txid).unwrap();
+    assert!(!electrum.in_mempool(&retried_txid));
+
+    // the first receiver switches to the new transfer and ACKs it
+    rcv_wallet_1.refresh(rcv_online_1.clone(), None).unwrap();
+    assert_eq!(
+        get_test_batch_transfers(&rcv_wallet_1, &retried.txid).len(),
+        1
+    );
+    assert_eq!(proxy.ack(&replacement_id), Some(true));
+
+    // the new transaction is then broadcast and both sides settle
+    wallet.refresh(online.clone(), None).unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &retried.txid,
+        TransferStatus::WaitingConfirmations
+    ));
+    assert!(electrum.in_mempool(&retried_txid));
+    electrum.mine(1);
+    rcv_wallet_1.refresh(rcv_online_1, None).unwrap();
+    wallet.refresh(online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet_1,
+        &blind_data_1.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &retried.txid,
+        TransferStatus::Settled
+    ));
+}
@@ -24,15 +24,20 @@ fn success() {
         .unwrap();
 
     // send
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount,
             blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo);
@@ -73,7 +78,7 @@ fn success() {
     );
     assert_eq!(
         transfer_data.expiration,
-        Some(transfer_data.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data.created_at + DURATION_SEND_TRANSFER as i64)
     );
     // transfer is incoming for receiver and outgoing for sender
     assert!(rcv_transfer_data.incoming);
@@ -230,15 +235,20 @@ fn spend_all() {
     assert!(allocation_asset_ids.contains(&asset_blank.asset_id));
 
     // send
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount: AMOUNT,
             blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &blind_data.blinded_utxo);
@@ -365,15 +375,20 @@ fn send_twice_success() {
     //
 
     // send
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount: amount_1,
             blinded_utxo: blind_data_1.blinded_utxo.clone(),
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_1 = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid_1 = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -417,15 +432,20 @@ fn send_twice_success() {
     //
 
     // send
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount: amount_2,
             blinded_utxo: blind_data_2.blinded_utxo.clone(),
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_2 = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid_2 = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -550,17 +570,20 @@ fn send_blank_success() {
 
     // send
     println!("\n=== send 1");
-    let blind_data_1 = wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_1.blinded_utxo,
             amount: amount_1,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid_1 = wallet_1
-        .send(online_1.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_1.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -641,18 +664,21 @@ fn send_blank_success() {
     //
 
     // send
-    let blind_data_2 = wallet_2.blind(None, None, None).unwrap();
+    let blind_data_2 = wallet_2.blind(None, None, None, vec![], None).unwrap();
     println!("\n=== send 2");
     let recipient_map = HashMap::from([(
         asset_rgb121.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_2.blinded_utxo,
             amount: amount_2,
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid_2 = wallet_1
-        .send(online_1.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_1.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -771,14 +797,16 @@ fn send_received_success() {
     //
 
     // send
-    let blind_data_a20 = wallet_2.blind(None, None, None).unwrap();
-    let blind_data_a121 = wallet_2.blind(None, None, None).unwrap();
+    let blind_data_a20 = wallet_2.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_a121 = wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([
         (
             asset_rgb20.asset_id.clone(),
             vec![Recipient {
                 blinded_utxo: blind_data_a20.blinded_utxo.clone(),
                 amount: amount_1a,
+                consignment_key: Some(blind_data_a20.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
         (
@@ -786,12 +814,15 @@ fn send_received_success() {
             vec![Recipient {
                 blinded_utxo: blind_data_a121.blinded_utxo.clone(),
                 amount: amount_1b,
+                consignment_key: Some(blind_data_a121.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
     ]);
     let txid_1 = wallet_1
-        .send(online_1.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_1.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -847,14 +878,16 @@ fn send_received_success() {
     //
 
     // send
-    let blind_data_b20 = wallet_3.blind(None, None, None).unwrap();
-    let blind_data_b121 = wallet_3.blind(None, None, None).unwrap();
+    let blind_data_b20 = wallet_3.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_b121 = wallet_3.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([
         (
             asset_rgb20.asset_id.clone(),
             vec![Recipient {
                 blinded_utxo: blind_data_b20.blinded_utxo.clone(),
                 amount: amount_2a,
+                consignment_key: Some(blind_data_b20.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
         (
@@ -862,12 +895,15 @@ fn send_received_success() {
             vec![Recipient {
                 blinded_utxo: blind_data_b121.blinded_utxo.clone(),
                 amount: amount_2b,
+                consignment_key: Some(blind_data_b121.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
     ]);
     let txid_2 = wallet_2
-        .send(online_2.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_2.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -973,17 +1009,20 @@ fn send_received_rgb121_success() {
     //
 
     // send
-    let blind_data_1 = wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_1.blinded_utxo.clone(),
             amount: amount_1,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid_1 = wallet_1
-        .send(online_1.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_1.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1024,17 +1063,20 @@ fn send_received_rgb121_success() {
     //
 
     // send
-    let blind_data_2 = wallet_3.blind(None, None, None).unwrap();
+    let blind_data_2 = wallet_3.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data_2.blinded_utxo.clone(),
             amount: amount_2,
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid_2 = wallet_2
-        .send(online_2.clone(), recipient_map, false)
-        .unwrap();
+        .send(online_2.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1130,22 +1172,29 @@ fn receive_multiple_same_asset_success() {
         .unwrap();
 
     // send
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![
             Recipient {
                 amount: amount_1,
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 amount: amount_2,
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     let rcv_transfer_1 = get_test_transfer_recipient(&rcv_wallet, &blind_data_1.blinded_utxo);
@@ -1246,11 +1295,11 @@ fn receive_multiple_same_asset_success() {
     );
     assert_eq!(
         transfer_data_1.expiration,
-        Some(transfer_data_1.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data_1.created_at + DURATION_SEND_TRANSFER as i64)
     );
     assert_eq!(
         transfer_data_2.expiration,
-        Some(transfer_data_2.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data_2.created_at + DURATION_SEND_TRANSFER as i64)
     );
     // transfer is incoming for receiver and outgoing for sender
     assert!(rcv_transfer_data_1.incoming);
@@ -1448,14 +1497,16 @@ fn receive_multiple_different_assets_success() {
         .unwrap();
 
     // send
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([
         (
             asset_1.asset_id.clone(),
             vec![Recipient {
                 amount: amount_1,
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
         (
@@ -1463,10 +1514,15 @@ fn receive_multiple_different_assets_success() {
             vec![Recipient {
                 amount: amount_2,
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
     ]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     let rcv_transfer_1 = get_test_transfer_recipient(&rcv_wallet, &blind_data_1.blinded_utxo);
@@ -1570,11 +1626,11 @@ fn receive_multiple_different_assets_success() {
     );
     assert_eq!(
         transfer_data_1.expiration,
-        Some(transfer_data_1.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data_1.created_at + DURATION_SEND_TRANSFER as i64)
     );
     assert_eq!(
         transfer_data_2.expiration,
-        Some(transfer_data_2.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data_2.created_at + DURATION_SEND_TRANSFER as i64)
     );
     // transfers are incoming for receiver and outgoing for sender
     assert!(rcv_transfer_data_1.incoming);
@@ -1839,10 +1895,10 @@ fn batch_donation_success() {
     assert!(allocation_asset_ids.contains(&asset_c.asset_id));
 
     // blind
-    let blind_data_a1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_a2 = rcv_wallet_2.blind(None, None, None).unwrap();
-    let blind_data_b1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_b2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_a1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_a2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_b1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_b2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
 
     // send multiple assets to multiple recipients
     let recipient_map = HashMap::from([
@@ -1852,10 +1908,14 @@ fn batch_donation_success() {
                 Recipient {
                     blinded_utxo: blind_data_a1.blinded_utxo,
                     amount: amount_a1,
+                    consignment_key: Some(blind_data_a1.consignment_key.clone()),
+                    transport_endpoints: vec![],
                 },
                 Recipient {
                     blinded_utxo: blind_data_a2.blinded_utxo,
                     amount: amount_a2,
+                    consignment_key: Some(blind_data_a2.consignment_key.clone()),
+                    transport_endpoints: vec![],
                 },
             ],
         ),
@@ -1865,15 +1925,22 @@ fn batch_donation_success() {
                 Recipient {
                     blinded_utxo: blind_data_b1.blinded_utxo,
                     amount: amount_b1,
+                    consignment_key: Some(blind_data_b1.consignment_key.clone()),
+                    transport_endpoints: vec![],
                 },
                 Recipient {
                     blinded_utxo: blind_data_b2.blinded_utxo,
                     amount: amount_b2,
+                    consignment_key: Some(blind_data_b2.consignment_key.clone()),
+                    transport_endpoints: vec![],
                 },
             ],
         ),
     ]);
-    let txid = wallet.send(online, recipient_map, true).unwrap();
+    let txid = wallet
+        .send(online, recipient_map, true, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // check change UTXO has all the expected allocations
@@ -1955,17 +2022,22 @@ fn reuse_failed_blinded_success() {
         .unwrap();
 
     // 1st transfer
-    let blind_data = rcv_wallet.blind(None, None, Some(60)).unwrap();
+    let blind_data = rcv_wallet
+        .blind(None, None, Some(60), vec![], None)
+        .unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             amount,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid = wallet
-        .send(online.clone(), recipient_map.clone(), false)
-        .unwrap();
+        .send(online.clone(), recipient_map.clone(), false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // fail transfer so asset allocation can be spent again
@@ -1974,7 +2046,9 @@ fn reuse_failed_blinded_success() {
         .unwrap();
 
     // 2nd transfer using the same blinded UTXO
-    let txid = wallet.send(online, recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap();
     assert!(!txid.is_empty());
 }
 
@@ -2001,22 +2075,29 @@ fn ack() {
         .unwrap();
 
     // send with donation set to false
-    let blind_data_1 = rcv_wallet_1.blind(None, None, None).unwrap();
-    let blind_data_2 = rcv_wallet_2.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet_1.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_2 = rcv_wallet_2.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![
             Recipient {
                 blinded_utxo: blind_data_1.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_1.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
             Recipient {
                 blinded_utxo: blind_data_2.blinded_utxo.clone(),
                 amount,
+                consignment_key: Some(blind_data_2.consignment_key.clone()),
+                transport_endpoints: vec![],
             },
         ],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // all transfers are in WaitingCounterparty status
@@ -2088,15 +2169,20 @@ fn nack() {
         .unwrap();
 
     // send with donation set to false
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo.clone(),
             amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // transfers are in WaitingCounterparty status
@@ -2148,15 +2234,20 @@ fn expire() {
         .unwrap();
 
     // send
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             amount,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online, recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // check expiration is set correctly
@@ -2164,7 +2255,7 @@ fn expire() {
     let transfer_data = wallet.database.get_transfer_data(&transfer).unwrap();
     assert_eq!(
         transfer_data.expiration,
-        Some(transfer_data.created_at + DURATION_SEND_TRANSFER)
+        Some(transfer_data.created_at + DURATION_SEND_TRANSFER as i64)
     );
 
     // manually set expiration time in the near future to speed up the test
@@ -2202,7 +2293,9 @@ fn fail() {
         )
         .unwrap();
     // blind
-    let blind_data = rcv_wallet.blind(None, None, Some(60)).unwrap();
+    let blind_data = rcv_wallet
+        .blind(None, None, Some(60), vec![], None)
+        .unwrap();
 
     // invalid input (asset id)
     let recipient_map = HashMap::from([(
@@ -2210,9 +2303,11 @@ fn fail() {
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo.clone(),
             amount: AMOUNT / 2,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online.clone(), recipient_map, false);
+    let result = wallet.send(online.clone(), recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::AssetNotFound(_))));
 
     // invalid input (blinded UTXO)
@@ -2221,20 +2316,81 @@ fn fail() {
         vec![Recipient {
             blinded_utxo: s!("invalid"),
             amount: AMOUNT / 2,
+            consignment_key: None,
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online.clone(), recipient_map, false);
+    let result = wallet.send(online.clone(), recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::InvalidBlindedUTXO(_))));
 
+    // invalid input (consignment key)
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount: AMOUNT / 2,
+            consignment_key: Some(s!("invalid")),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let result = wallet.send(online.clone(), recipient_map, false, send_options());
+    assert!(matches!(result, Err(Error::InvalidConsignmentKey(_))));
+
+    // invalid input (transport endpoint)
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount: AMOUNT / 2,
+            consignment_key: None,
+            transport_endpoints: vec![s!("invalid")],
+        }],
+    )]);
+    let result = wallet.send(online.clone(), recipient_map, false, send_options());
+    assert!(matches!(result, Err(Error::InvalidTransportEndpoint(_))));
+
+    // invalid fee rate (too low and too high)
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount: AMOUNT / 2,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let result = wallet.send(
+        online.clone(),
+        recipient_map.clone(),
+        false,
+        SendOptions {
+            fee_rate: Some(0.5),
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+    let result = wallet.send(
+        online.clone(),
+        recipient_map,
+        false,
+        SendOptions {
+            fee_rate: Some(1001.0),
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InvalidFeeRate(_))));
+
     // insufficient assets (amount too big)
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo,
             amount: AMOUNT + 1,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online, recipient_map, false);
+    let result = wallet.send(online, recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::InsufficientTotalAssets(t)) if t == asset.asset_id));
 }
 
@@ -2266,15 +2422,20 @@ fn pending_incoming_transfer_fail() {
     //
 
     // send
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount: amount_1,
             blinded_utxo: blind_data_1.blinded_utxo,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_1 = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid_1 = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -2293,27 +2454,34 @@ fn pending_incoming_transfer_fail() {
     //
 
     // send
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             amount: amount_2,
             blinded_utxo: blind_data_2.blinded_utxo,
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid_2 = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid_2 = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid_2.is_empty());
 
     // send from receiving wallet, 1st receive Settled, 2nd one still pending
-    let blind_data = wallet.blind(None, None, None).unwrap();
+    let blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo,
             amount: amount_3,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online, recipient_map, false);
+    let result = wallet.send(online, recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::InsufficientSpendableAssets(t)) if t == asset.asset_id));
 }
 
@@ -2339,27 +2507,34 @@ fn pending_outgoing_transfer_fail() {
         .unwrap();
 
     // 1st send
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo,
             amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let txid = wallet.send(online.clone(), recipient_map, false).unwrap();
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // 2nd send (1st still pending)
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo,
             amount: amount / 2,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online, recipient_map, false);
+    let result = wallet.send(online, recipient_map, false, send_options());
     assert!(matches!(result, Err(_)));
 }
 
@@ -2385,18 +2560,20 @@ fn pending_transfer_input_fail() {
         .unwrap();
 
     // blind with sender wallet to create a pending transfer
-    wallet.blind(None, None, None).unwrap();
+    wallet.blind(None, None, None, vec![], None).unwrap();
 
     // send and check it fails as the issuance UTXO is "blocked" by the pending receive operation
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
             blinded_utxo: blind_data.blinded_utxo,
             amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let result = wallet.send(online, recipient_map, false);
+    let result = wallet.send(online, recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::InsufficientSpendableAssets(t)) if t == asset.asset_id));
 }
 
@@ -2422,21 +2599,26 @@ fn already_used_fail() {
         .unwrap();
 
     // 1st transfer
-    let blind_data = rcv_wallet.blind(None, None, Some(60)).unwrap();
+    let blind_data = rcv_wallet
+        .blind(None, None, Some(60), vec![], None)
+        .unwrap();
     let recipient_map = HashMap::from([(
         asset.asset_id,
         vec![Recipient {
             amount,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
     let txid = wallet
-        .send(online.clone(), recipient_map.clone(), false)
-        .unwrap();
+        .send(online.clone(), recipient_map.clone(), false, send_options())
+        .unwrap()
+        .txid;
     assert!(!txid.is_empty());
 
     // 2nd transfer using the same blinded UTXO
-    let result = wallet.send(online, recipient_map, false);
+    let result = wallet.send(online, recipient_map, false, send_options());
     assert!(matches!(result, Err(Error::BlindedUTXOAlreadyUsed)));
 }
 
@@ -2475,7 +2657,7 @@ fn rgb121_blank_success() {
         )
         .unwrap();
 
-    let blind_data = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
 
     // try sending RGB20
     let recipient_map = HashMap::from([(
@@ -2483,9 +2665,11 @@ fn rgb121_blank_success() {
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data.blinded_utxo,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online, recipient_map, false);
+    let res = wallet.send_begin(online, recipient_map, false, send_options());
     assert!(!res.unwrap().is_empty());
 }
 
@@ -2502,8 +2686,9 @@ fn psbt_rgb_consumer_success() {
     // create 1 UTXO
     println!("utxo 1");
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), true, Some(1), None)
-        .unwrap();
+        .create_utxos(online.clone(), true, Some(1), None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
 
     // issue an RGB20 asset
@@ -2521,21 +2706,24 @@ fn psbt_rgb_consumer_success() {
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 2");
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), None)
-        .unwrap();
+        .create_utxos(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
 
     // try to send it
     println!("send_begin 1");
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20_a.asset_id,
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data_1.blinded_utxo,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online.clone(), recipient_map, false);
+    let res = wallet.send_begin(online.clone(), recipient_map, false, send_options());
     assert!(!res.unwrap().is_empty());
 
     // issue one more RGB20 asset, should go to the same UTXO as the 1st issuance
@@ -2552,22 +2740,24 @@ fn psbt_rgb_consumer_success() {
 
     // try to send the second asset
     println!("send_begin 2");
-    let blind_data_2 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_2 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20_b.asset_id.clone(),
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data_2.blinded_utxo,
+            consignment_key: Some(blind_data_2.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online.clone(), recipient_map, false);
+    let res = wallet.send_begin(online.clone(), recipient_map, false, send_options());
     assert!(!res.unwrap().is_empty());
 
     // exhaust allocations + issue 3rd asset, on a different UTXO
     println!("exhaust allocations on current UTXO");
     let new_allocation_count = (MAX_ALLOCATIONS_PER_UTXO - 2).max(0);
     for _ in 0..new_allocation_count {
-        let _blind_data = wallet.blind(None, None, None).unwrap();
+        let _blind_data = wallet.blind(None, None, None, vec![], None).unwrap();
     }
     println!("issue 3");
     let asset_rgb20_c = wallet
@@ -2585,20 +2775,23 @@ fn psbt_rgb_consumer_success() {
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 3");
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), None)
-        .unwrap();
+        .create_utxos(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
 
     // try to send the second asset to a recipient and the third to different one
     println!("send_begin 3");
-    let blind_data_3a = rcv_wallet.blind(None, None, None).unwrap();
-    let blind_data_3b = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_3a = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let blind_data_3b = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([
         (
             asset_rgb20_b.asset_id,
             vec![Recipient {
                 amount: 1,
                 blinded_utxo: blind_data_3a.blinded_utxo,
+                consignment_key: Some(blind_data_3a.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
         (
@@ -2606,10 +2799,12 @@ fn psbt_rgb_consumer_success() {
             vec![Recipient {
                 amount: 1,
                 blinded_utxo: blind_data_3b.blinded_utxo,
+                consignment_key: Some(blind_data_3b.consignment_key.clone()),
+                transport_endpoints: vec![],
             }],
         ),
     ]);
-    let res = wallet.send_begin(online, recipient_map, false);
+    let res = wallet.send_begin(online, recipient_map, false, send_options());
     assert!(!res.unwrap().is_empty());
 }
 
@@ -2625,11 +2820,17 @@ fn insufficient_bitcoins() {
 
     // create 1 UTXO with not enough bitcoins for a send and drain the rest
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), Some(tiny_btc_amount))
+        .create_utxos(
+            online.clone(),
+            false,
+            Some(1),
+            Some(tiny_btc_amount),
+            FEE_RATE,
+        )
         .unwrap();
     assert_eq!(num_utxos_created, 1);
     wallet
-        .drain_to(online.clone(), rcv_wallet.get_address(), false)
+        .drain_to(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
 
     // issue an RGB20 asset
@@ -2646,32 +2847,41 @@ fn insufficient_bitcoins() {
     // send with no colorable UTXOs available as additional bitcoin inputs and no other funds
     let unspents = wallet.list_unspents(false).unwrap();
     assert_eq!(unspents.len(), 1);
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20_a.asset_id,
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data_1.blinded_utxo,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online.clone(), recipient_map.clone(), false);
+    let res = wallet.send_begin(online.clone(), recipient_map.clone(), false, send_options());
     assert!(matches!(res, Err(Error::InsufficientBitcoins)));
 
     // create 1 UTXO for change (add funds, create UTXO, drain the rest)
     fund_wallet(wallet.get_address());
     wallet._sync_db_txos().unwrap();
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), Some(tiny_btc_amount))
-        .unwrap();
+        .create_utxos(
+            online.clone(),
+            false,
+            Some(1),
+            Some(tiny_btc_amount),
+            FEE_RATE,
+        )
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
     wallet
-        .drain_to(online.clone(), rcv_wallet.get_address(), false)
+        .drain_to(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
 
     // send with only 1 colorable UTXO available, for change
     let unspents = wallet.list_unspents(false).unwrap();
     assert_eq!(unspents.len(), 2);
-    let res = wallet.send_begin(online, recipient_map, false);
+    let res = wallet.send_begin(online, recipient_map, false, send_options());
     assert!(matches!(res, Err(Error::InsufficientBitcoins)));
 }
 
@@ -2685,7 +2895,7 @@ fn insufficient_allocations_fail() {
 
     // create 1 UTXO with not enough bitcoins for a send
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), Some(300))
+        .create_utxos(online.clone(), false, Some(1), Some(300), FEE_RATE)
         .unwrap();
     assert_eq!(num_utxos_created, 1);
 
@@ -2701,26 +2911,29 @@ fn insufficient_allocations_fail() {
         .unwrap();
 
     // send with no colorable UTXOs available as change
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20_a.asset_id,
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data_1.blinded_utxo,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online.clone(), recipient_map.clone(), false);
+    let res = wallet.send_begin(online.clone(), recipient_map.clone(), false, send_options());
     assert!(matches!(res, Err(Error::InsufficientAllocationSlots)));
 
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 2");
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), None)
-        .unwrap();
+        .create_utxos(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap()
+        .num_utxos;
     assert_eq!(num_utxos_created, 1);
 
     // send with no colorable UTXOs available as additional bitcoin inputs, uncolorable available
-    let res = wallet.send_begin(online, recipient_map, false);
+    let res = wallet.send_begin(online, recipient_map, false, send_options());
     assert!(matches!(res, Err(Error::InsufficientAllocationSlots)));
 }
 
@@ -2734,7 +2947,7 @@ fn insufficient_allocations_success() {
 
     // create 1 UTXO with not enough bitcoins for a send
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(1), Some(300))
+        .create_utxos(online.clone(), false, Some(1), Some(300), FEE_RATE)
         .unwrap();
     assert_eq!(num_utxos_created, 1);
 
@@ -2751,19 +2964,597 @@ fn insufficient_allocations_success() {
 
     // create 2 more UTXOs, 1 for change + 1 as additional bitcoin input
     let num_utxos_created = wallet
-        .create_utxos(online.clone(), false, Some(2), None)
+        .create_utxos(online.clone(), false, Some(2), None, FEE_RATE)
         .unwrap();
     assert_eq!(num_utxos_created, 2);
 
     // send with 1 colorable UTXOs available as additional bitcoin input
-    let blind_data_1 = rcv_wallet.blind(None, None, None).unwrap();
+    let blind_data_1 = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
     let recipient_map = HashMap::from([(
         asset_rgb20_a.asset_id,
         vec![Recipient {
             amount: 1,
             blinded_utxo: blind_data_1.blinded_utxo,
+            consignment_key: Some(blind_data_1.consignment_key.clone()),
+            transport_endpoints: vec![],
         }],
     )]);
-    let res = wallet.send_begin(online, recipient_map, false);
+    let res = wallet.send_begin(online, recipient_map, false, send_options());
     assert!(!res.unwrap().is_empty());
 }
+
+#[test]
+fn encrypted_consignment_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    wallet
+        .create_utxos(online.clone(), false, Some(UTXO_NUM), None, FEE_RATE)
+        .unwrap();
+    electrum.mine(1);
+
+    // issue, with an allocation for each send
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT, AMOUNT, AMOUNT],
+        )
+        .unwrap();
+    let mut send = |blinded_utxo: &str, consignment_key: Option<String>| {
+        let recipient_map = HashMap::from([(
+            asset.asset_id.clone(),
+            vec![Recipient {
+                blinded_utxo: blinded_utxo.to_string(),
+                amount,
+                consignment_key,
+                transport_endpoints: vec![],
+            }],
+        )]);
+        wallet
+            .send(online.clone(), recipient_map, false, send_options())
+            .unwrap()
+    };
+
+    // the consignment is posted encrypted with the key from the invoice
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let invoice_data = Invoice::new(blind_data.invoice.clone())
+        .unwrap()
+        .invoice_data();
+    send(&blind_data.blinded_utxo, invoice_data.consignment_key);
+    let posted = proxy.consignment(&blind_data.blinded_utxo).unwrap();
+    assert!(encryption::is_encrypted(&posted));
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), Some(true));
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+
+    // cleartext consignments are refused, as the invoice requested encryption
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    send(&blind_data.blinded_utxo, None);
+    let posted = proxy.consignment(&blind_data.blinded_utxo).unwrap();
+    assert!(!encryption::is_encrypted(&posted));
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), Some(false));
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Failed
+    ));
+
+    // consignments encrypted with a different key are refused
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    send(&blind_data.blinded_utxo, Some(encryption::generate_key()));
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), Some(false));
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Failed
+    ));
+}
+
+#[test]
+fn encrypted_media_on_mocks() {
+    let amount: u64 = 66;
+    let file_str = "README.md";
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb121(
+            online.clone(),
+            NAME.to_string(),
+            Some(DESCRIPTION.to_string()),
+            PRECISION,
+            vec![AMOUNT],
+            None,
+            Some(file_str.to_string()),
+        )
+        .unwrap();
+    let attachment_id = asset.data_paths[0]
+        .file_path
+        .split('/')
+        .rev()
+        .nth(1)
+        .unwrap()
+        .to_string();
+
+    // send
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    wallet
+        .send(online, recipient_map, false, send_options())
+        .unwrap();
+
+    // media is only posted encrypted, under an ID specific to the recipient
+    assert!(proxy.media(&attachment_id).is_none());
+    let media_id = encrypted_media_id(&attachment_id, &blind_data.blinded_utxo);
+    let posted = proxy.media(&media_id).unwrap();
+    assert!(encryption::is_encrypted(&posted));
+
+    // the recipient decrypts the media and accepts the transfer
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert_eq!(proxy.ack(&blind_data.blinded_utxo), Some(true));
+    let rcv_assets = rcv_wallet.list_assets(vec![]).unwrap();
+    let rcv_asset = &rcv_assets.rgb121.unwrap()[0];
+    let media_bytes = std::fs::read(&rcv_asset.data_paths[0].file_path).unwrap();
+    assert_eq!(media_bytes, std::fs::read(file_str).unwrap());
+}
+
+#[test]
+fn transport_endpoints_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+    let rcv_proxy = MockProxy::start();
+
+    // wallets, each using a different proxy
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &rcv_proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the invoice points the sender to the receiver's proxy
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let invoice_data = Invoice::new(blind_data.invoice).unwrap().invoice_data();
+    assert_eq!(invoice_data.transport_endpoints, vec![rcv_proxy.url()]);
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: invoice_data.consignment_key,
+            transport_endpoints: invoice_data.transport_endpoints,
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    assert!(proxy.consignment(&blind_data.blinded_utxo).is_none());
+    assert!(rcv_proxy.consignment(&blind_data.blinded_utxo).is_some());
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
+    assert_eq!(transfer.proxy_url, Some(rcv_proxy.url()));
+
+    // the protocol version of the receiver's proxy is cached by the sender
+    let getinfo_count = || {
+        rcv_proxy
+            .requests()
+            .iter()
+            .filter(|r| r.path.ends_with("getinfo"))
+            .count()
+    };
+    let getinfo_requests = getinfo_count();
+    wallet._check_proxy_version(&rcv_proxy.url()).unwrap();
+    assert_eq!(getinfo_count(), getinfo_requests);
+
+    // the receiver gets the consignment from its own proxy
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::WaitingConfirmations
+    ));
+    assert_eq!(rcv_proxy.ack(&blind_data.blinded_utxo), Some(true));
+
+    // the sender gets the ACK from the receiver's proxy
+    wallet.refresh(online, Some(asset.asset_id)).unwrap();
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::WaitingConfirmations
+    ));
+}
+
+#[test]
+fn input_selection_on_mocks() {
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue asset A on 4 UTXOs, then asset B on the one holding 10 of asset A
+    let asset_a = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![10, 20, 30, 40],
+        )
+        .unwrap();
+    let asset_b = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![5],
+        )
+        .unwrap();
+    let unspents = wallet.list_unspents(false).unwrap();
+    let merged = unspents
+        .iter()
+        .find(|u| {
+            u.rgb_allocations
+                .iter()
+                .any(|a| a.asset_id == Some(asset_b.asset_id.clone()))
+        })
+        .unwrap();
+    assert!(merged
+        .rgb_allocations
+        .iter()
+        .any(|a| a.asset_id == Some(asset_a.asset_id.clone()) && a.amount == 10));
+
+    // prepare a send of asset A and return the number of its inputs, its change and the assets
+    // moved with blank transitions
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let mut check_send = |amount: u64, input_selection: Option<InputSelection>| {
+        let recipient_map = HashMap::from([(
+            asset_a.asset_id.clone(),
+            vec![Recipient {
+                amount,
+                blinded_utxo: blind_data.blinded_utxo.clone(),
+                consignment_key: Some(blind_data.consignment_key.clone()),
+                transport_endpoints: vec![],
+            }],
+        )]);
+        let psbt = wallet
+            .send_begin(
+                online.clone(),
+                recipient_map,
+                false,
+                SendOptions {
+                    input_selection,
+                    ..send_options()
+                },
+            )
+            .unwrap();
+        let txid = PartiallySignedTransaction::from_str(&psbt)
+            .unwrap()
+            .extract_tx()
+            .txid()
+            .to_string();
+        let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR).join(txid);
+        let info_batch: InfoBatchTransfer = serde_json::from_str(
+            &fs::read_to_string(transfer_dir.join(TRANSFER_DATA_FILE)).unwrap(),
+        )
+        .unwrap();
+        let info_asset: InfoAssetTransfer = serde_json::from_str(
+            &fs::read_to_string(
+                transfer_dir
+                    .join(&asset_a.asset_id)
+                    .join(TRANSFER_DATA_FILE),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let mut blank_assets: Vec<String> = info_batch.blank_allocations.into_keys().collect();
+        blank_assets.sort();
+        (
+            info_asset.asset_spend.input_outpoints.len(),
+            info_asset.asset_spend.change_amount,
+            blank_assets,
+        )
+    };
+    let blank_b = vec![asset_b.asset_id.clone()];
+
+    // database order spends the first UTXO, moving asset B along
+    assert_eq!(check_send(10, None), (1, 0, blank_b.clone()));
+
+    // largest first
+    assert_eq!(
+        check_send(10, Some(InputSelection::LargestFirst)),
+        (1, 30, vec![])
+    );
+    assert_eq!(
+        check_send(60, Some(InputSelection::LargestFirst)),
+        (2, 10, vec![])
+    );
+
+    // smallest sufficient, falling back to largest first
+    assert_eq!(
+        check_send(25, Some(InputSelection::SmallestSufficient)),
+        (1, 5, vec![])
+    );
+    assert_eq!(
+        check_send(65, Some(InputSelection::SmallestSufficient)),
+        (2, 5, vec![])
+    );
+
+    // exact match, falling back to smallest sufficient
+    assert_eq!(
+        check_send(50, Some(InputSelection::BranchAndBound)),
+        (2, 0, blank_b.clone())
+    );
+    assert_eq!(
+        check_send(90, Some(InputSelection::BranchAndBound)),
+        (3, 0, vec![])
+    );
+    assert_eq!(
+        check_send(35, Some(InputSelection::BranchAndBound)),
+        (1, 5, vec![])
+    );
+
+    // avoid merging, using UTXOs with other assets only when needed
+    assert_eq!(
+        check_send(10, Some(InputSelection::AvoidMerging)),
+        (1, 10, vec![])
+    );
+    assert_eq!(
+        check_send(95, Some(InputSelection::AvoidMerging)),
+        (4, 5, blank_b)
+    );
+
+    // not enough of the asset with any strategy
+    let recipient_map = HashMap::from([(
+        asset_a.asset_id.clone(),
+        vec![Recipient {
+            amount: 101,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let result = wallet.send_begin(
+        online,
+        recipient_map,
+        false,
+        SendOptions {
+            input_selection: Some(InputSelection::BranchAndBound),
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InsufficientTotalAssets(_))));
+}
+
+#[test]
+fn btc_recipients_on_mocks() {
+    let amount: u64 = 66;
+    let btc_amount: u64 = 1500;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send asset and bitcoins in the same transaction
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            amount,
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let btc_address = rcv_wallet.get_address();
+    let btc_recipients = vec![BtcRecipient {
+        address: btc_address.clone(),
+        amount: btc_amount,
+    }];
+    let txid = wallet
+        .send(
+            online.clone(),
+            recipient_map.clone(),
+            false,
+            SendOptions {
+                btc_recipients,
+                ..send_options()
+            },
+        )
+        .unwrap()
+        .txid;
+
+    // the transaction pays the BTC recipient and the commitment stays in the last output
+    let transfer_dir = wallet.get_wallet_dir().join(TRANSFER_DIR).join(&txid);
+    let tx = wallet._get_signed_psbt(transfer_dir).unwrap().extract_tx();
+    let btc_script = Address::from_str(&btc_address).unwrap().script_pubkey();
+    assert!(tx
+        .output
+        .iter()
+        .any(|o| o.script_pubkey == btc_script && o.value == btc_amount));
+    assert!(tx.output.last().unwrap().script_pubkey.is_op_return());
+
+    // both sides settle
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    electrum.mine(1);
+    rcv_wallet.refresh(rcv_online, None).unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()))
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &blind_data.blinded_utxo,
+        TransferStatus::Settled
+    ));
+    assert!(check_test_transfer_status_sender(
+        &wallet,
+        &txid,
+        TransferStatus::Settled
+    ));
+    let balance = wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(balance.settled, AMOUNT - amount);
+
+    // invalid address
+    let result = wallet.send_begin(
+        online.clone(),
+        recipient_map.clone(),
+        false,
+        SendOptions {
+            btc_recipients: vec![BtcRecipient {
+                address: s!("invalid"),
+                amount: btc_amount,
+            }],
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InvalidAddress(_))));
+
+    // address for another network
+    let result = wallet.send_begin(
+        online.clone(),
+        recipient_map.clone(),
+        false,
+        SendOptions {
+            btc_recipients: vec![BtcRecipient {
+                address: s!("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"),
+                amount: btc_amount,
+            }],
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InvalidBtcRecipient(_))));
+
+    // amount below the dust limit
+    let result = wallet.send_begin(
+        online,
+        recipient_map,
+        false,
+        SendOptions {
+            btc_recipients: vec![BtcRecipient {
+                address: btc_address,
+                amount: 100,
+            }],
+            ..send_options()
+        },
+    );
+    assert!(matches!(result, Err(Error::InvalidBtcRecipient(_))));
+}
+
+#[test]
+fn refused_consignment_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets
+    let (mut wallet_1, online_1) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut wallet_2, online_2) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, _rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+
+    // issue
+    let asset_1 = wallet_1
+        .issue_asset_rgb20(
+            online_1.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let asset_2 = wallet_2
+        .issue_asset_rgb20(
+            online_2.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the first send to a blinded UTXO gets its consignment posted
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient = Recipient {
+        blinded_utxo: blind_data.blinded_utxo.clone(),
+        amount,
+        consignment_key: Some(blind_data.consignment_key.clone()),
+        transport_endpoints: vec![],
+    };
+    let recipient_map = HashMap::from([(asset_1.asset_id, vec![recipient.clone()])]);
+    wallet_1
+        .send(online_1, recipient_map, false, send_options())
+        .unwrap();
+    let consignment = proxy.consignment(&blind_data.blinded_utxo).unwrap();
+
+    // a second one is refused by the proxy and goes back to its unsigned state
+    let recipient_map = HashMap::from([(asset_2.asset_id, vec![recipient])]);
+    let unsigned_psbt = wallet_2
+        .send_begin(online_2.clone(), recipient_map, false, send_options())
+        .unwrap();
+    let mut psbt = PartiallySignedTransaction::from_str(&unsigned_psbt).unwrap();
+    wallet_2
+        .bdk_wallet
+        .sign(&mut psbt, SignOptions::default())
+        .unwrap();
+    let txid = psbt.clone().extract_tx().txid().to_string();
+    let result = wallet_2.send_end(online_2, psbt.to_string());
+    assert!(matches!(result, Err(Error::RefusedConsignment(id)) if id == blind_data.blinded_utxo));
+    assert!(get_test_batch_transfers(&wallet_2, &txid).is_empty());
+    let transfer_dir = wallet_2.get_wallet_dir().join(TRANSFER_DIR).join(txid);
+    assert!(!transfer_dir.join(SIGNED_PSBT_FILE).exists());
+    assert_eq!(
+        proxy.consignment(&blind_data.blinded_utxo).unwrap(),
+        consignment
+    );
+}
@@ -0,0 +1,149 @@
+use super::*;
+use std::sync::Mutex;
+
+#[derive(Clone, Default)]
+struct RecordingListener {
+    changes: Arc<Mutex<Vec<TransferStatusChange>>>,
+}
+
+impl RecordingListener {
+    fn take(&self) -> Vec<TransferStatusChange> {
+        self.changes.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl TransferListener for RecordingListener {
+    fn on_transfer_status_change(&self, change: TransferStatusChange) {
+        self.changes.lock().unwrap().push(change);
+    }
+}
+
+fn check_change(
+    change: &TransferStatusChange,
+    old_status: Option<TransferStatus>,
+    new_status: TransferStatus,
+) {
+    assert_eq!(change.old_status, old_status);
+    assert_eq!(change.transfer.status, new_status);
+}
+
+#[test]
+fn success_on_mocks() {
+    let amount: u64 = 66;
+    let electrum = MockElectrum::start();
+    let proxy = MockProxy::start();
+
+    // wallets, with a listener each
+    let (mut wallet, online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let (mut rcv_wallet, rcv_online) = get_funded_wallet_on_mocks(&electrum, &proxy);
+    let listener = RecordingListener::default();
+    let rcv_listener = RecordingListener::default();
+    wallet.set_transfer_listener(Box::new(listener.clone()));
+    rcv_wallet.set_transfer_listener(Box::new(rcv_listener.clone()));
+
+    // issue
+    let asset = wallet
+        .issue_asset_rgb20(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // the sender is notified of the new transfer
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            blinded_utxo: blind_data.blinded_utxo.clone(),
+            consignment_key: Some(blind_data.consignment_key.clone()),
+            transport_endpoints: vec![],
+        }],
+    )]);
+    let txid = wallet
+        .send(online.clone(), recipient_map, false, send_options())
+        .unwrap()
+        .txid;
+    let changes = listener.take();
+    assert_eq!(changes.len(), 1);
+    check_change(&changes[0], None, TransferStatus::WaitingCounterparty);
+    assert_eq!(changes[0].asset_id, Some(asset.asset_id.clone()));
+    assert_eq!(changes[0].transfer.txid, Some(txid.clone()));
+    assert!(rcv_listener.take().is_empty());
+
+    // both sides are notified when the consignment is accepted
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    let changes = rcv_listener.take();
+    assert_eq!(changes.len(), 1);
+    check_change(
+        &changes[0],
+        Some(TransferStatus::WaitingCounterparty),
+        TransferStatus::WaitingConfirmations,
+    );
+    assert_eq!(changes[0].asset_id, Some(asset.asset_id.clone()));
+    assert_eq!(
+        changes[0].transfer.blinded_utxo,
+        Some(blind_data.blinded_utxo.clone())
+    );
+    assert!(changes[0].transfer.incoming);
+    wallet.refresh(online.clone(), None).unwrap();
+    let changes = listener.take();
+    assert_eq!(changes.len(), 1);
+    check_change(
+        &changes[0],
+        Some(TransferStatus::WaitingCounterparty),
+        TransferStatus::WaitingConfirmations,
+    );
+
+    // refreshing without changes doesn't notify
+    wallet.refresh(online.clone(), None).unwrap();
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    assert!(listener.take().is_empty());
+    assert!(rcv_listener.take().is_empty());
+
+    // both sides are notified when the transfer settles
+    electrum.mine(1);
+    wallet.refresh(online, None).unwrap();
+    rcv_wallet.refresh(rcv_online.clone(), None).unwrap();
+    for changes in [listener.take(), rcv_listener.take()] {
+        assert_eq!(changes.len(), 1);
+        check_change(
+            &changes[0],
+            Some(TransferStatus::WaitingConfirmations),
+            TransferStatus::Settled,
+        );
+    }
+
+    // failing a transfer notifies
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    rcv_wallet
+        .fail_transfers(
+            rcv_online.clone(),
+            Some(blind_data.blinded_utxo.clone()),
+            None,
+        )
+        .unwrap();
+    let changes = rcv_listener.take();
+    assert_eq!(changes.len(), 1);
+    check_change(
+        &changes[0],
+        Some(TransferStatus::WaitingCounterparty),
+        TransferStatus::Failed,
+    );
+    assert_eq!(changes[0].asset_id, None);
+    assert_eq!(
+        changes[0].transfer.blinded_utxo,
+        Some(blind_data.blinded_utxo)
+    );
+
+    // no notifications after the listener has been removed
+    rcv_wallet.remove_transfer_listener();
+    let blind_data = rcv_wallet.blind(None, None, None, vec![], None).unwrap();
+    rcv_wallet
+        .fail_transfers(rcv_online, Some(blind_data.blinded_utxo), None)
+        .unwrap();
+    assert!(rcv_listener.take().is_empty());
+}